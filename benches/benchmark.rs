@@ -1,51 +1,96 @@
+use std::hint::spin_loop;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+#[cfg(not(feature = "bench"))]
+use std::thread;
+#[cfg(not(feature = "bench"))]
+use std::time::{Duration, Instant};
+
 use criterion::{criterion_group, criterion_main, Criterion};
+#[cfg(feature = "bench")]
+use rendezvous_swap::bench::time_pair;
 use rendezvous_swap::{Rendezvous, RendezvousData};
-use std::thread;
-use std::time::Instant;
+
+/// `bench::time_pair`, so that the benchmark also runs without the `bench`
+/// feature.
+#[cfg(not(feature = "bench"))]
+fn time_pair<H: Send + 'static>(
+    iterations: u64,
+    new: impl FnOnce() -> (H, H),
+    operation: fn(&mut H),
+) -> Duration {
+    let (mut mine, mut theirs) = new();
+    let handle = thread::spawn(move || {
+        for _ in 0..iterations {
+            operation(&mut theirs);
+        }
+    });
+    let start = Instant::now();
+    for _ in 0..iterations {
+        operation(&mut mine);
+    }
+    let time = start.elapsed();
+    handle.join().unwrap();
+    time
+}
+
+/// Baseline with the layout `Rendezvous` used to have: two separately
+/// allocated, unpadded counters, which may share a cache line.
+struct UnpaddedPair {
+    my_counter: Arc<AtomicUsize>,
+    their_counter: Arc<AtomicUsize>,
+    generation: usize,
+}
+impl UnpaddedPair {
+    fn new() -> (Self, Self) {
+        let (first, second) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        (
+            Self {
+                my_counter: Arc::clone(&first),
+                their_counter: Arc::clone(&second),
+                generation: 0,
+            },
+            Self {
+                my_counter: second,
+                their_counter: first,
+                generation: 0,
+            },
+        )
+    }
+
+    fn wait(&mut self) {
+        self.my_counter
+            .store(self.generation.wrapping_add(1), Ordering::Release);
+        while self.their_counter.load(Ordering::Acquire) == self.generation {
+            spin_loop();
+        }
+        self.generation = self.generation.wrapping_add(1);
+    }
+}
 
 fn bench(c: &mut Criterion) {
     c.bench_function("rendezvous swap and modify", move |b| {
         b.iter_custom(|iterations| {
-            #[inline(always)]
-            fn swap_increment(mut rendezvous: RendezvousData<i32>, iterations: u64) {
-                for _ in 0..iterations {
-                    *rendezvous.swap() += 1;
-                }
-            }
-            let (rendezvous_0, rendezvous_1) = RendezvousData::new(0, 0);
-
-            let handle = thread::spawn(move || {
-                swap_increment(rendezvous_0, iterations);
-            });
-
-            let start = Instant::now();
-            swap_increment(rendezvous_1, iterations);
-            let time = start.elapsed();
-            handle.join().unwrap();
-            time
+            time_pair(
+                iterations,
+                || RendezvousData::new(0, 0),
+                |rendezvous| *rendezvous.swap() += 1,
+            )
         })
     });
 
     c.bench_function("rendezvous", move |b| {
-        b.iter_custom(|iterations| {
-            #[inline(always)]
-            fn wait(mut rendezvous: Rendezvous, iterations: u64) {
-                for _ in 0..iterations {
-                    rendezvous.wait();
-                }
-            }
-            let (rendezvous_0, rendezvous_1) = Rendezvous::new();
-
-            let handle = thread::spawn(move || {
-                wait(rendezvous_0, iterations);
-            });
-
-            let start = Instant::now();
-            wait(rendezvous_1, iterations);
-            let time = start.elapsed();
-            handle.join().unwrap();
-            time
-        })
+        b.iter_custom(|iterations| time_pair(iterations, Rendezvous::new, Rendezvous::wait))
+    });
+
+    c.bench_function("unpadded separate counters", move |b| {
+        b.iter_custom(|iterations| time_pair(iterations, UnpaddedPair::new, UnpaddedPair::wait))
+    });
+
+    c.bench_function("rendezvous new", move |b| b.iter(Rendezvous::new));
+
+    c.bench_function("unpadded separate counters new", move |b| {
+        b.iter(UnpaddedPair::new)
     });
 }
 
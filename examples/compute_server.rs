@@ -0,0 +1,27 @@
+//! The "ping-pong compute server" pattern: a worker thread computes a
+//! response for every request, with the buffers swapped between the threads.
+
+use rendezvous_swap::worker::ComputeWorker;
+use std::time::Instant;
+
+/// Number of requests to send.
+const CALLS: u32 = 100_000;
+
+fn main() {
+    let mut worker = ComputeWorker::spawn(|request: &mut Vec<f64>, response: &mut f64| {
+        *response = request.iter().map(|x| x * x).sum::<f64>().sqrt();
+    });
+    let start = Instant::now();
+    let mut total = 0.0;
+    for i in 0..CALLS {
+        let request = worker.request();
+        request.clear();
+        request.extend([f64::from(i), 1.0, 2.0]);
+        total += *worker.call();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{CALLS} calls in {elapsed:?} ({:?} per call), total {total:.1}",
+        elapsed / CALLS
+    );
+}
@@ -0,0 +1,232 @@
+//! Contains [`Arena`] and [`WithArena`]
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::cell::{Cell, UnsafeCell};
+use core::mem::MaybeUninit;
+use core::ptr::NonNull;
+
+use crate::{SwapPoint, SyncPoint};
+
+/// Size of the first chunk of an [`Arena`], in bytes.
+const MIN_CHUNK: usize = 1 << 10;
+
+/// Bump allocator for temporaries that live for exactly one generation, see
+/// [`WithArena`].
+///
+/// Allocating only bumps an offset, and all allocations are freed at once
+/// when the arena is reset. Only [`Copy`] types can be allocated, since
+/// destructors never run.
+#[non_exhaustive]
+pub struct Arena {
+    /// Chunks allocated as `Box<[MaybeUninit<u8>]>`, the last one is being
+    /// allocated from. Raw pointers, so that handing out a new allocation
+    /// never invalidates the previous ones.
+    chunks: UnsafeCell<Vec<NonNull<[MaybeUninit<u8>]>>>,
+    /// Bytes used in the last chunk
+    used: Cell<usize>,
+}
+// SAFETY:
+// The arena owns its chunks, sending it sends them.
+unsafe impl Send for Arena {}
+impl Arena {
+    /// Create an empty arena, memory is allocated on first use.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            chunks: UnsafeCell::new(Vec::new()),
+            used: Cell::new(0),
+        }
+    }
+
+    /// Move `value` into the arena.
+    #[inline]
+    #[allow(clippy::mut_from_ref)] // every call returns fresh memory
+    pub fn alloc<U: Copy>(&self, value: U) -> &mut U {
+        let ptr = self.alloc_layout(Layout::new::<U>()).cast::<U>();
+        // SAFETY:
+        // The memory is fresh, aligned and large enough for `U`.
+        unsafe {
+            ptr.as_ptr().write(value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Copy `values` into the arena.
+    #[inline]
+    #[allow(clippy::mut_from_ref)] // every call returns fresh memory
+    pub fn alloc_slice<U: Copy>(&self, values: &[U]) -> &mut [U] {
+        let ptr = self.alloc_layout(Layout::for_value(values)).cast::<U>();
+        // SAFETY:
+        // The memory is fresh, aligned and large enough for `values`.
+        unsafe {
+            ptr.as_ptr()
+                .copy_from_nonoverlapping(values.as_ptr(), values.len());
+            core::slice::from_raw_parts_mut(ptr.as_ptr(), values.len())
+        }
+    }
+
+    /// Free all allocations, keeping the largest chunk for reuse.
+    #[inline]
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        let last = chunks.pop();
+        chunks.drain(..).for_each(free);
+        chunks.extend(last);
+        self.used.set(0);
+    }
+
+    /// Number of bytes the arena has allocated from the global allocator.
+    #[must_use]
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        // SAFETY:
+        // No reference to the vector escapes this arena, and arena methods
+        // do not call each other while holding one.
+        let chunks = unsafe { &*self.chunks.get() };
+        chunks.iter().map(|chunk| chunk.len()).sum()
+    }
+
+    /// Find room for `layout`, allocating a new chunk if needed.
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        // SAFETY:
+        // See `capacity`.
+        let chunks = unsafe { &mut *self.chunks.get() };
+        if let Some(chunk) = chunks.last() {
+            let start = chunk.cast::<u8>();
+            let used = self.used.get();
+            let offset = used.wrapping_add(
+                start
+                    .as_ptr()
+                    .wrapping_add(used)
+                    .align_offset(layout.align()),
+            );
+            if offset <= chunk.len() && layout.size() <= chunk.len() - offset {
+                self.used.set(offset + layout.size());
+                // SAFETY:
+                // In bounds of the chunk, checked above.
+                return unsafe { start.add(offset) };
+            }
+        }
+        let size = (layout.size() + layout.align())
+            .max(chunks.last().map_or(MIN_CHUNK, |chunk| chunk.len() * 2));
+        let chunk = NonNull::from(Box::leak(Box::<[u8]>::new_uninit_slice(size)));
+        let start = chunk.cast::<u8>();
+        let offset = start.as_ptr().align_offset(layout.align());
+        chunks.push(chunk);
+        self.used.set(offset + layout.size());
+        // SAFETY:
+        // The chunk has room for the padding and `layout`.
+        unsafe { start.add(offset) }
+    }
+}
+impl Default for Arena {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Drop for Arena {
+    fn drop(&mut self) {
+        self.chunks.get_mut().drain(..).for_each(free);
+    }
+}
+
+/// Free a chunk of an [`Arena`].
+fn free(chunk: NonNull<[MaybeUninit<u8>]>) {
+    // SAFETY:
+    // Chunks are allocated with `Box` in `Arena::alloc_layout`, and freed
+    // once.
+    drop(unsafe { Box::from_raw(chunk.as_ptr()) });
+}
+
+/// A sync point with an [`Arena`] for temporaries whose lifetime is exactly
+/// one generation.
+///
+/// The arena is reset at every sync. Allocations borrow the handle, so the
+/// compiler rejects any temporary that would outlive its generation, and
+/// nothing allocated in the arena can leak into the next one.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{RendezvousData, WithArena};
+/// use std::thread;
+///
+/// let (my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+/// let handle = thread::spawn(move || {
+///     for i in 1..=10 {
+///         *their_rendezvous.swap() = i;
+///     }
+///     their_rendezvous.swap();
+/// });
+/// let mut my_rendezvous = WithArena::new(my_rendezvous);
+/// for _ in 0..=10 {
+///     let (data, arena) = my_rendezvous.swap();
+///     let squares = arena.alloc_slice(&[*data; 4]);
+///     squares.iter_mut().for_each(|x| *x *= *x);
+/// }
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct WithArena<S> {
+    /// The sync point
+    inner: S,
+    /// Temporaries of the current generation
+    arena: Arena,
+}
+impl<S: SyncPoint> WithArena<S> {
+    /// Attach an empty arena to `inner`.
+    #[must_use]
+    #[inline]
+    pub const fn new(inner: S) -> Self {
+        Self {
+            inner,
+            arena: Arena::new(),
+        }
+    }
+
+    /// Sync, then reset and get the arena for the new generation.
+    #[inline]
+    pub fn sync(&mut self) -> &Arena {
+        self.inner.sync();
+        self.arena.reset();
+        &self.arena
+    }
+
+    /// The arena of the current generation.
+    #[must_use]
+    #[inline]
+    pub const fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    /// The sync point.
+    #[must_use]
+    #[inline]
+    pub const fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Take the sync point back, freeing the arena.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+impl<S: SwapPoint> WithArena<S> {
+    /// Swap, then reset and get the arena for the new generation.
+    #[inline]
+    pub fn swap(&mut self) -> (&mut S::Data, &Arena) {
+        let data = self.inner.swap();
+        self.arena.reset();
+        (data, &self.arena)
+    }
+}
+impl<S: SyncPoint> SyncPoint for WithArena<S> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = Self::sync(self);
+    }
+}
@@ -0,0 +1,97 @@
+//! Contains [`AtomicCounts`]
+//!
+//! Counts the atomic operations performed by the calling thread, to verify
+//! that the hot path stays at a single store and a single load per sync, with
+//! no read-modify-write (CAS) operations at all. The parking slow path of the
+//! `parking` feature and the checks of the `checked` feature are not counted.
+//! The CAS of a waker arriving on behalf of a handle is counted, but is not
+//! part of a sync, see [`Rendezvous::waker`](crate::Rendezvous::waker).
+
+use core::cell::Cell;
+
+crate::os::std::thread_local! {
+    /// Counts of the current thread
+    static COUNTS: Cell<AtomicCounts> = const { Cell::new(AtomicCounts::new()) };
+}
+
+/// Number of atomic operations performed by a thread.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AtomicCounts {
+    /// Atomic loads
+    pub loads: u64,
+    /// Atomic stores
+    pub stores: u64,
+    /// Atomic read-modify-write operations (including CAS)
+    pub read_modify_writes: u64,
+    /// Fences
+    pub fences: u64,
+}
+impl AtomicCounts {
+    /// No operations
+    const fn new() -> Self {
+        Self {
+            loads: 0,
+            stores: 0,
+            read_modify_writes: 0,
+            fences: 0,
+        }
+    }
+}
+
+/// Take the counts of the current thread, resetting them to zero.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::atomic_counts;
+/// use rendezvous_swap::Rendezvous;
+/// use std::thread;
+///
+/// use std::sync::atomic::Ordering;
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+/// let handle = thread::spawn(move || their_rendezvous.wait());
+/// let (_, theirs) = my_rendezvous.counter_ptrs();
+/// // SAFETY: the pointer is valid while `my_rendezvous` is alive
+/// while unsafe { &*theirs }.load(Ordering::Acquire) == 0 {}
+///
+/// atomic_counts::take();
+/// my_rendezvous.wait(); // the other thread already arrived
+/// let counts = atomic_counts::take();
+/// assert_eq!((counts.loads, counts.stores, counts.read_modify_writes), (1, 1, 0));
+/// # handle.join().unwrap();
+/// ```
+#[must_use]
+#[inline]
+pub fn take() -> AtomicCounts {
+    COUNTS.with(|counts| counts.replace(AtomicCounts::new()))
+}
+
+/// Apply `f` to the counts of the current thread.
+fn record(f: impl FnOnce(&mut AtomicCounts)) {
+    COUNTS.with(|counts| {
+        let mut value = counts.get();
+        f(&mut value);
+        counts.set(value);
+    });
+}
+
+/// Count a load.
+pub(crate) fn record_load() {
+    record(|counts| counts.loads = counts.loads.wrapping_add(1));
+}
+
+/// Count a store.
+pub(crate) fn record_store() {
+    record(|counts| counts.stores = counts.stores.wrapping_add(1));
+}
+
+/// Count a read-modify-write operation.
+pub(crate) fn record_read_modify_write() {
+    record(|counts| counts.read_modify_writes = counts.read_modify_writes.wrapping_add(1));
+}
+
+/// Count a fence.
+#[cfg(feature = "explicit-fences")]
+pub(crate) fn record_fence() {
+    record(|counts| counts.fences = counts.fences.wrapping_add(1));
+}
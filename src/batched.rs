@@ -0,0 +1,255 @@
+//! Contains [`BatchProducer`] and [`BatchConsumer`]
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::hint::spin_loop;
+
+#[cfg(feature = "std")]
+use crate::contention::{Contention, ContentionStats};
+use crate::core_cell::SlotCell;
+use crate::counter::Counter;
+use crate::padded::Padded;
+
+/// Shared between a [`BatchProducer`] and its [`BatchConsumer`].
+struct BatchShared<T: Send> {
+    /// Number of buffers published by the producer
+    published: Padded<Counter>,
+    /// Number of buffers received by the consumer
+    received: Padded<Counter>,
+    /// The pool of buffers
+    /// The pool of buffers, each slot is only accessed by the thread that
+    /// currently owns it according to the counters
+    slots: Box<[Padded<SlotCell<T>>]>,
+}
+
+/// Producing side of a pool of buffers, a hybrid between
+/// [`RendezvousData`](crate::RendezvousData) and a bounded queue.
+///
+/// The producer fills a buffer and publishes it, and immediately gets the
+/// next free buffer from the pool, so a bursty producer can run up to
+/// `pool size - 2` buffers ahead of a slow consumer before it has to wait.
+/// The consumer receives the buffers in the order they were published.
+/// With a pool of two buffers this behaves like a swap where only the
+/// producer waits.
+///
+/// Buffers are never moved or cleared, so a buffer returned by
+/// [`BatchProducer::publish`] contains whatever was last written to it.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::BatchProducer;
+/// use std::thread;
+///
+/// let (mut producer, mut consumer) = BatchProducer::new(vec![0; 4]);
+/// let handle = thread::spawn(move || {
+///     for i in 1..=10 {
+///         *producer.current() = i;
+///         producer.publish();
+///     }
+/// });
+/// for i in 1..=10 {
+///     assert_eq!(*consumer.receive(), i);
+/// }
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct BatchProducer<T: Send> {
+    /// Number of buffers published
+    published: usize,
+    /// Index of the buffer being filled
+    slot: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// Shared counters and buffers
+    shared: Arc<BatchShared<T>>,
+}
+
+/// Consuming side of a pool of buffers, see [`BatchProducer`].
+#[non_exhaustive]
+pub struct BatchConsumer<T: Send> {
+    /// Number of buffers received
+    received: usize,
+    /// Index of the buffer last received
+    slot: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// Shared counters and buffers
+    shared: Arc<BatchShared<T>>,
+}
+
+impl<T: Send> BatchProducer<T> {
+    /// Create a linked producer and consumer from a pool of buffers.
+    ///
+    /// The producer starts out filling the first buffer, and the consumer
+    /// starts out holding the last.
+    ///
+    /// # Panics
+    /// If the pool has fewer than two buffers.
+    #[must_use]
+    #[inline]
+    pub fn new(pool: Vec<T>) -> (Self, BatchConsumer<T>) {
+        assert!(pool.len() >= 2, "pool needs at least two buffers");
+        let last = pool.len().wrapping_sub(1);
+        let shared = Arc::new(BatchShared {
+            published: Padded::new(Counter::new()),
+            received: Padded::new(Counter::new()),
+            slots: pool
+                .into_iter()
+                .map(|data| Padded::new(SlotCell::new(data)))
+                .collect(),
+        });
+        (
+            Self {
+                published: 0,
+                slot: 0,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                shared: Arc::clone(&shared),
+            },
+            BatchConsumer {
+                received: 0,
+                slot: last,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                shared,
+            },
+        )
+    }
+
+    /// Get a mutable reference to the buffer being filled.
+    #[must_use]
+    #[inline]
+    pub fn current(&mut self) -> &mut T {
+        // SAFETY:
+        // The buffer at `slot` is owned by the producer until it is published.
+        unsafe { self.shared.slots[self.slot].get_mut() }
+    }
+
+    /// Publish the buffer being filled and get the next free buffer, waiting
+    /// for the consumer only if the pool is exhausted.
+    #[inline]
+    pub fn publish(&mut self) -> &mut T {
+        let published = self.published.wrapping_add(1);
+        let shared = &*self.shared;
+        shared.published.store(published);
+        self.published = published;
+        // The consumer holds one buffer and we hold one, so at most
+        // `len - 2` buffers can be queued.
+        let max_queued = shared.slots.len().wrapping_sub(2);
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        while {
+            spin_loop();
+            published.wrapping_sub(shared.received.load()) > max_queued
+        } {
+            #[cfg(feature = "std")]
+            if self.contention.spin(&mut spins) {
+                shared.received.block(shared.received.load());
+            }
+        }
+        shared.received.acquire();
+        #[cfg(feature = "std")]
+        self.contention.finish(spins);
+        self.slot = next_slot(self.slot, shared.slots.len());
+        self.current()
+    }
+
+    /// Number of published buffers the consumer has not received yet.
+    #[must_use]
+    #[inline]
+    pub fn queued(&self) -> usize {
+        self.published.wrapping_sub(self.shared.received.load())
+    }
+
+    /// Statistics on how often [`BatchProducer::publish`] gave up spinning
+    /// and yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+}
+
+impl<T: Send> BatchConsumer<T> {
+    /// Get a mutable reference to the buffer last received.
+    #[must_use]
+    #[inline]
+    pub fn current(&mut self) -> &mut T {
+        // SAFETY:
+        // The buffer at `slot` is owned by the consumer until it receives the
+        // next one.
+        unsafe { self.shared.slots[self.slot].get_mut() }
+    }
+
+    /// Give back the buffer last received, and wait for the next published
+    /// buffer.
+    #[inline]
+    pub fn receive(&mut self) -> &mut T {
+        let shared = &*self.shared;
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        while {
+            spin_loop();
+            shared.published.load() == self.received
+        } {
+            #[cfg(feature = "std")]
+            if self.contention.spin(&mut spins) {
+                shared.published.block(self.received);
+            }
+        }
+        shared.published.acquire();
+        #[cfg(feature = "std")]
+        self.contention.finish(spins);
+        self.advance()
+    }
+
+    /// Receive the next published buffer if there is one, without waiting.
+    #[must_use]
+    #[inline]
+    pub fn try_receive(&mut self) -> Option<&mut T> {
+        if self.shared.published.load() == self.received {
+            return None;
+        }
+        self.shared.published.acquire();
+        Some(self.advance())
+    }
+
+    /// Number of published buffers that have not been received yet.
+    #[must_use]
+    #[inline]
+    pub fn queued(&self) -> usize {
+        self.shared.published.load().wrapping_sub(self.received)
+    }
+
+    /// Statistics on how often [`BatchConsumer::receive`] gave up spinning
+    /// and yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+
+    /// Hand the buffer last received back to the producer, and take the next
+    /// one, which must have been published.
+    fn advance(&mut self) -> &mut T {
+        let received = self.received.wrapping_add(1);
+        self.shared.received.store(received);
+        self.received = received;
+        self.slot = next_slot(self.slot, self.shared.slots.len());
+        self.current()
+    }
+}
+
+/// Index of the buffer after `slot` in a pool of `len` buffers.
+const fn next_slot(slot: usize, len: usize) -> usize {
+    let next = slot.wrapping_add(1);
+    if next == len {
+        0
+    } else {
+        next
+    }
+}
@@ -0,0 +1,306 @@
+//! Contains [`measure_pair`], [`LatencyReport`], [`PingPongMeter`] and
+//! [`time_pair`]
+
+use crate::os::std::thread;
+use crate::os::std::time::Instant;
+use alloc::vec::Vec;
+use core::hint::black_box;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::time::Duration;
+
+use crate::{Rendezvous, RendezvousData, SwapPoint, SyncPoint};
+
+/// Number of samples taken by [`measure_pair`].
+pub const DEFAULT_SAMPLES: usize = 10_000;
+
+/// Latency percentiles of one operation.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Latency {
+    /// Median latency
+    pub p50: Duration,
+    /// 99th percentile latency
+    pub p99: Duration,
+}
+impl Latency {
+    /// Percentiles of `samples`, which are sorted in the process.
+    fn from_samples(samples: &mut [Duration]) -> Self {
+        samples.sort_unstable();
+        let percentile = |p: usize| {
+            samples
+                .get((samples.len().saturating_sub(1)).saturating_mul(p) / 100)
+                .copied()
+                .unwrap_or_default()
+        };
+        Self {
+            p50: percentile(50),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// Round-trip latencies of a pair measured by [`measure_pair`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// Latency of [`Rendezvous::wait`]
+    pub wait: Latency,
+    /// Latency of [`RendezvousData::swap`]
+    pub swap: Latency,
+    /// Number of samples per operation
+    pub samples: usize,
+}
+
+/// Measure wait and swap latency on this machine, with the enabled features.
+///
+/// Runs a ping-pong between two spawned threads, where one thread times every
+/// operation. The overhead of reading the clock is measured first and
+/// subtracted from every sample. Intended to validate the configuration of a
+/// deployment at startup.
+/// # Example
+/// ```rust
+/// let report = rendezvous_swap::bench::measure_pair();
+/// assert!(report.wait.p50 <= report.wait.p99);
+/// ```
+#[must_use]
+#[inline]
+pub fn measure_pair() -> LatencyReport {
+    measure_pair_with(DEFAULT_SAMPLES)
+}
+
+/// [`measure_pair`] with a custom number of samples.
+#[must_use]
+#[inline]
+pub fn measure_pair_with(samples: usize) -> LatencyReport {
+    let overhead = clock_overhead();
+    LatencyReport {
+        wait: measure(samples, overhead, Rendezvous::new, Rendezvous::wait),
+        swap: measure(
+            samples,
+            overhead,
+            || RendezvousData::new(0_u64, 0_u64),
+            |rendezvous| {
+                *rendezvous.swap() += 1;
+            },
+        ),
+        samples,
+    }
+}
+
+/// Extra time a read takes, beyond reading the clock, when the cache line
+/// has to be fetched from another core.
+pub const REMOTE_LINE_THRESHOLD: Duration = Duration::from_nanos(30);
+
+/// Counts how often the first cache line of the data received by a swap was
+/// still in the cache of the other core, which costs a cross-core transfer
+/// on first access.
+///
+/// The first byte of the received data is read and timed after every swap,
+/// slow reads are counted as remote (this is an approximation, a read from
+/// memory is slow too). If most reads are remote and the data is small,
+/// copying the data at the sync point may beat swapping pointers, since the
+/// transfer happens anyway; if few are, pointer swapping is free.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::bench::PingPongMeter;
+/// use rendezvous_swap::RendezvousData;
+/// use std::thread;
+///
+/// let (my_rendezvous, mut their_rendezvous) = RendezvousData::new([0_u8; 64], [0; 64]);
+/// let handle = thread::spawn(move || {
+///     for i in 0..1000 {
+///         their_rendezvous.swap()[0] = i as u8;
+///     }
+/// });
+/// let mut meter = PingPongMeter::new(my_rendezvous);
+/// for _ in 0..1000 {
+///     meter.swap()[1] = 1;
+/// }
+/// # handle.join().unwrap();
+/// let stats = meter.stats();
+/// assert_eq!(stats.swaps, 1000);
+/// println!("{} of {} lines were remote", stats.remote_lines, stats.swaps);
+/// ```
+#[non_exhaustive]
+pub struct PingPongMeter<S> {
+    /// The measured handle
+    inner: S,
+    /// Reads slower than this are counted as remote
+    threshold: Duration,
+    /// Statistics so far
+    stats: PingPongStats,
+}
+
+/// Counts of a [`PingPongMeter`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PingPongStats {
+    /// Number of swaps measured.
+    pub swaps: u64,
+    /// Number of swaps where the first line of the received data was
+    /// (likely) in another core's cache.
+    pub remote_lines: u64,
+}
+
+impl<S: SwapPoint> PingPongMeter<S> {
+    /// Measure the swaps of `inner`, with a threshold of the clock overhead
+    /// plus [`REMOTE_LINE_THRESHOLD`].
+    #[must_use]
+    #[inline]
+    pub fn new(inner: S) -> Self {
+        Self::with_threshold(
+            inner,
+            clock_overhead().saturating_add(REMOTE_LINE_THRESHOLD),
+        )
+    }
+
+    /// Measure the swaps of `inner`, counting reads slower than `threshold`
+    /// (including reading the clock) as remote.
+    #[must_use]
+    #[inline]
+    pub const fn with_threshold(inner: S, threshold: Duration) -> Self {
+        Self {
+            inner,
+            threshold,
+            stats: PingPongStats {
+                swaps: 0,
+                remote_lines: 0,
+            },
+        }
+    }
+
+    /// Swap, and time the first read of the received data.
+    #[inline]
+    pub fn swap(&mut self) -> &mut S::Data {
+        let data = self.inner.swap();
+        if size_of_val(data) > 0 {
+            let start = Instant::now();
+            // SAFETY:
+            // The data is at least one byte, which may be padding, hence
+            // `MaybeUninit`.
+            black_box(unsafe { ptr::read_volatile(ptr::from_mut(data).cast::<MaybeUninit<u8>>()) });
+            if start.elapsed() > self.threshold {
+                self.stats.remote_lines = self.stats.remote_lines.wrapping_add(1);
+            }
+        }
+        self.stats.swaps = self.stats.swaps.wrapping_add(1);
+        data
+    }
+
+    /// Counts so far.
+    #[must_use]
+    #[inline]
+    pub const fn stats(&self) -> PingPongStats {
+        self.stats
+    }
+
+    /// Take the handle back.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+impl<S: SwapPoint> SyncPoint for PingPongMeter<S> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.swap();
+    }
+}
+
+/// Median time it takes to read the clock twice.
+fn clock_overhead() -> Duration {
+    let mut samples: Vec<Duration> = (0..1000).map(|_| Instant::now().elapsed()).collect();
+    Latency::from_samples(&mut samples).p50
+}
+
+/// Time `iterations` calls of `operation` on one handle of a pair created
+/// by `new`, while a spawned thread calls it on the other handle.
+///
+/// The reusable part of a pair benchmark: the signature fits Criterion's
+/// `iter_custom`, so benchmarking a payload type or wait strategy (any
+/// handle type, such as [`FailoverRendezvous`](crate::FailoverRendezvous))
+/// is one call. Spawning the thread is not timed. Divide by `iterations`
+/// to compare with [`REFERENCE_SWAP_LATENCY`].
+/// # Example
+/// ```rust,no_run
+/// use criterion::Criterion;
+/// use rendezvous_swap::bench::time_pair;
+/// use rendezvous_swap::RendezvousData;
+///
+/// let mut criterion = Criterion::default();
+/// criterion.bench_function("swap [u64; 8]", |b| {
+///     b.iter_custom(|iterations| {
+///         time_pair(
+///             iterations,
+///             || RendezvousData::new([0_u64; 8], [0; 8]),
+///             |rendezvous| rendezvous.swap()[0] += 1,
+///         )
+///     });
+/// });
+/// ```
+#[must_use]
+#[inline]
+pub fn time_pair<H: Send + 'static>(
+    iterations: u64,
+    new: impl FnOnce() -> (H, H),
+    operation: fn(&mut H),
+) -> Duration {
+    with_partner(iterations, new, operation, |mine| {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            operation(mine);
+        }
+        start.elapsed()
+    })
+}
+
+/// Latency of a [`RendezvousData::swap`] of a small payload measured by the
+/// authors, on an `i5-7200U`, for comparison with [`time_pair`] and
+/// [`measure_pair`] on other hardware.
+pub const REFERENCE_SWAP_LATENCY: Duration = Duration::from_nanos(100);
+
+/// Run `timed` on one handle of a pair created by `new`, while a spawned
+/// thread calls `operation` `iterations` times on the other handle.
+fn with_partner<H: Send + 'static, R>(
+    iterations: u64,
+    new: impl FnOnce() -> (H, H),
+    operation: fn(&mut H),
+    timed: impl FnOnce(&mut H) -> R,
+) -> R {
+    let (mut mine, mut theirs) = new();
+    let handle = thread::spawn(move || {
+        for _ in 0..iterations {
+            operation(&mut theirs);
+        }
+    });
+    let result = timed(&mut mine);
+    #[allow(clippy::expect_used)]
+    handle.join().expect("benchmark thread panicked");
+    result
+}
+
+/// Time `samples` calls of `operation` while another thread performs it too.
+/// A warm-up of the same length is run first.
+fn measure<H: Send + 'static>(
+    samples: usize,
+    overhead: Duration,
+    new: impl FnOnce() -> (H, H),
+    operation: fn(&mut H),
+) -> Latency {
+    let total = u64::try_from(samples.saturating_mul(2)).unwrap_or(u64::MAX);
+    let mut times = with_partner(total, new, operation, |mine| {
+        for _ in 0..samples {
+            operation(mine);
+        }
+        (0..samples)
+            .map(|_| {
+                let start = Instant::now();
+                operation(mine);
+                start.elapsed().saturating_sub(overhead)
+            })
+            .collect::<Vec<Duration>>()
+    });
+    Latency::from_samples(&mut times)
+}
@@ -0,0 +1,256 @@
+//! Contains [`RendezvousDataRef`], [`RendezvousRef`] and
+//! [`RendezvousDataStorage`]
+//!
+//! The only pair that does not need an allocator: the shared state is
+//! provided by the user (on the stack, in a static, in an arena) and
+//! borrowed by both handles.
+
+#[cfg(feature = "std")]
+use crate::contention::Contention;
+use crate::core_cell::SlotCell;
+use crate::counter::Counter;
+use crate::padded::Padded;
+use crate::pair_id::PairId;
+use crate::strict::Op;
+use crate::sync::const_unless_loom;
+#[cfg(feature = "stats")]
+use crate::WaitStats;
+use crate::{SwapPoint, SyncPoint};
+
+/// Shared state of a [`RendezvousDataRef`] pair: the counters and the two
+/// buffers.
+///
+/// Can be created in a `const` context, and be reused for a new pair once
+/// the handles of the previous one are gone.
+#[non_exhaustive]
+pub struct RendezvousDataStorage<T: Send> {
+    /// Counters of the first and second handle
+    counters: [Padded<Counter>; 2],
+    /// Shared data
+    slots: [Padded<SlotCell<T>>; 2],
+}
+impl<T: Send> RendezvousDataStorage<T> {
+    const_unless_loom! {
+        /// Storage with the initial values of the buffers, the first handle
+        /// starts out with `data1` and the second with `data2`.
+        #[must_use]
+        #[inline]
+        pub fn new(data1: T, data2: T) -> Self {
+            Self {
+                counters: [Padded::new(Counter::new()), Padded::new(Counter::new())],
+                slots: [
+                    Padded::new(SlotCell::new(data1)),
+                    Padded::new(SlotCell::new(data2)),
+                ],
+            }
+        }
+    }
+
+    /// Take the buffers out, the buffer of the first handle first.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> (T, T) {
+        let [first, second] = self.slots.map(|slot| slot.into_inner().into_inner());
+        (first, second)
+    }
+}
+
+/// [`RendezvousData`](crate::RendezvousData) on shared state the handles
+/// borrow, for targets without an allocator, created by
+/// [`RendezvousDataRef::new_in`].
+///
+/// Works like [`RendezvousData`](crate::RendezvousData), including arriving
+/// at the next swap when dropped, but only provides the swap itself.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{RendezvousDataRef, RendezvousDataStorage};
+/// use std::thread;
+///
+/// let mut storage = RendezvousDataStorage::new(0, 0);
+/// let (mut my_rendezvous, mut their_rendezvous) = RendezvousDataRef::new_in(&mut storage);
+/// thread::scope(|scope| {
+///     scope.spawn(move || {
+///         *their_rendezvous.swap() = 3;
+///         their_rendezvous.swap();
+///     });
+///     my_rendezvous.swap();
+///     assert_eq!(*my_rendezvous.swap(), 3);
+/// });
+/// ```
+#[non_exhaustive]
+pub struct RendezvousDataRef<'storage, T: Send> {
+    /// Thread local generation
+    generation: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// Index of the slot this thread currently owns
+    slot: usize,
+    /// True for the first handle of the pair, which uses the first counter
+    first: bool,
+    /// Counters and data
+    storage: &'storage RendezvousDataStorage<T>,
+}
+impl<'storage, T: Send> RendezvousDataRef<'storage, T> {
+    /// Create a linked pair on `storage`, which stays borrowed while
+    /// either handle exists.
+    ///
+    /// The counters are reset, so storage left behind by an earlier pair
+    /// can be reused, the buffers keep their contents.
+    #[must_use]
+    #[inline]
+    pub fn new_in(storage: &'storage mut RendezvousDataStorage<T>) -> (Self, Self) {
+        let pair = PairId::next();
+        storage
+            .counters
+            .iter_mut()
+            .for_each(|counter| **counter = Counter::for_pair(pair));
+        Self::from_fresh(storage)
+    }
+
+    /// Create a linked pair on `storage`, which no other pair has used.
+    pub(crate) fn from_fresh(storage: &'storage RendezvousDataStorage<T>) -> (Self, Self) {
+        (
+            Self {
+                generation: 0,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                slot: 0,
+                first: true,
+                storage,
+            },
+            Self {
+                generation: 0,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                slot: 1,
+                first: false,
+                storage,
+            },
+        )
+    }
+
+    /// Swap data with other thread and get a mutable reference to the data,
+    /// see [`RendezvousDataN::swap`](crate::RendezvousDataN::swap).
+    #[inline]
+    pub fn swap(&mut self) -> &mut T {
+        let next_generation = self.generation.wrapping_add(1);
+        let (mine, theirs) = (self.my_counter(), self.their_counter());
+        mine.tag(next_generation, Op::Swap);
+        mine.store(next_generation);
+        theirs.wait_past(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+        );
+        theirs.check(next_generation, Op::Swap, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+        self.slot ^= 1;
+        // SAFETY:
+        // The other thread handed this slot over when it arrived at this
+        // swap, which `wait_past` observed, and only accesses it again
+        // after this thread arrives at the next swap.
+        unsafe { self.storage.slots[self.slot].get_mut() }
+    }
+
+    /// Spins and time of the waits of this thread, see
+    /// [`RendezvousDataN::wait_stats`](crate::RendezvousDataN::wait_stats).
+    #[cfg(feature = "stats")]
+    #[must_use]
+    #[inline]
+    pub fn wait_stats(&self) -> WaitStats {
+        self.my_counter().wait_stats()
+    }
+
+    /// [`RendezvousDataRef::wait_stats`] of the other thread.
+    #[cfg(feature = "stats")]
+    #[must_use]
+    #[inline]
+    pub fn peer_wait_stats(&self) -> WaitStats {
+        self.their_counter().wait_stats()
+    }
+
+    /// Atomic counter for this thread
+    fn my_counter(&self) -> &'storage Counter {
+        &self.storage.counters[usize::from(!self.first)]
+    }
+
+    /// Atomic counter for other thread
+    fn their_counter(&self) -> &'storage Counter {
+        &self.storage.counters[usize::from(self.first)]
+    }
+}
+impl<T: Send> Drop for RendezvousDataRef<'_, T> {
+    /// Arrive at the next swap without waiting, see
+    /// [`RendezvousDataN`](crate::RendezvousDataN).
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        if crate::os::std::thread::panicking() {
+            self.my_counter().hang_up();
+            return;
+        }
+        self.my_counter()
+            .detach(self.generation.wrapping_add(1), Op::Swap);
+    }
+}
+impl<T: Send> SyncPoint for RendezvousDataRef<'_, T> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = RendezvousDataRef::swap(self);
+    }
+}
+impl<T: Send> SwapPoint for RendezvousDataRef<'_, T> {
+    type Data = T;
+    #[inline]
+    fn swap(&mut self) -> &mut T {
+        RendezvousDataRef::swap(self)
+    }
+}
+
+/// [`Rendezvous`](crate::Rendezvous) on shared state the handles borrow,
+/// for targets without an allocator, see
+/// [`StaticRendezvous`](crate::StaticRendezvous).
+#[non_exhaustive]
+pub struct RendezvousRef<'storage> {
+    /// A pair without data
+    inner: RendezvousDataRef<'storage, ()>,
+}
+impl<'storage> RendezvousRef<'storage> {
+    /// Create a linked pair on `storage`, which no other pair has used.
+    pub(crate) fn from_fresh(storage: &'storage RendezvousDataStorage<()>) -> (Self, Self) {
+        let (first, second) = RendezvousDataRef::from_fresh(storage);
+        (Self { inner: first }, Self { inner: second })
+    }
+
+    /// Synchronize execution with other thread, see
+    /// [`Rendezvous::wait`](crate::Rendezvous::wait).
+    #[inline]
+    pub fn wait(&mut self) {
+        self.inner.swap();
+    }
+
+    /// Spins and time of the waits of this thread, see
+    /// [`Rendezvous::wait_stats`](crate::Rendezvous::wait_stats).
+    #[cfg(feature = "stats")]
+    #[must_use]
+    #[inline]
+    pub fn wait_stats(&self) -> WaitStats {
+        self.inner.wait_stats()
+    }
+
+    /// [`RendezvousRef::wait_stats`] of the other thread.
+    #[cfg(feature = "stats")]
+    #[must_use]
+    #[inline]
+    pub fn peer_wait_stats(&self) -> WaitStats {
+        self.inner.peer_wait_stats()
+    }
+}
+impl SyncPoint for RendezvousRef<'_> {
+    #[inline]
+    fn sync(&mut self) {
+        self.wait();
+    }
+}
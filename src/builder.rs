@@ -0,0 +1,240 @@
+//! Contains [`RendezvousBuilder`] and [`RendezvousDataBuilder`]
+
+use alloc::boxed::Box;
+use core::num::NonZeroUsize;
+
+use crate::counter::Counter;
+use crate::pair_id::PairId;
+use crate::{Rendezvous, RendezvousData, WaitStrategy};
+
+/// Wait strategies of the first and second handle
+type Strategies = [Box<dyn WaitStrategy>; 2];
+
+/// Set up the counters of a pair, before they are shared.
+fn configure(
+    first: &mut Counter,
+    second: &mut Counter,
+    strategies: Option<Strategies>,
+    name: Option<&'static str>,
+) {
+    if let Some([first_strategy, second_strategy]) = strategies {
+        first.set_strategy(first_strategy);
+        second.set_strategy(second_strategy);
+    }
+    if let Some(name) = name {
+        first.set_name(name);
+        second.set_name(name);
+    }
+}
+
+/// Both handles get a clone of `strategy`.
+fn strategies<S: WaitStrategy + Clone + 'static>(strategy: S) -> Strategies {
+    [Box::new(strategy.clone()), Box::new(strategy)]
+}
+
+/// Configures a pair of [`Rendezvous`], created by [`Rendezvous::builder`].
+///
+/// Combines the options of the `new_*` constructors, which stay as
+/// shorthands for a single option.
+/// # Example
+/// ```rust
+/// use core::num::NonZeroUsize;
+/// use rendezvous_swap::{Rendezvous, SpinBackoff};
+/// use std::thread;
+///
+/// let (mut audio, mut mixer) = Rendezvous::builder()
+///     .wait_strategy(SpinBackoff::new(16))
+///     .ratio(NonZeroUsize::new(4).unwrap())
+///     .name("audio<->mixer")
+///     .build_pair();
+/// assert_eq!(mixer.name(), Some("audio<->mixer"));
+/// let handle = thread::spawn(move || {
+///     for _ in 0..8 {
+///         audio.wait();
+///     }
+/// });
+/// mixer.wait();
+/// mixer.wait();
+/// # handle.join().unwrap();
+/// ```
+#[must_use = "call `build_pair` to create the handles"]
+#[non_exhaustive]
+pub struct RendezvousBuilder {
+    /// See [`RendezvousBuilder::ratio`]
+    ratio: NonZeroUsize,
+    /// See [`RendezvousBuilder::wait_strategy`]
+    strategies: Option<Strategies>,
+    /// See [`RendezvousBuilder::name`]
+    name: Option<&'static str>,
+}
+impl RendezvousBuilder {
+    /// Start with the options of [`Rendezvous::new`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            ratio: NonZeroUsize::MIN,
+            strategies: None,
+            name: None,
+        }
+    }
+
+    /// Wait with `strategy` (each handle a clone of it) instead of spinning
+    /// and yielding, see [`Rendezvous::new_with_strategy`].
+    #[inline]
+    pub fn wait_strategy<S: WaitStrategy + Clone + 'static>(mut self, strategy: S) -> Self {
+        self.strategies = Some(strategies(strategy));
+        self
+    }
+
+    /// Synchronize only every `ratio`:th wait of the first handle, see
+    /// [`Rendezvous::new_ratio`].
+    #[inline]
+    pub const fn ratio(mut self, ratio: NonZeroUsize) -> Self {
+        self.ratio = ratio;
+        self
+    }
+
+    /// Name the pair, for [`Rendezvous::name`] and the debug output of the
+    /// handles.
+    #[inline]
+    pub const fn name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Create the linked pair of [`Rendezvous`].
+    ///
+    /// # Panics
+    /// If the ratio is larger than `2^32`, see [`Rendezvous::new_ratio`].
+    #[must_use]
+    #[inline]
+    pub fn build_pair(self) -> (Rendezvous, Rendezvous) {
+        let skip = u32::try_from(self.ratio.get() - 1).expect("ratio larger than 2^32");
+        let pair = PairId::next();
+        let (mut first, mut second) = (Counter::for_pair(pair), Counter::for_pair(pair));
+        configure(&mut first, &mut second, self.strategies, self.name);
+        Rendezvous::from_counters(first, second, skip)
+    }
+}
+impl Default for RendezvousBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configures a pair of [`RendezvousData`], created by
+/// [`RendezvousData::builder`].
+///
+/// The initial values must be given with [`RendezvousDataBuilder::init`] or
+/// [`RendezvousDataBuilder::init_with`].
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{RendezvousData, SpinBackoff};
+/// use std::thread;
+///
+/// let (mut physics, mut render) = RendezvousData::builder()
+///     .init_with(|| vec![0.0_f32; 1024], || vec![0.0_f32; 1024])
+///     .wait_strategy(SpinBackoff::default())
+///     .name("physics<->render")
+///     .build_pair();
+/// println!("{render:?}"); // RendezvousDataN { name: Some("physics<->render"), .. }
+/// let handle = thread::spawn(move || {
+///     physics.swap()[0] = 1.0;
+///     physics.swap();
+/// });
+/// render.swap();
+/// assert_eq!(render.swap()[0], 1.0);
+/// # handle.join().unwrap();
+/// ```
+#[must_use = "call `build_pair` to create the handles"]
+#[non_exhaustive]
+pub struct RendezvousDataBuilder<T: Send> {
+    /// Initial values of the first and second handle
+    init: Option<(T, T)>,
+    /// See [`RendezvousDataBuilder::wait_strategy`]
+    strategies: Option<Strategies>,
+    /// See [`RendezvousDataBuilder::name`]
+    name: Option<&'static str>,
+}
+impl<T: Send> RendezvousDataBuilder<T> {
+    /// Start with the options of [`RendezvousData::new`], without initial
+    /// values.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            init: None,
+            strategies: None,
+            name: None,
+        }
+    }
+
+    /// Initial values of the data that will be swapped, the first handle
+    /// starts out with `data1` and the second with `data2`.
+    #[inline]
+    pub fn init(mut self, data1: T, data2: T) -> Self {
+        self.init = Some((data1, data2));
+        self
+    }
+
+    /// Initial values built by `f1` for the first handle and by `f2` for the
+    /// second.
+    #[inline]
+    pub fn init_with<F1: FnOnce() -> T, F2: FnOnce() -> T>(self, f1: F1, f2: F2) -> Self {
+        self.init(f1(), f2())
+    }
+
+    /// Swap with `strategy` (each handle a clone of it) instead of spinning
+    /// and yielding, see [`RendezvousData::new_with_strategy`].
+    #[inline]
+    pub fn wait_strategy<S: WaitStrategy + Clone + 'static>(mut self, strategy: S) -> Self {
+        self.strategies = Some(strategies(strategy));
+        self
+    }
+
+    /// Name the pair, for [`RendezvousDataN::name`](crate::RendezvousDataN::name)
+    /// and the debug output of the handles.
+    #[inline]
+    pub fn name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Create the linked pair of [`RendezvousData`].
+    ///
+    /// # Panics
+    /// If no initial values were given.
+    #[must_use]
+    #[inline]
+    pub fn build_pair(self) -> (RendezvousData<T>, RendezvousData<T>) {
+        let (data1, data2) = self
+            .init
+            .expect("initial values given with `init` or `init_with`");
+        RendezvousData::new_configured(data1, data2, |first, second| {
+            configure(first, second, self.strategies, self.name);
+        })
+    }
+}
+impl<T: Send> Default for RendezvousDataBuilder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rendezvous {
+    /// Configure a linked pair of [`Rendezvous`], see [`RendezvousBuilder`].
+    #[inline]
+    pub const fn builder() -> RendezvousBuilder {
+        RendezvousBuilder::new()
+    }
+}
+
+impl<T: Send> RendezvousData<T> {
+    /// Configure a linked pair of [`RendezvousData`], see
+    /// [`RendezvousDataBuilder`].
+    #[inline]
+    pub const fn builder() -> RendezvousDataBuilder<T> {
+        RendezvousDataBuilder::new()
+    }
+}
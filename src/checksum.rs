@@ -0,0 +1,56 @@
+//! Contains [`ChecksumMismatch`], see [`RendezvousDataN::checked_swap`](crate::RendezvousDataN::checked_swap)
+
+use core::fmt;
+
+/// Lookup table of the reflected CRC-32 (IEEE) polynomial.
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xEDB8_8320
+            };
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+};
+
+/// CRC-32 (IEEE) of `bytes`, as used by zlib and Ethernet.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc: u32, &byte| {
+        TABLE[usize::from(crc.to_le_bytes()[0] ^ byte)] ^ (crc >> 8)
+    })
+}
+
+/// The data received by [`RendezvousDataN::checked_swap`](crate::RendezvousDataN::checked_swap)
+/// does not match the checksum the other thread computed when it sent it.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChecksumMismatch {
+    /// Generation of the receiving thread after the swap.
+    pub generation: usize,
+    /// CRC-32 computed by the sending thread.
+    pub expected: u32,
+    /// CRC-32 of the data received.
+    pub actual: u32,
+}
+impl fmt::Display for ChecksumMismatch {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checksum mismatch at generation {}: sent {:#010x}, received {:#010x}",
+            self.generation, self.expected, self.actual
+        )
+    }
+}
+#[cfg(feature = "std")]
+impl crate::os::std::error::Error for ChecksumMismatch {}
@@ -0,0 +1,69 @@
+//! Contains [`Clock`] and [`StdClock`]
+
+use core::hint::spin_loop;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use crate::os::std::{sync::OnceLock, thread, time::Instant};
+
+/// Monotonic time source used by everything in the crate that measures
+/// time: timeouts, deadlines, spin budgets and statistics.
+///
+/// With `std` the crate uses [`StdClock`], and every timed method has a
+/// `_with` variant taking a clock, which is also available without `std`, so
+/// embedded targets can plug in a hardware timer.
+/// # Example
+/// ```rust
+/// use core::cell::Cell;
+/// use core::time::Duration;
+/// use rendezvous_swap::{Clock, ProbeResult, Rendezvous};
+///
+/// /// A timer that advances by a millisecond every time it is read.
+/// struct Ticks(Cell<u64>);
+/// impl Clock for Ticks {
+///     fn now(&self) -> Duration {
+///         self.0.set(self.0.get() + 1);
+///         Duration::from_millis(self.0.get())
+///     }
+/// }
+///
+/// let (my_rendezvous, _their_rendezvous) = Rendezvous::new();
+/// let clock = Ticks(Cell::new(0));
+/// let result = my_rendezvous.probe_with(Duration::from_millis(10), &clock);
+/// assert_eq!(result, ProbeResult::Unresponsive);
+/// ```
+pub trait Clock {
+    /// Time since an arbitrary, fixed origin, which never decreases.
+    fn now(&self) -> Duration;
+
+    /// Let `duration` pass, giving the CPU away if the platform can.
+    ///
+    /// The default spins until [`Clock::now`] has advanced by `duration`.
+    #[inline]
+    fn sleep(&self, duration: Duration) {
+        let start = self.now();
+        while self.now().saturating_sub(start) < duration {
+            spin_loop();
+        }
+    }
+}
+
+/// [`Clock`] of the operating system, [`Instant`] and
+/// [`thread::sleep`].
+#[cfg(feature = "std")]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdClock;
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    #[inline]
+    fn now(&self) -> Duration {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed()
+    }
+
+    #[inline]
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
@@ -0,0 +1,177 @@
+//! Adapters for using the sync points of this crate where other frameworks
+//! expect their own callback or barrier types.
+//!
+//! Game loop, simulation and scheduler crates usually drive user code
+//! through `FnMut` tick callbacks, the functions here turn any
+//! [`SyncPoint`] or [`SwapPoint`] into such a callback, so a framework
+//! thread can be locked to a worker thread without wrapper code.
+//!
+//! [`Barrier2`] stands in for a `std::sync::Barrier` of two threads.
+
+use core::fmt;
+use core::hint::spin_loop;
+use core::sync::atomic::{AtomicUsize, Ordering::AcqRel, Ordering::Acquire, Ordering::Release};
+
+use crate::padded::Padded;
+use crate::{SwapPoint, SyncPoint};
+
+/// Spins of [`Barrier2::wait`] before it starts yielding to the OS
+/// scheduler (with `std`).
+#[cfg(feature = "std")]
+const SPINS_BEFORE_YIELD: u32 = 1 << 12;
+
+/// Turn a [`SyncPoint`] into a tick callback that syncs once per call.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{compat, Rendezvous};
+/// use std::thread;
+///
+/// // stands in for a framework taking a tick callback
+/// fn run_loop(ticks: usize, mut on_tick: impl FnMut()) {
+///     for _ in 0..ticks {
+///         on_tick();
+///     }
+/// }
+/// let (my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+/// let handle = thread::spawn(move || {
+///     for _ in 0..10 {
+///         their_rendezvous.wait();
+///     }
+/// });
+/// run_loop(10, compat::tick(my_rendezvous));
+/// # handle.join().unwrap();
+/// ```
+#[inline]
+pub fn tick<S: SyncPoint>(mut sync: S) -> impl FnMut() {
+    move || sync.sync()
+}
+
+/// Turn a [`SwapPoint`] into a tick callback that swaps once per call and
+/// passes the data received to `on_data`.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{compat, RendezvousData};
+/// use std::thread;
+///
+/// let (my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+/// let handle = thread::spawn(move || {
+///     for i in 1..=10 {
+///         *their_rendezvous.swap() = i;
+///     }
+///     their_rendezvous.swap();
+/// });
+/// let mut sum = 0;
+/// let mut on_tick = compat::tick_swap(my_rendezvous, |data: &mut i32| sum += *data);
+/// for _ in 0..11 {
+///     on_tick();
+/// }
+/// drop(on_tick);
+/// assert_eq!(sum, 55);
+/// # handle.join().unwrap();
+/// ```
+#[inline]
+pub fn tick_swap<S, F>(mut swap: S, mut on_data: F) -> impl FnMut()
+where
+    S: SwapPoint,
+    F: FnMut(&mut S::Data),
+{
+    move || on_data(swap.swap())
+}
+
+/// A barrier for two threads with the API of
+/// [`std::sync::Barrier`](https://doc.rust-lang.org/std/sync/struct.Barrier.html),
+/// so that code written for `Barrier::new(2)` switches by changing only the
+/// constructor.
+///
+/// Like the std barrier it is shared by reference (usually in an `Arc`),
+/// and any two threads can wait on it, so it cannot know which of the pair
+/// a thread is: unlike the handles of this crate, arriving is one atomic
+/// read-modify-write instead of a plain store. Waiting spins (and then
+/// yields, with `std`) instead of blocking on a mutex. For new code,
+/// [`Rendezvous`](crate::Rendezvous) is faster.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::compat::Barrier2;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let barrier = Arc::new(Barrier2::new()); // was `Barrier::new(2)`
+/// let their_barrier = Arc::clone(&barrier);
+/// let handle = thread::spawn(move || their_barrier.wait().is_leader());
+/// let leaders = usize::from(barrier.wait().is_leader()) + usize::from(handle.join().unwrap());
+/// assert_eq!(leaders, 1);
+/// ```
+#[non_exhaustive]
+pub struct Barrier2 {
+    /// Number of threads that arrived at the current generation
+    arrived: Padded<AtomicUsize>,
+    /// Number of completed generations
+    generation: Padded<AtomicUsize>,
+}
+impl Barrier2 {
+    /// Create a barrier for two threads, like `Barrier::new(2)`.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            arrived: Padded::new(AtomicUsize::new(0)),
+            generation: Padded::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Block until two threads have called `wait` in this generation, like
+    /// `Barrier::wait`. The thread that arrives last is the leader.
+    #[inline]
+    pub fn wait(&self) -> BarrierWaitResult {
+        let generation = self.generation.load(Acquire);
+        if self.arrived.fetch_add(1, AcqRel) == 1 {
+            // The other thread is spinning on `generation`, nobody touches
+            // `arrived` until it has been published.
+            self.arrived.store(0, Release);
+            self.generation.store(generation.wrapping_add(1), Release);
+            return BarrierWaitResult { is_leader: true };
+        }
+        #[cfg(feature = "std")]
+        let mut spins = 0_u32;
+        while self.generation.load(Acquire) == generation {
+            spin_loop();
+            #[cfg(feature = "std")]
+            {
+                spins = spins.saturating_add(1);
+                if spins > SPINS_BEFORE_YIELD {
+                    crate::os::yield_now();
+                }
+            }
+        }
+        BarrierWaitResult { is_leader: false }
+    }
+}
+impl Default for Barrier2 {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl fmt::Debug for Barrier2 {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Barrier2").finish_non_exhaustive()
+    }
+}
+
+/// Returned by [`Barrier2::wait`], like `std::sync::BarrierWaitResult`.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BarrierWaitResult {
+    /// See [`BarrierWaitResult::is_leader`]
+    is_leader: bool,
+}
+impl BarrierWaitResult {
+    /// True for exactly one of the two threads of each generation, the one
+    /// that arrived last.
+    #[must_use]
+    #[inline]
+    pub const fn is_leader(&self) -> bool {
+        self.is_leader
+    }
+}
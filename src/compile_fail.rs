@@ -0,0 +1,197 @@
+//! Misuse that must not compile, checked as `compile_fail` doctests (only
+//! compiled when running doctests).
+//!
+//! Each test records the expected error code, which rustdoc checks on
+//! nightly, so a test cannot quietly pass because of an unrelated mistake in
+//! it. The central promise of the crate is that two
+//! threads can never have mutable references to the same buffer at the same
+//! time: every reference handed out borrows the handle until the next sync,
+//! and every API that hands out data must keep it that way. New APIs that
+//! hand out references add their misuse here.
+//!
+//! # Data from a swap cannot be used after the next swap
+//! ```compile_fail,E0499
+//! use rendezvous_swap::RendezvousData;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) = RendezvousData::new(0, 0);
+//! let old = my_rendezvous.swap();
+//! let _new = my_rendezvous.swap();
+//! *old = 3;
+//! ```
+//!
+//! # Data from a fixed number of swaps cannot be used after the next swap
+//! ```compile_fail,E0499
+//! use rendezvous_swap::RendezvousData;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) = RendezvousData::new(0, 0);
+//! let old = my_rendezvous.for_generations(10, |_| {});
+//! let _new = my_rendezvous.swap();
+//! *old = 3;
+//! ```
+//!
+//! # Data that can be sent between threads only
+//! ```compile_fail,E0277
+//! use rendezvous_swap::RendezvousData;
+//! use std::rc::Rc;
+//!
+//! let _ = RendezvousData::new(Rc::new(0), Rc::new(0));
+//! ```
+//!
+//! # Data read by several threads at once can be shared between threads only
+//! ```compile_fail,E0277
+//! use rendezvous_swap::FanOutPublisher;
+//! use std::cell::Cell;
+//!
+//! let _ = FanOutPublisher::new(Cell::new(0), Cell::new(0), 2);
+//! ```
+//!
+//! # A handle cannot be used while a split-phase swap is pending
+//! ```compile_fail,E0499
+//! use rendezvous_swap::RendezvousData;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) = RendezvousData::new(0, 0);
+//! let (_completer, pending) = my_rendezvous.arrive_later();
+//! my_rendezvous.swap();
+//! pending.finish();
+//! ```
+//!
+//! # A handle cannot be used before its completer has arrived
+//! ```compile_fail,E0499
+//! use rendezvous_swap::RendezvousData;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) = RendezvousData::new(0, 0);
+//! let (completer, pending) = my_rendezvous.arrive_later();
+//! drop(pending);
+//! my_rendezvous.swap();
+//! completer.complete();
+//! ```
+//!
+//! # Data from a split-phase swap cannot be used after the next swap
+//! ```compile_fail,E0499
+//! use rendezvous_swap::RendezvousData;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) = RendezvousData::new(0, 0);
+//! let (_completer, pending) = my_rendezvous.arrive_later();
+//! let old = pending.finish();
+//! let _new = my_rendezvous.swap();
+//! *old = 3;
+//! ```
+//!
+//! # Received data of a split handle is read-only
+//! ```compile_fail,E0594
+//! use rendezvous_swap::SplitRendezvousData;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) = SplitRendezvousData::new([0; 2], [0; 2]);
+//! let (received, _sent) = my_rendezvous.swap();
+//! *received = 3;
+//! ```
+//!
+//! # Received data of a split handle cannot be used after the next swap
+//! ```compile_fail,E0499
+//! use rendezvous_swap::SplitRendezvousData;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) = SplitRendezvousData::new([0; 2], [0; 2]);
+//! let (received, _sent) = my_rendezvous.swap();
+//! my_rendezvous.swap();
+//! let _ = *received;
+//! ```
+//!
+//! # Pinned data cannot be moved out of
+//! ```compile_fail,E0277
+//! use rendezvous_swap::PinnedRendezvousData;
+//! use std::marker::PhantomPinned;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) =
+//!     PinnedRendezvousData::new(PhantomPinned, PhantomPinned);
+//! let _ = my_rendezvous.swap_pinned().get_mut();
+//! ```
+//!
+//! # Arena temporaries cannot outlive their generation
+//! ```compile_fail,E0499
+//! use rendezvous_swap::{RendezvousData, WithArena};
+//!
+//! let (my_rendezvous, _their_rendezvous) = RendezvousData::new(0, 0);
+//! let mut my_rendezvous = WithArena::new(my_rendezvous);
+//! let temporary = my_rendezvous.sync().alloc(1);
+//! my_rendezvous.sync();
+//! *temporary = 3;
+//! ```
+//!
+//! # Data of a paused pair cannot be used after resuming
+//! ```compile_fail,E0505
+//! use rendezvous_swap::RendezvousData;
+//!
+//! let (my_rendezvous, _their_rendezvous) = RendezvousData::new(0, 0);
+//! let mut paused = my_rendezvous.pause();
+//! let data = paused.get_mut();
+//! let _my_rendezvous = paused.resume();
+//! *data = 3;
+//! ```
+//!
+//! # A received batch cannot be used after receiving the next one
+//! ```compile_fail,E0499
+//! use rendezvous_swap::BatchProducer;
+//!
+//! let (_producer, mut consumer) = BatchProducer::new(vec![0, 0, 0]);
+//! let old = consumer.receive();
+//! let _new = consumer.receive();
+//! *old = 3;
+//! ```
+//!
+//! # A dirty range cannot be written after the swap that copied it
+//! ```compile_fail,E0499
+//! use rendezvous_swap::DirtyRendezvousData;
+//!
+//! let (mut my_rendezvous, _their_rendezvous) = DirtyRendezvousData::new(&[0; 4]);
+//! let dirty = my_rendezvous.get_mut(0..2);
+//! my_rendezvous.swap();
+//! dirty[0] = 3;
+//! ```
+//!
+//! # A publication cannot be read after releasing it
+//! ```compile_fail,E0499
+//! use rendezvous_swap::FanOutPublisher;
+//!
+//! let (_publisher, mut readers) = FanOutPublisher::new(0, 0, 1);
+//! let old = readers[0].read();
+//! let _new = readers[0].read();
+//! let _ = *old;
+//! ```
+//!
+//! # Readers cannot write a publication
+//! ```compile_fail,E0594
+//! use rendezvous_swap::FanOutPublisher;
+//!
+//! let (_publisher, mut readers) = FanOutPublisher::new(0, 0, 1);
+//! *readers[0].read() = 3;
+//! ```
+//!
+//! # Read-only handles cannot write the data
+//! ```compile_fail,E0594
+//! use rendezvous_swap::RendezvousData;
+//!
+//! let (_writer, reader) = RendezvousData::new(0, 0);
+//! let mut reader = reader.read_only();
+//! *reader.read() = 3;
+//! ```
+//!
+//! # Handles with different phase tags are different types
+//! ```compile_fail,E0308
+//! use rendezvous_swap::{Rendezvous, Tagged};
+//!
+//! struct Audio;
+//! struct Network;
+//!
+//! fn audio(_: Tagged<Rendezvous, Audio>) {}
+//!
+//! let (network, _) = Tagged::<_, Network>::pair(Rendezvous::new());
+//! audio(network);
+//! ```
+//!
+//! # Scoped handles cannot leave their scope
+//! ```compile_fail
+//! use rendezvous_swap::Rendezvous;
+//! use std::thread;
+//!
+//! let (_my_rendezvous, _their_rendezvous) = thread::scope(|scope| Rendezvous::scoped(scope));
+//! ```
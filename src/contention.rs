@@ -0,0 +1,200 @@
+//! Contains [`ContentionStats`] and [`SpinBudget`]
+
+use core::hint::spin_loop;
+use core::time::Duration;
+
+/// Spin budget used when a handle is created, and the most it can grow back to.
+const MAX_SPINS: u32 = 1 << 12;
+
+/// The spin budget never shrinks below this, so an uncontended wait can still
+/// be detected and grow the budget back.
+const MIN_SPINS: u32 = 1 << 4;
+
+/// Counters describing how often waiting on the other thread had to fall back
+/// to yielding.
+///
+/// A large share of `contended_waits` means that the two threads are often not
+/// running at the same time (for example when there are more threads than
+/// cores), and that spinning is wasting time the other thread could have used.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContentionStats {
+    /// Number of completed waits.
+    pub waits: u64,
+    /// Number of waits that used up the spin budget and started yielding.
+    pub contended_waits: u64,
+    /// Total number of times the thread yielded to the OS scheduler (or
+    /// parked, with the `parking` feature).
+    pub yields: u64,
+    /// Current spin budget: how many spins a wait does before yielding.
+    pub spin_limit: u32,
+    /// Number of waits that spun past the preemption threshold and blocked
+    /// for the rest of the wait, see `set_preemption_threshold` on the
+    /// handles. Always 0 for handles without a threshold.
+    pub suspected_preemptions: u64,
+}
+impl ContentionStats {
+    /// True if the spin budget has been reduced because of contention.
+    #[must_use]
+    #[inline]
+    pub const fn is_degraded(&self) -> bool {
+        self.spin_limit < MAX_SPINS
+    }
+}
+
+/// Duty cycle limiting how long a waiting thread spins, for battery powered
+/// devices where one side often waits a long time (for example on a 10 Hz
+/// sensor).
+///
+/// While waiting, the thread alternates between spinning for `spin` and
+/// sleeping for `sleep`, so it spins for at most `spin / (spin + sleep)` of
+/// the time. Sleeping delays noticing the other thread by up to `sleep`.
+/// Durations are kept with microsecond resolution, up to about an hour.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{Rendezvous, SpinBudget};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+/// my_rendezvous.set_spin_budget(Some(SpinBudget::new(
+///     Duration::from_micros(50),
+///     Duration::from_millis(1),
+/// )));
+/// let handle = thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(10));
+///     their_rendezvous.wait();
+/// });
+/// my_rendezvous.wait();
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpinBudget {
+    /// Time to spin before sleeping.
+    pub spin: Duration,
+    /// Time to sleep between spinning, at least one microsecond.
+    pub sleep: Duration,
+}
+impl SpinBudget {
+    /// Spin for `spin`, then sleep for `sleep` (rounded to microseconds, at
+    /// least one), and repeat.
+    #[must_use]
+    #[inline]
+    pub fn new(spin: Duration, sleep: Duration) -> Self {
+        Self::unpack(pack_micros(spin), pack_micros(sleep).max(1))
+    }
+
+    /// Pack into `(spin, sleep)` microseconds, `sleep` is never 0.
+    pub(crate) fn pack(self) -> (u32, u32) {
+        (pack_micros(self.spin), pack_micros(self.sleep).max(1))
+    }
+
+    /// Inverse of [`SpinBudget::pack`].
+    pub(crate) fn unpack(spin: u32, sleep: u32) -> Self {
+        Self {
+            spin: Duration::from_micros(u64::from(spin)),
+            sleep: Duration::from_micros(u64::from(sleep)),
+        }
+    }
+}
+
+/// Microseconds of `duration`, saturating.
+fn pack_micros(duration: Duration) -> u32 {
+    u32::try_from(duration.as_micros()).unwrap_or(u32::MAX)
+}
+
+/// Spin-count heuristic deciding when spinning has stopped paying off.
+///
+/// Every wait spins up to `spin_limit` times before yielding.
+/// Each contended wait halves the budget and each uncontended wait doubles it,
+/// so a loaded machine quickly degrades to yielding and recovers when the load
+/// goes away.
+pub(crate) struct Contention {
+    /// See [`ContentionStats::waits`].
+    waits: u64,
+    /// See [`ContentionStats::contended_waits`].
+    contended_waits: u64,
+    /// See [`ContentionStats::yields`].
+    yields: u64,
+    /// See [`ContentionStats::spin_limit`].
+    spin_limit: u32,
+    /// Extra pause instructions per spin, see `smt::SIBLING_PAUSES`.
+    extra_pauses: u32,
+}
+impl Contention {
+    /// Construct with the full spin budget.
+    pub(crate) const fn new() -> Self {
+        Self {
+            waits: 0,
+            contended_waits: 0,
+            yields: 0,
+            spin_limit: MAX_SPINS,
+            extra_pauses: 0,
+        }
+    }
+
+    /// Called once per failed check of the other counter.
+    /// Returns true if the spin budget is used up and the thread should block.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn spin(&mut self, spins: &mut u32) -> bool {
+        for _ in 0..self.extra_pauses {
+            spin_loop();
+        }
+        *spins = spins.saturating_add(1);
+        let block = *spins > self.spin_limit;
+        if block {
+            self.yields = self.yields.wrapping_add(1);
+        }
+        block
+    }
+
+    /// Called when a wait completes, after `spins` failed checks.
+    #[inline]
+    pub(crate) fn finish(&mut self, spins: u32) {
+        self.waits = self.waits.wrapping_add(1);
+        if spins > self.spin_limit {
+            self.contended_waits = self.contended_waits.wrapping_add(1);
+            self.spin_limit = (self.spin_limit / 2).max(MIN_SPINS);
+        } else {
+            self.spin_limit = self.spin_limit.saturating_mul(2).min(MAX_SPINS);
+        }
+    }
+
+    /// Called when a wait with a preemption threshold completes, which
+    /// leaves the spin budget alone: blocking for one suspected preemption
+    /// says nothing about later waits.
+    pub(crate) fn finish_preemptible(&mut self, preempted: bool) {
+        self.waits = self.waits.wrapping_add(1);
+        if preempted {
+            self.contended_waits = self.contended_waits.wrapping_add(1);
+        }
+    }
+
+    /// Called when a wait that followed a [`SpinBudget`] slept.
+    pub(crate) fn slept(&mut self) {
+        self.yields = self.yields.wrapping_add(1);
+    }
+
+    /// Execute `pauses` pause instructions per spin instead of one.
+    pub(crate) fn set_pauses(&mut self, pauses: u32) {
+        self.extra_pauses = pauses.saturating_sub(1);
+    }
+
+    /// True if more than one pause instruction is executed per spin.
+    pub(crate) const fn has_extra_pauses(&self) -> bool {
+        self.extra_pauses > 0
+    }
+
+    /// Snapshot of the statistics.
+    pub(crate) const fn stats(&self) -> ContentionStats {
+        ContentionStats {
+            waits: self.waits,
+            contended_waits: self.contended_waits,
+            yields: self.yields,
+            spin_limit: self.spin_limit,
+            suspected_preemptions: 0,
+        }
+    }
+}
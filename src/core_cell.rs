@@ -0,0 +1,105 @@
+//! Contains [`SlotCell`], the cell the pairs store their shared buffers in.
+//!
+//! Every pair type that hands buffers back and forth stores them in
+//! [`SlotCell`]s, instead of implementing [`Sync`] for its own shared state
+//! and dereferencing [`UnsafeCell`] pointers itself, so that the `Sync` impl
+//! and the raw pointer accesses are written once. This does not make the
+//! pairs safe by construction: [`SlotCell::get_mut`] and [`SlotCell::get`]
+//! are `unsafe`, and each pair type still states at every call why its
+//! protocol currently gives the calling thread the slot. Those calls, and
+//! the other `unsafe` code of the crate, are spread over the modules of the
+//! pair types.
+
+use crate::sync::{const_unless_loom, UnsafeCell};
+
+/// A buffer shared by the threads of a pair, which is accessed by one thread
+/// at a time, or read by several threads while none writes it.
+///
+/// Which thread may access a slot is decided by the counters of the pair, a
+/// thread that has handed a slot over must not access it again until it has
+/// observed, with acquire ordering, that the other thread has handed it
+/// back.
+#[repr(transparent)]
+pub(crate) struct SlotCell<T>(UnsafeCell<T>);
+// SAFETY:
+// Sharing a `SlotCell` lets threads obtain `&mut T` one after the other,
+// which moves `T` between threads (`Send`). Several threads only hold `&T`
+// at the same time through `get`, which requires `T: Sync`. Data races are
+// ruled out by the contracts of `get_mut` and `get`.
+unsafe impl<T: Send> Sync for SlotCell<T> {}
+impl<T> SlotCell<T> {
+    const_unless_loom! {
+        /// Slot holding `value`.
+        pub(crate) fn new(value: T) -> Self {
+            Self(UnsafeCell::new(value))
+        }
+    }
+
+    /// Take the value out, the slot is not shared any more.
+    pub(crate) fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+
+    /// Mutable access to a slot the calling thread owns.
+    ///
+    /// # Safety
+    /// Until the returned reference is dropped, no other reference to the
+    /// value exists and no other thread accesses it: the calling thread owns
+    /// the slot according to the protocol of the pair, and has synchronized
+    /// with the thread that owned it before (acquire ordering).
+    #[allow(clippy::mut_from_ref, clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) unsafe fn get_mut(&self) -> &mut T {
+        // SAFETY:
+        // The pointer is valid and aligned for the lifetime of `self`, and
+        // the caller guarantees exclusive access.
+        #[cfg(not(loom))]
+        unsafe {
+            &mut *self.0.get()
+        }
+        // SAFETY:
+        // As above, loom checks that the access does not race with others.
+        #[cfg(loom)]
+        self.0.with_mut(|value| unsafe { &mut *value })
+    }
+}
+impl<T: Sync> SlotCell<T> {
+    /// Shared access to a slot that nobody writes.
+    ///
+    /// # Safety
+    /// Until the returned reference is dropped, no thread obtains a mutable
+    /// reference to the value: the protocol of the pair gives the calling
+    /// thread (possibly among others) read access, and it has synchronized
+    /// with the thread that last wrote it (acquire ordering).
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) unsafe fn get(&self) -> &T {
+        // SAFETY:
+        // The pointer is valid and aligned for the lifetime of `self`, and
+        // the caller guarantees that there are no writers.
+        #[cfg(not(loom))]
+        unsafe {
+            &*self.0.get()
+        }
+        // SAFETY:
+        // As above, loom checks that the access does not race with writes.
+        #[cfg(loom)]
+        self.0.with(|value| unsafe { &*value })
+    }
+}
+
+/// Mutable access to the values of `cells`, which the calling thread owns.
+///
+/// # Safety
+/// As for [`SlotCell::get_mut`], for every cell of `cells`.
+#[cfg(not(loom))]
+#[allow(clippy::mut_from_ref)]
+#[inline]
+pub(crate) unsafe fn slice_mut<T>(cells: &[SlotCell<T>]) -> &mut [T] {
+    // `SlotCell<T>` and `UnsafeCell<T>` have the layout of `T`.
+    let data = UnsafeCell::raw_get(cells.as_ptr().cast::<UnsafeCell<T>>());
+    // SAFETY:
+    // The pointer and length describe `cells`, and the caller guarantees
+    // exclusive access to all of them.
+    unsafe { core::slice::from_raw_parts_mut(data, cells.len()) }
+}
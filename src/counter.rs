@@ -0,0 +1,1125 @@
+//! Contains [`Counter`]
+
+#[cfg(any(feature = "parking", feature = "async"))]
+use core::sync::atomic::fence;
+#[cfg(any(feature = "std", feature = "checksum"))]
+use core::sync::atomic::AtomicU32;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering::{self, Acquire, Relaxed, Release};
+use core::sync::atomic::{AtomicU8, AtomicUsize};
+
+use crate::clock::Clock;
+#[cfg(feature = "std")]
+use crate::clock::StdClock;
+#[cfg(feature = "std")]
+use crate::contention::{Contention, SpinBudget};
+use crate::last_error::{ErrorKind, LastError};
+#[cfg(feature = "alloc")]
+use crate::padded::Padded;
+use crate::pair_id::PairId;
+use crate::strict::Op;
+use crate::sync::{self, const_unless_loom, spin_loop};
+#[cfg(feature = "stats")]
+use crate::wait_stats::{WaitRecord, WaitStats};
+#[cfg(feature = "alloc")]
+use crate::WaitStrategy;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use core::sync::atomic::AtomicBool;
+#[cfg(any(feature = "parking", feature = "async"))]
+use core::sync::atomic::Ordering::SeqCst;
+#[cfg(feature = "async")]
+use core::task::Waker;
+
+#[cfg(feature = "async")]
+use crate::os::std::sync::{Mutex, PoisonError};
+use core::time::Duration;
+
+/// [`Counter::left`] of a handle that is alive.
+const ATTACHED: u8 = 0;
+/// [`Counter::left`] of a handle that is arriving at the next sync point on
+/// leaving, see [`Counter::detach`].
+const DETACHING: u8 = 1;
+/// [`Counter::left`] of a handle that arrived at the next sync point on
+/// leaving.
+const DETACHED: u8 = 2;
+/// [`Counter::left`] of a handle that left without arriving, see
+/// [`Counter::hang_up`].
+const HUNG_UP: u8 = 3;
+
+/// Ordering of loads of the generation.
+///
+/// With the `explicit-fences` feature loads and stores are relaxed, and
+/// ordering is instead established by a release fence before the store and an
+/// acquire fence once the other thread has been observed, see
+/// [`Counter::acquire`].
+const LOAD: Ordering = if cfg!(feature = "explicit-fences") {
+    Relaxed
+} else {
+    Acquire
+};
+/// Ordering of stores of the generation, see [`LOAD`].
+const STORE: Ordering = if cfg!(feature = "explicit-fences") {
+    Relaxed
+} else {
+    Release
+};
+
+/// Load `atomic`, counted with the `count-atomics` feature.
+#[allow(clippy::inline_always)]
+#[inline(always)]
+fn load(atomic: &sync::AtomicUsize, order: Ordering) -> usize {
+    #[cfg(feature = "count-atomics")]
+    crate::atomic_counts::record_load();
+    atomic.load(order)
+}
+
+/// Store `value` in `atomic`, counted with the `count-atomics` feature.
+#[allow(clippy::inline_always)]
+#[inline(always)]
+fn store(atomic: &sync::AtomicUsize, value: usize, order: Ordering) {
+    #[cfg(feature = "count-atomics")]
+    crate::atomic_counts::record_store();
+    atomic.store(value, order);
+}
+
+/// Fence, counted with the `count-atomics` feature.
+#[cfg(feature = "explicit-fences")]
+#[allow(clippy::inline_always)]
+#[inline(always)]
+fn counted_fence(order: Ordering) {
+    #[cfg(feature = "count-atomics")]
+    crate::atomic_counts::record_fence();
+    sync::fence(order);
+}
+
+/// Report a protocol violation found by [`Counter::check`].
+#[cfg(feature = "strict")]
+#[cold]
+#[inline(never)]
+fn strict_violation(generation: usize, mine: Op, theirs: Option<Op>) -> ! {
+    match theirs {
+        Some(theirs) => panic!(
+            "strict protocol violation at generation {generation}: this thread called {mine}, the other thread called {theirs}"
+        ),
+        None => panic!(
+            "strict protocol violation at generation {generation}: this thread called {mine}, the other thread did not record an operation"
+        ),
+    }
+}
+
+/// Report a wait that can never complete or an impossible generation of the
+/// other thread, found by [`Counter::check_stalled`] and
+/// [`Counter::check_arrival`].
+#[cfg(any(debug_assertions, feature = "checked"))]
+#[cold]
+#[inline(never)]
+fn unbalanced(generation: usize, theirs: &Counter) -> ! {
+    let arrived = generation.wrapping_add(1);
+    let at = theirs.load();
+    if theirs.hung_up() {
+        panic!(
+            "unbalanced sync points: this thread arrived at generation {arrived}, but the other thread panicked at generation {at}"
+        )
+    } else if theirs.has_left() {
+        panic!(
+            "unbalanced sync points: this thread arrived at generation {arrived}, but the other handle was dropped after arriving at generation {at}, so the other thread synced fewer times"
+        )
+    } else {
+        panic!(
+            "impossible state: this thread arrived at generation {arrived} while the other thread is at generation {at}, the handles synced unevenly or were resumed at different generations"
+        )
+    }
+}
+
+/// Counters of the first and second handle of a pair, each in its own cache
+/// line, so that a pair needs one allocation and the two threads never
+/// contend for a line.
+#[cfg(feature = "alloc")]
+pub(crate) type CounterPair = [Padded<Counter>; 2];
+
+/// Generation counter published by one thread and waited on by the other.
+pub(crate) struct Counter {
+    /// Generation of the owning thread
+    generation: sync::AtomicUsize,
+    /// Sub-barrier generation of the owning thread, see [`crate::SubRendezvous`]
+    sub_generation: sync::AtomicUsize,
+    /// Last probe posted by the other thread
+    probe_request: sync::AtomicUsize,
+    /// Last probe answered by the owning thread
+    probe_response: sync::AtomicUsize,
+    /// Generation the owning thread marked as urgent, indexed by generation
+    /// parity like `ops`, so that marking the next generation does not hide
+    /// the mark of the current one from the other thread
+    urgent: [sync::AtomicUsize; 2],
+    /// CPU the owning thread last reported running on, plus one (0 is
+    /// unknown)
+    #[cfg(feature = "std")]
+    cpu: AtomicUsize,
+    /// [`SpinBudget`] of the owning thread packed as microseconds of
+    /// spinning and sleeping, sleeping 0 if none. Only accessed by the
+    /// owning thread, kept here to keep handles small.
+    #[cfg(feature = "std")]
+    budget: [AtomicU32; 2],
+    /// Preemption threshold of the owning thread in microseconds, 0 if
+    /// none, see [`Counter::set_preemption_threshold`]
+    #[cfg(feature = "std")]
+    preemption_threshold: AtomicU32,
+    /// Number of waits of the owning thread that suspected preemption
+    #[cfg(feature = "std")]
+    preemptions: AtomicUsize,
+    /// [`WaitStrategy`] of the owning thread, replacing spinning and
+    /// yielding. Only used by the owning thread, kept here to keep handles
+    /// small.
+    #[cfg(feature = "alloc")]
+    strategy: Option<Box<dyn WaitStrategy>>,
+    /// True if the owning thread has a [`WaitStrategy`], a spin budget or a
+    /// preemption threshold, so that a wait checks one flag instead of each
+    /// setting, see [`Counter::update_slow_wait`]
+    #[cfg(feature = "alloc")]
+    slow_wait: AtomicBool,
+    /// Set by the other thread while it may be parked on `generation`.
+    #[cfg(feature = "parking")]
+    parked: AtomicBool,
+    /// Set by the other thread while `waker` may hold the waker of a task
+    /// awaiting `generation`
+    #[cfg(feature = "async")]
+    waiting: AtomicBool,
+    /// Waker of the task of the other thread, see [`Counter::register_waker`]
+    #[cfg(feature = "async")]
+    waker: Mutex<Option<Waker>>,
+    /// Operation of the owning thread at the last two generations, indexed by
+    /// generation parity. The other thread is never more than one
+    /// generation ahead, so the slot it reads is not being overwritten.
+    #[cfg(feature = "strict")]
+    ops: [AtomicUsize; 2],
+    /// Number of times the owning thread was found on another CPU than at
+    /// the previous sample
+    #[cfg(feature = "migration-stats")]
+    migrations: AtomicUsize,
+    /// Waits of the owning thread, see [`Counter::wait_past`]
+    #[cfg(feature = "stats")]
+    wait_stats: WaitRecord,
+    /// Checksum of the data the owning thread sent at the last two
+    /// generations, indexed by generation parity like `ops`
+    #[cfg(feature = "checksum")]
+    checksums: [AtomicU32; 2],
+    /// Tag the owning thread sent at the last two generations, indexed by
+    /// generation parity like `ops`
+    #[cfg(target_has_atomic = "64")]
+    tags: [AtomicU64; 2],
+    /// How the owning handle left for good, [`ATTACHED`] while it is alive,
+    /// see [`Counter::detach`] and [`Counter::hang_up`]
+    left: AtomicU8,
+    /// Last error the owning thread ran into, packed with
+    /// [`LastError::pack`], 0 if none
+    last_error: AtomicUsize,
+    /// Pair the counter belongs to, [`PairId::NONE`] for counters outside
+    /// of a handle pair
+    pair: PairId,
+    /// Name of the pair for debug output, see `name` on the builders
+    name: Option<&'static str>,
+}
+impl Counter {
+    const_unless_loom! {
+        /// Counter at generation 0
+        pub(crate) fn new() -> Self {
+            Self {
+                generation: sync::AtomicUsize::new(0),
+                sub_generation: sync::AtomicUsize::new(0),
+                probe_request: sync::AtomicUsize::new(0),
+                probe_response: sync::AtomicUsize::new(0),
+                urgent: [sync::AtomicUsize::new(0), sync::AtomicUsize::new(0)],
+                #[cfg(feature = "std")]
+                cpu: AtomicUsize::new(0),
+                #[cfg(feature = "std")]
+                budget: [AtomicU32::new(0), AtomicU32::new(0)],
+                #[cfg(feature = "std")]
+                preemption_threshold: AtomicU32::new(0),
+                #[cfg(feature = "std")]
+                preemptions: AtomicUsize::new(0),
+                #[cfg(feature = "alloc")]
+                strategy: None,
+                #[cfg(feature = "alloc")]
+                slow_wait: AtomicBool::new(false),
+                #[cfg(feature = "parking")]
+                parked: AtomicBool::new(false),
+                #[cfg(feature = "async")]
+                waiting: AtomicBool::new(false),
+                #[cfg(feature = "async")]
+                waker: Mutex::new(None),
+                #[cfg(feature = "strict")]
+                ops: [AtomicUsize::new(0), AtomicUsize::new(0)],
+                #[cfg(feature = "migration-stats")]
+                migrations: AtomicUsize::new(0),
+                #[cfg(feature = "stats")]
+                wait_stats: WaitRecord::new(),
+                #[cfg(feature = "checksum")]
+                checksums: [AtomicU32::new(0), AtomicU32::new(0)],
+                #[cfg(target_has_atomic = "64")]
+                tags: [AtomicU64::new(0), AtomicU64::new(0)],
+                left: AtomicU8::new(ATTACHED),
+                last_error: AtomicUsize::new(0),
+                pair: PairId::NONE,
+                name: None,
+            }
+        }
+    }
+
+    const_unless_loom! {
+        /// Counter at generation 0 belonging to `pair`
+        pub(crate) fn for_pair(pair: PairId) -> Self {
+            let mut counter = Self::new();
+            counter.pair = pair;
+            counter
+        }
+    }
+
+    /// Pair the counter belongs to, see [`Counter::for_pair`].
+    pub(crate) const fn pair(&self) -> PairId {
+        self.pair
+    }
+
+    /// Name the pair for debug output, before the counter is shared.
+    pub(crate) fn set_name(&mut self, name: &'static str) {
+        self.name = Some(name);
+    }
+
+    /// Name of the pair, if it was given one.
+    pub(crate) const fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// Read the generation of the owning thread.
+    ///
+    /// Call [`Counter::acquire`] once a new generation has been observed.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn load(&self) -> usize {
+        load(&self.generation, LOAD)
+    }
+
+    /// Synchronize memory with the owning thread after [`Counter::load`]
+    /// observed a new generation.
+    ///
+    /// Only does something with the `explicit-fences` feature.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn acquire(&self) {
+        #[cfg(feature = "explicit-fences")]
+        counted_fence(Acquire);
+    }
+
+    /// Spin until the owning thread publishes a generation other than
+    /// `generation`, then synchronize memory with it.
+    ///
+    /// `mine` is the counter of the calling thread, probes posted to it are
+    /// answered while spinning. With `std`, `contention` decides when to
+    /// stop spinning and block instead. With the `stats` feature the wait is
+    /// recorded in `mine`, see [`Counter::wait_stats`].
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn wait_past(
+        &self,
+        generation: usize,
+        mine: &Self,
+        #[cfg(feature = "std")] contention: &mut Contention,
+    ) {
+        #[cfg(all(feature = "stats", feature = "std"))]
+        let start = StdClock.now();
+        let spins = self.spin_past(
+            generation,
+            mine,
+            #[cfg(feature = "std")]
+            contention,
+        );
+        self.check_arrival(generation, mine);
+        #[cfg(feature = "stats")]
+        mine.wait_stats.record(
+            spins,
+            #[cfg(feature = "std")]
+            StdClock.now().saturating_sub(start),
+        );
+        #[cfg(not(feature = "stats"))]
+        let _ = spins;
+    }
+
+    /// [`Counter::wait_past`] without recording the wait, returns the number
+    /// of failed checks of the generation.
+    ///
+    /// Only the generation is loaded on every spin. Probes are answered and
+    /// the other thread is checked for having left every `CHECK_INTERVAL`
+    /// spins, and before blocking.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn spin_past(
+        &self,
+        generation: usize,
+        mine: &Self,
+        #[cfg(feature = "std")] contention: &mut Contention,
+    ) -> u32 {
+        /// Answer probes and check for a stall once every this many spins.
+        const CHECK_INTERVAL: u32 = 64;
+
+        #[cfg(feature = "alloc")]
+        if mine.slow_wait.load(Relaxed) {
+            return self.wait_past_slow(
+                generation,
+                mine,
+                #[cfg(feature = "std")]
+                contention,
+            );
+        }
+        #[cfg_attr(not(any(feature = "std", feature = "stats")), allow(unused_mut))]
+        let mut spins = 0_u32;
+        let mut checks = 0_u32;
+        while {
+            // Signal to processor (not OS) that we are in a spinloop.
+            // Performance seems to improve by a tiny bit with this.
+            spin_loop();
+            self.load() == generation
+        } {
+            checks = checks.wrapping_add(1);
+            #[cfg(feature = "std")]
+            let block = contention.spin(&mut spins);
+            #[cfg(not(feature = "std"))]
+            let block = false;
+            if block || checks.is_multiple_of(CHECK_INTERVAL) {
+                mine.answer_probe();
+                self.check_stalled(generation, mine);
+            }
+            #[cfg(feature = "std")]
+            if block {
+                self.block(generation);
+            }
+            #[cfg(all(feature = "stats", not(feature = "std")))]
+            {
+                spins = spins.saturating_add(1);
+            }
+        }
+        self.acquire();
+        #[cfg(feature = "std")]
+        contention.finish(spins);
+        spins
+    }
+
+    /// [`Counter::wait_past`] for a thread with a [`WaitStrategy`], a spin
+    /// budget or a preemption threshold, in that order of precedence.
+    #[cfg(feature = "alloc")]
+    #[cold]
+    #[inline(never)]
+    fn wait_past_slow(
+        &self,
+        generation: usize,
+        mine: &Self,
+        #[cfg(feature = "std")] contention: &mut Contention,
+    ) -> u32 {
+        if let Some(strategy) = mine.strategy.as_deref() {
+            return self.wait_past_strategy(generation, mine, strategy);
+        }
+        #[cfg(feature = "std")]
+        if let Some(budget) = mine.spin_budget() {
+            return self.wait_past_budget(generation, mine, contention, budget);
+        }
+        #[cfg(feature = "std")]
+        if let Some(threshold) = mine.preemption_threshold() {
+            return self.wait_past_preemptible(generation, mine, contention, threshold);
+        }
+        unreachable!("slow wait without a strategy, spin budget or preemption threshold")
+    }
+
+    /// Recompute [`Counter::slow_wait`] after a wait setting of the owning
+    /// thread changed.
+    #[cfg(feature = "alloc")]
+    fn update_slow_wait(&self) {
+        let slow = self.strategy.is_some();
+        #[cfg(feature = "std")]
+        let slow = slow
+            || self.budget[1].load(Relaxed) != 0
+            || self.preemption_threshold.load(Relaxed) != 0;
+        self.slow_wait.store(slow, Relaxed);
+    }
+
+    /// [`Counter::wait_past`] for a thread with a [`WaitStrategy`].
+    #[cfg(feature = "alloc")]
+    fn wait_past_strategy(
+        &self,
+        generation: usize,
+        mine: &Self,
+        strategy: &dyn WaitStrategy,
+    ) -> u32 {
+        let mut spins = 0_u32;
+        while self.load() == generation {
+            mine.answer_probe();
+            self.check_stalled(generation, mine);
+            spins = spins.saturating_add(1);
+            strategy.pause(spins);
+        }
+        self.acquire();
+        spins
+    }
+
+    /// Set the [`WaitStrategy`] of the owning thread, before the counter is
+    /// shared.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn set_strategy(&mut self, strategy: Box<dyn WaitStrategy>) {
+        self.strategy = Some(strategy);
+        self.update_slow_wait();
+    }
+
+    /// [`Counter::wait_past`] for a thread with a [`SpinBudget`].
+    #[cfg(feature = "std")]
+    fn wait_past_budget(
+        &self,
+        generation: usize,
+        mine: &Self,
+        contention: &mut Contention,
+        budget: SpinBudget,
+    ) -> u32 {
+        let clock = StdClock;
+        let mut spinning_since = clock.now();
+        let mut spins = 0_u32;
+        while {
+            spin_loop();
+            self.load() == generation
+        } {
+            mine.answer_probe();
+            self.check_stalled(generation, mine);
+            spins = spins.saturating_add(1);
+            if clock.now().saturating_sub(spinning_since) >= budget.spin {
+                contention.slept();
+                clock.sleep(budget.sleep);
+                spinning_since = clock.now();
+            }
+        }
+        self.acquire();
+        contention.finish(spins);
+        spins
+    }
+
+    /// [`Counter::wait_past`] for a thread with a preemption threshold:
+    /// spin without yielding until `threshold` has passed, then suspect that
+    /// the other thread was preempted and block for the rest of this wait
+    /// only, without reducing the spin budget of later waits.
+    #[cfg(feature = "std")]
+    fn wait_past_preemptible(
+        &self,
+        generation: usize,
+        mine: &Self,
+        contention: &mut Contention,
+        threshold: Duration,
+    ) -> u32 {
+        /// Read the clock once every this many spins.
+        const CLOCK_INTERVAL: u32 = 64;
+
+        let clock = StdClock;
+        let start = clock.now();
+        let mut spins = 0_u32;
+        let mut preempted = false;
+        while {
+            spin_loop();
+            self.load() == generation
+        } {
+            mine.answer_probe();
+            self.check_stalled(generation, mine);
+            spins = spins.wrapping_add(1);
+            if preempted {
+                contention.slept();
+                self.block(generation);
+            } else if spins.is_multiple_of(CLOCK_INTERVAL)
+                && clock.now().saturating_sub(start) >= threshold
+            {
+                preempted = true;
+                mine.preemptions
+                    .store(mine.preemptions.load(Relaxed).wrapping_add(1), Relaxed);
+            }
+        }
+        self.acquire();
+        contention.finish_preemptible(preempted);
+        spins
+    }
+
+    /// Set the preemption threshold of the owning thread, see
+    /// `set_preemption_threshold` on the handles.
+    #[cfg(feature = "std")]
+    pub(crate) fn set_preemption_threshold(&self, threshold: Option<Duration>) {
+        let micros = threshold.map_or(0, |threshold| {
+            u32::try_from(threshold.as_micros())
+                .unwrap_or(u32::MAX)
+                .max(1)
+        });
+        self.preemption_threshold.store(micros, Relaxed);
+        self.update_slow_wait();
+    }
+
+    /// The preemption threshold of the owning thread, if any.
+    #[cfg(feature = "std")]
+    pub(crate) fn preemption_threshold(&self) -> Option<Duration> {
+        match self.preemption_threshold.load(Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros.into())),
+        }
+    }
+
+    /// Number of waits of the owning thread that suspected preemption.
+    #[cfg(feature = "std")]
+    pub(crate) fn preemptions(&self) -> u64 {
+        u64::try_from(self.preemptions.load(Relaxed)).unwrap_or(u64::MAX)
+    }
+
+    /// Set the [`SpinBudget`] of the owning thread.
+    #[cfg(feature = "std")]
+    pub(crate) fn set_spin_budget(&self, budget: Option<SpinBudget>) {
+        let (spin, sleep) = budget.map_or((0, 0), SpinBudget::pack);
+        self.budget[0].store(spin, Relaxed);
+        self.budget[1].store(sleep, Relaxed);
+        self.update_slow_wait();
+    }
+
+    /// The [`SpinBudget`] of the owning thread, if any.
+    #[cfg(feature = "std")]
+    pub(crate) fn spin_budget(&self) -> Option<SpinBudget> {
+        match self.budget[1].load(Relaxed) {
+            0 => None,
+            sleep => Some(SpinBudget::unpack(self.budget[0].load(Relaxed), sleep)),
+        }
+    }
+
+    /// Like [`Counter::wait_past`], but call `yield_to` instead of spinning
+    /// or blocking, for threads that are scheduled cooperatively.
+    #[inline]
+    pub(crate) fn wait_past_with<F: FnMut()>(
+        &self,
+        generation: usize,
+        mine: &Self,
+        mut yield_to: F,
+    ) {
+        while self.load() == generation {
+            mine.answer_probe();
+            yield_to();
+        }
+        self.acquire();
+    }
+
+    /// Publish a new generation, waking the other thread if it is parked.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn store(&self, generation: usize) {
+        #[cfg(feature = "test-hooks")]
+        crate::test_hooks::before_store();
+        #[cfg(feature = "explicit-fences")]
+        counted_fence(Release);
+        store(&self.generation, generation, STORE);
+        #[cfg(feature = "parking")]
+        {
+            // Pairs with the fence in `block`: either we see `parked` or the
+            // parking thread sees the new generation.
+            fence(SeqCst);
+            if self.parked.load(Relaxed) {
+                self.unpark();
+            }
+        }
+        #[cfg(feature = "async")]
+        {
+            // Pairs with the fence in `register_waker`.
+            fence(SeqCst);
+            if self.waiting.load(Relaxed) {
+                self.wake_waiter();
+            }
+        }
+    }
+
+    /// Have the next [`Counter::store`] wake `waker`, for a task of the
+    /// other thread that awaits a new generation. Check the generation again
+    /// afterwards, it may have been stored before the waker was registered.
+    #[cfg(feature = "async")]
+    #[cold]
+    pub(crate) fn register_waker(&self, waker: &Waker) {
+        {
+            let mut slot = self.waker.lock().unwrap_or_else(PoisonError::into_inner);
+            match &mut *slot {
+                Some(registered) if registered.will_wake(waker) => {},
+                slot => *slot = Some(waker.clone()),
+            }
+            self.waiting.store(true, Relaxed);
+        }
+        // Either the owner sees `waiting`, or the caller sees the new
+        // generation.
+        fence(SeqCst);
+    }
+
+    /// Wake the task registered with [`Counter::register_waker`].
+    #[cfg(feature = "async")]
+    #[cold]
+    fn wake_waiter(&self) {
+        let waker = {
+            let mut slot = self.waker.lock().unwrap_or_else(PoisonError::into_inner);
+            self.waiting.store(false, Relaxed);
+            slot.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Mark the owning handle as gone for good, and arrive at `generation`
+    /// (the next sync point) with `op` on its behalf, so that the other
+    /// thread is not left waiting there. Does nothing if it already left.
+    pub(crate) fn detach(&self, generation: usize, op: Op) {
+        if self.left.load(Relaxed) != ATTACHED {
+            return;
+        }
+        // Marked before arriving, so that the other thread sees the mark
+        // once it sees the arrival, see `Counter::is_gone`.
+        self.left.store(DETACHING, Relaxed);
+        self.tag(generation, op);
+        self.store(generation);
+        self.left.store(DETACHED, Release);
+    }
+
+    /// Mark the owning handle as gone for good without arriving at the next
+    /// sync point, for a thread that is panicking halfway through a
+    /// generation. Does nothing if it already left.
+    #[cfg(feature = "std")]
+    pub(crate) fn hang_up(&self) {
+        if self.left.load(Relaxed) == ATTACHED {
+            self.left.store(HUNG_UP, Release);
+        }
+    }
+
+    /// True if the owning handle has left for good without arriving, see
+    /// [`Counter::hang_up`].
+    pub(crate) fn hung_up(&self) -> bool {
+        self.left.load(Acquire) == HUNG_UP
+    }
+
+    /// True if the owning handle has left for good.
+    pub(crate) fn has_left(&self) -> bool {
+        self.left.load(Acquire) != ATTACHED
+    }
+
+    /// True if the owning handle has left for good, and the other thread, at
+    /// `generation`, has already passed the last sync point it arrived at.
+    pub(crate) fn is_gone(&self, generation: usize) -> bool {
+        // A detaching handle is about to publish its arrival, which decides
+        // whether the other thread has passed it.
+        let mut left = self.left.load(Acquire);
+        while left == DETACHING {
+            spin_loop();
+            left = self.left.load(Acquire);
+        }
+        // The generation is published before the handle is marked as left.
+        left != ATTACHED && self.load() == generation
+    }
+
+    /// [`Counter::wait_past`] that gives up and returns false once `clock`
+    /// reaches `deadline` (never for `None`).
+    ///
+    /// Yields instead of blocking, a parked thread would miss the deadline.
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn wait_past_until(
+        &self,
+        generation: usize,
+        mine: &Self,
+        #[cfg(feature = "std")] contention: &mut Contention,
+        deadline: Option<Duration>,
+        clock: &impl Clock,
+    ) -> bool {
+        /// Read the clock once every this many spins.
+        const CLOCK_INTERVAL: u32 = 64;
+
+        let mut checks = 0_u32;
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        while {
+            spin_loop();
+            self.load() == generation
+        } {
+            mine.answer_probe();
+            checks = checks.wrapping_add(1);
+            if checks.is_multiple_of(CLOCK_INTERVAL)
+                && deadline.is_some_and(|deadline| clock.now() >= deadline)
+            {
+                return false;
+            }
+            #[cfg(feature = "std")]
+            if contention.spin(&mut spins) {
+                crate::os::yield_now();
+            }
+        }
+        self.acquire();
+        #[cfg(feature = "std")]
+        contention.finish(spins);
+        true
+    }
+
+    /// [`Counter::wait_past`] that gives up and returns false if the owning
+    /// handle hangs up (see [`Counter::hang_up`]) instead of arriving.
+    ///
+    /// Yields instead of blocking, since a hang up does not wake a parked
+    /// thread.
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn wait_past_checked(
+        &self,
+        generation: usize,
+        mine: &Self,
+        #[cfg(feature = "std")] contention: &mut Contention,
+    ) -> bool {
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        #[cfg(feature = "alloc")]
+        let mut pauses = 0_u32;
+        while {
+            spin_loop();
+            self.load() == generation
+        } {
+            if self.left.load(Acquire) == HUNG_UP {
+                return false;
+            }
+            mine.answer_probe();
+            #[cfg(feature = "alloc")]
+            if let Some(strategy) = mine.strategy.as_deref() {
+                pauses = pauses.saturating_add(1);
+                strategy.pause(pauses);
+                continue;
+            }
+            #[cfg(feature = "std")]
+            if contention.spin(&mut spins) {
+                crate::os::yield_now();
+            }
+        }
+        self.acquire();
+        #[cfg(feature = "std")]
+        contention.finish(spins);
+        true
+    }
+
+    /// Record the operation the owning thread performs to reach `generation`,
+    /// call before [`Counter::store`] publishes it.
+    ///
+    /// Only does something with the `strict` feature.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn tag(&self, generation: usize, op: Op) {
+        #[cfg(feature = "strict")]
+        self.ops[generation & 1].store(op.encode(), Relaxed);
+        #[cfg(not(feature = "strict"))]
+        let _ = (generation, op);
+    }
+
+    /// Check that the owning thread reached `generation` with the same
+    /// operation as the calling thread, call after [`Counter::acquire`].
+    /// `mine` is the counter of the calling thread, which records the error.
+    ///
+    /// Only does something with the `strict` feature.
+    ///
+    /// # Panics
+    /// If the operations differ, the threads have gotten out of step.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn check(&self, generation: usize, op: Op, mine: &Self) {
+        #[cfg(feature = "strict")]
+        {
+            let theirs = Op::decode(self.ops[generation & 1].load(Relaxed));
+            if theirs != Some(op) {
+                mine.record_error(ErrorKind::StrictViolation, generation);
+                strict_violation(generation, op, theirs);
+            }
+        }
+        #[cfg(not(feature = "strict"))]
+        let _ = (generation, op, mine);
+    }
+
+    /// Panic if the owning handle has left for good while the other thread,
+    /// at `generation`, waits for it to arrive, which it never will. Only
+    /// with debug assertions or the `checked` feature.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn check_stalled(&self, generation: usize, mine: &Self) {
+        #[cfg(any(debug_assertions, feature = "checked"))]
+        if self.is_gone(generation) {
+            mine.record_error(ErrorKind::Unbalanced, generation);
+            unbalanced(generation, self);
+        }
+        #[cfg(not(any(debug_assertions, feature = "checked")))]
+        let _ = (generation, mine);
+    }
+
+    /// Panic if the owning thread, waited on by the other thread at
+    /// `generation`, is not one or two generations ahead of it once the
+    /// wait completes, which no balanced sequence of sync points leads to.
+    /// Only with debug assertions or the `checked` feature.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn check_arrival(&self, generation: usize, mine: &Self) {
+        // Not counted with `count-atomics`, like the parking slow path.
+        #[cfg(any(debug_assertions, feature = "checked"))]
+        if !(1..=2).contains(&self.generation.load(Relaxed).wrapping_sub(generation)) {
+            mine.record_error(ErrorKind::Unbalanced, generation);
+            unbalanced(generation, self);
+        }
+        #[cfg(not(any(debug_assertions, feature = "checked")))]
+        let _ = (generation, mine);
+    }
+
+    /// Record an error the owning thread ran into at `generation`, see
+    /// [`LastError`].
+    #[cold]
+    pub(crate) fn record_error(&self, kind: ErrorKind, generation: usize) {
+        self.last_error
+            .store(LastError::pack(kind, generation), Relaxed);
+    }
+
+    /// Record the checksum of the data sent at `generation`, before
+    /// publishing it.
+    #[cfg(feature = "checksum")]
+    pub(crate) fn set_checksum(&self, generation: usize, checksum: u32) {
+        self.checksums[generation & 1].store(checksum, Relaxed);
+    }
+
+    /// Checksum of the data the owning thread sent at `generation`.
+    #[cfg(feature = "checksum")]
+    pub(crate) fn checksum(&self, generation: usize) -> u32 {
+        self.checksums[generation & 1].load(Relaxed)
+    }
+
+    /// Set the tag sent with `generation`, before publishing it.
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) fn set_tag(&self, generation: usize, tag: u64) {
+        self.tags[generation & 1].store(tag, Relaxed);
+    }
+
+    /// Tag the owning thread sent with `generation`.
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) fn sent_tag(&self, generation: usize) -> u64 {
+        self.tags[generation & 1].load(Relaxed)
+    }
+
+    /// The last error recorded by the owning thread, packed, see
+    /// [`LastError::unpack`].
+    pub(crate) fn last_error(&self) -> usize {
+        self.last_error.load(Relaxed)
+    }
+
+    /// Publish `generation + 1` on behalf of the owning thread, unless it has
+    /// already published it (or a later generation).
+    ///
+    /// Unlike [`Counter::store`] this can be called from any thread, at the
+    /// cost of a compare-and-swap. Only wakers arriving on behalf of a handle
+    /// use it, which may race with the owning thread arriving itself, never
+    /// a wait or swap.
+    #[inline]
+    pub(crate) fn advance_from(&self, generation: usize) {
+        #[cfg(feature = "explicit-fences")]
+        counted_fence(Release);
+        #[cfg(feature = "count-atomics")]
+        crate::atomic_counts::record_read_modify_write();
+        let advanced = self
+            .generation
+            .compare_exchange(generation, generation.wrapping_add(1), STORE, Relaxed)
+            .is_ok();
+        #[cfg(feature = "parking")]
+        if advanced {
+            fence(SeqCst);
+            if self.parked.load(Relaxed) {
+                self.unpark();
+            }
+        }
+        #[cfg(feature = "async")]
+        if advanced {
+            fence(SeqCst);
+            if self.waiting.load(Relaxed) {
+                self.wake_waiter();
+            }
+        }
+        #[cfg(not(any(feature = "parking", feature = "async")))]
+        let _ = advanced;
+    }
+
+    /// Read the sub-barrier generation of the owning thread.
+    ///
+    /// Call [`Counter::acquire`] once a new generation has been observed.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn load_sub(&self) -> usize {
+        load(&self.sub_generation, LOAD)
+    }
+
+    /// Publish a new sub-barrier generation.
+    #[inline]
+    pub(crate) fn store_sub(&self, sub_generation: usize) {
+        #[cfg(feature = "explicit-fences")]
+        counted_fence(Release);
+        store(&self.sub_generation, sub_generation, STORE);
+    }
+
+    /// Post a probe for the owning thread to answer, returns its ticket.
+    ///
+    /// Must only be called by the other thread.
+    #[inline]
+    pub(crate) fn post_probe(&self) -> usize {
+        let ticket = load(&self.probe_request, Relaxed).wrapping_add(1);
+        store(&self.probe_request, ticket, Relaxed);
+        ticket
+    }
+
+    /// True if the owning thread has answered the probe with `ticket`.
+    #[inline]
+    pub(crate) fn probe_answered(&self, ticket: usize) -> bool {
+        load(&self.probe_response, Relaxed) == ticket
+    }
+
+    /// Answer the last probe posted by the other thread.
+    ///
+    /// Must only be called by the owning thread.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn answer_probe(&self) {
+        let request = load(&self.probe_request, Relaxed);
+        if request != load(&self.probe_response, Relaxed) {
+            store(&self.probe_response, request, Relaxed);
+        }
+    }
+
+    /// Mark `generation` as urgent, it is published along with it by
+    /// [`Counter::store`].
+    ///
+    /// Must only be called by the owning thread.
+    #[inline]
+    pub(crate) fn mark_urgent(&self, generation: usize) {
+        store(&self.urgent[generation & 1], generation, Relaxed);
+    }
+
+    /// True if the owning thread marked `generation` as urgent, which it
+    /// must have published.
+    #[inline]
+    pub(crate) fn is_urgent(&self, generation: usize) -> bool {
+        load(&self.urgent[generation & 1], Relaxed) == generation
+    }
+
+    /// Report the CPU the owning thread runs on.
+    #[cfg(feature = "std")]
+    pub(crate) fn set_cpu(&self, cpu: Option<usize>) {
+        self.cpu
+            .store(cpu.map_or(0, |cpu| cpu.wrapping_add(1)), Relaxed);
+    }
+
+    /// The CPU the owning thread last reported running on.
+    #[cfg(feature = "std")]
+    pub(crate) fn cpu(&self) -> Option<usize> {
+        self.cpu.load(Relaxed).checked_sub(1)
+    }
+
+    /// Sample the CPU of the owning thread after it reached `generation`,
+    /// every [`SAMPLE_INTERVAL`](crate::os::migration::SAMPLE_INTERVAL)
+    /// generations, counting migrations.
+    ///
+    /// Only does something with the `migration-stats` feature.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn sample_cpu(&self, generation: usize) {
+        #[cfg(feature = "migration-stats")]
+        if generation.is_multiple_of(crate::os::migration::SAMPLE_INTERVAL) {
+            self.record_cpu(crate::os::migration::current_cpu());
+        }
+        #[cfg(not(feature = "migration-stats"))]
+        let _ = generation;
+    }
+
+    /// Report the CPU the owning thread runs on, counting a migration if it
+    /// changed.
+    #[cfg(feature = "migration-stats")]
+    #[cold]
+    fn record_cpu(&self, cpu: Option<usize>) {
+        let previous = self.cpu();
+        if previous.is_some() && cpu.is_some() && previous != cpu {
+            let migrations = self.migrations.load(Relaxed);
+            self.migrations.store(migrations.wrapping_add(1), Relaxed);
+        }
+        self.set_cpu(cpu);
+    }
+
+    /// The owning handle was deliberately moved to another thread: report
+    /// the CPU of the new thread without counting a migration, and start
+    /// counting migrations from zero.
+    #[cfg(feature = "std")]
+    #[cold]
+    pub(crate) fn rebase_cpu(&self) {
+        self.set_cpu(crate::os::smt::current_cpu());
+        #[cfg(feature = "migration-stats")]
+        self.migrations.store(0, Relaxed);
+    }
+
+    /// Number of migrations of the owning thread, see [`Counter::sample_cpu`].
+    #[cfg(feature = "migration-stats")]
+    pub(crate) fn migrations(&self) -> usize {
+        self.migrations.load(Relaxed)
+    }
+
+    /// Statistics on the waits of the owning thread.
+    #[cfg(feature = "stats")]
+    pub(crate) fn wait_stats(&self) -> WaitStats {
+        self.wait_stats.stats()
+    }
+
+    /// Give the CPU away for a while, until `generation` has likely changed.
+    ///
+    /// Without the `parking` feature this yields to the OS scheduler, with it
+    /// the thread sleeps until the owner of the counter publishes a new
+    /// generation.
+    #[cfg(feature = "std")]
+    #[cold]
+    pub(crate) fn block(&self, generation: usize) {
+        #[cfg(not(feature = "parking"))]
+        {
+            let _ = generation;
+            crate::os::yield_now();
+        }
+        #[cfg(feature = "parking")]
+        {
+            self.parked.store(true, Relaxed);
+            fence(SeqCst);
+            let key = self.key();
+            // SAFETY:
+            // The closures do not panic or call into `parking_lot_core`.
+            unsafe {
+                parking_lot_core::park(
+                    key,
+                    || self.load() == generation,
+                    || {},
+                    |_, _| {},
+                    parking_lot_core::DEFAULT_PARK_TOKEN,
+                    None,
+                );
+            }
+            self.parked.store(false, Relaxed);
+        }
+    }
+
+    /// Wake the thread parked on this counter.
+    #[cfg(feature = "parking")]
+    #[cold]
+    fn unpark(&self) {
+        // SAFETY:
+        // Parking is keyed on this counter, which is only parked on in `block`.
+        unsafe {
+            parking_lot_core::unpark_all(self.key(), parking_lot_core::DEFAULT_UNPARK_TOKEN);
+        }
+    }
+
+    /// Address of the generation, see `counter_ptrs` on the handles.
+    #[cfg(not(loom))]
+    pub(crate) const fn as_ptr(&self) -> *const AtomicUsize {
+        &self.generation
+    }
+
+    /// Parking key: the address of the counter.
+    #[cfg(feature = "parking")]
+    fn key(&self) -> usize {
+        self.as_ptr() as usize
+    }
+}
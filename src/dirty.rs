@@ -0,0 +1,264 @@
+//! Contains [`DirtyRendezvousData`]
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::ops::Range;
+use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+use crate::padded::Padded;
+use crate::{Rendezvous, SyncPoint};
+
+/// Replicas and published dirty ranges of a [`DirtyRendezvousData`] pair.
+struct DirtyShared<U> {
+    /// One replica per handle, which handle owns which replica changes when
+    /// they are exchanged
+    buffers: [Padded<Box<[UnsafeCell<U>]>>; 2],
+    /// Dirty range of each side as `[start, end]`, published before the
+    /// first wait of a swap, indexed by side and swap parity. When no
+    /// replica changed the second wait is skipped, and a thread can publish
+    /// its next range while the other is still reading this one.
+    dirty: [Padded<[[AtomicUsize; 2]; 2]>; 2],
+    /// Exchange buffers instead of copying above this many dirty elements
+    exchange_above: usize,
+}
+// SAFETY:
+// UnsafeCell needs special consideration. Outside of a swap each replica is
+// only accessed by the handle owning it. Within a swap each thread writes
+// one replica and reads the other, at ranges that never overlap with what
+// the other thread accesses, see `DirtyRendezvousData::swap`.
+unsafe impl<U: Send + Sync> Sync for DirtyShared<U> {}
+impl<U> DirtyShared<U> {
+    /// Pointer to the first element of replica `buffer`.
+    fn buffer(&self, buffer: usize) -> *mut U {
+        UnsafeCell::raw_get(self.buffers[buffer].as_ptr())
+    }
+}
+
+/// Synchronise execution between threads that each hold a replica of the
+/// same slice, exchanging only the ranges that changed.
+///
+/// Changes are made through [`DirtyRendezvousData::get_mut`], which records
+/// the span of elements touched since the last swap. At a swap each
+/// thread's dirty span is copied into the other thread's replica, so that
+/// both replicas are equal again afterwards, and both threads keep working
+/// on a buffer that is warm in their own cache. Where both threads changed
+/// the same elements, the changes of the first handle win.
+///
+/// When the dirty spans together exceed a threshold (half the slice by
+/// default, see [`DirtyRendezvousData::with_threshold`]) copying the other
+/// thread's changes gets expensive, so the replicas are exchanged instead,
+/// and each thread copies its own changes into the replica it received,
+/// reading them from its own cache. The choice is made each generation, and
+/// both threads always make the same one.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::DirtyRendezvousData;
+/// use std::thread;
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = DirtyRendezvousData::new(&[0; 1000]);
+/// let handle = thread::spawn(move || {
+///     their_rendezvous.get_mut(900..902).fill(2);
+///     their_rendezvous.swap()[..2].to_vec()
+/// });
+/// my_rendezvous.get_mut(0..2).fill(1);
+/// assert_eq!(my_rendezvous.swap()[900..902], [2, 2]);
+/// assert_eq!(handle.join().unwrap(), [1, 1]);
+/// ```
+#[non_exhaustive]
+pub struct DirtyRendezvousData<U: Copy + Send + Sync> {
+    /// Synchronizes the generations
+    rendezvous: Rendezvous,
+    /// Index of the replica this handle owns
+    buffer: usize,
+    /// True for the first handle, whose changes win
+    first: bool,
+    /// Span of elements changed since the last swap
+    dirty: Range<usize>,
+    /// Number of swaps that exchanged the replicas
+    exchanges: usize,
+    /// Number of swaps
+    swaps: usize,
+    /// Replicas and dirty ranges
+    shared: Arc<DirtyShared<U>>,
+}
+impl<U: Copy + Send + Sync> DirtyRendezvousData<U> {
+    /// Create a linked pair of [`DirtyRendezvousData`], both replicas
+    /// starting out as a copy of `initial`.
+    #[must_use]
+    #[inline]
+    pub fn new(initial: &[U]) -> (Self, Self) {
+        Self::with_threshold(initial, initial.len() / 2)
+    }
+
+    /// Like [`DirtyRendezvousData::new`], but exchange replicas instead of
+    /// copying changes when the dirty spans of both threads together cover
+    /// more than `exchange_above` elements.
+    #[must_use]
+    #[inline]
+    pub fn with_threshold(initial: &[U], exchange_above: usize) -> (Self, Self) {
+        let replica = || Padded::new(initial.iter().copied().map(UnsafeCell::new).collect());
+        let shared = Arc::new(DirtyShared {
+            buffers: [replica(), replica()],
+            dirty: [(); 2].map(|()| {
+                Padded::new([(); 2].map(|()| [AtomicUsize::new(0), AtomicUsize::new(0)]))
+            }),
+            exchange_above,
+        });
+        let (first, second) = Rendezvous::new();
+        let handle = |rendezvous, buffer| Self {
+            rendezvous,
+            buffer,
+            first: buffer == 0,
+            dirty: 0..0,
+            exchanges: 0,
+            swaps: 0,
+            shared: Arc::clone(&shared),
+        };
+        (handle(first, 0), handle(second, 1))
+    }
+
+    /// This thread's replica.
+    #[must_use]
+    #[inline]
+    pub fn get(&self) -> &[U] {
+        // SAFETY:
+        // Only this handle accesses its replica outside of a swap, and
+        // swapping needs `&mut self`.
+        unsafe { core::slice::from_raw_parts(self.shared.buffer(self.buffer), self.len()) }
+    }
+
+    /// Get `range` of this thread's replica for writing, and mark it as
+    /// dirty.
+    ///
+    /// # Panics
+    /// If `range` is out of bounds.
+    #[inline]
+    pub fn get_mut(&mut self, range: Range<usize>) -> &mut [U] {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range {range:?} out of bounds of replica of length {}",
+            self.len()
+        );
+        self.dirty = union(&self.dirty, &range);
+        // SAFETY:
+        // In bounds, and see `get`.
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self.shared.buffer(self.buffer).add(range.start),
+                range.len(),
+            )
+        }
+    }
+
+    /// Span of elements marked dirty since the last swap.
+    #[must_use]
+    #[inline]
+    pub fn dirty(&self) -> Range<usize> {
+        self.dirty.clone()
+    }
+
+    /// Number of elements in each replica.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shared.buffers[0].len()
+    }
+
+    /// True if the replicas are empty.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of swaps that exchanged the replicas instead of copying.
+    #[must_use]
+    #[inline]
+    pub const fn exchanges(&self) -> usize {
+        self.exchanges
+    }
+
+    /// Synchronize with the other thread and apply its changes, then get
+    /// this thread's (up to date) replica.
+    #[inline]
+    pub fn swap(&mut self) -> &[U] {
+        let (side, parity) = (usize::from(!self.first), self.swaps & 1);
+        self.swaps = self.swaps.wrapping_add(1);
+        let [start, end] = &self.shared.dirty[side][parity];
+        start.store(self.dirty.start, Relaxed);
+        end.store(self.dirty.end, Relaxed);
+        self.rendezvous.wait();
+        let [start, end] = &self.shared.dirty[side ^ 1][parity];
+        let theirs = start.load(Relaxed)..end.load(Relaxed);
+        let mine = core::mem::replace(&mut self.dirty, 0..0);
+        let (leader, follower) = if self.first {
+            (mine, theirs)
+        } else {
+            (theirs, mine)
+        };
+        if leader.is_empty() && follower.is_empty() {
+            // Both threads skip the second wait.
+            return self.get();
+        }
+        let follower_only = subtract(&follower, &leader);
+        let exchange = leader.len() + follower.len() > self.shared.exchange_above;
+        // Every element in `leader` or `follower` is copied by exactly one
+        // thread, from the replica the other thread owns after the swap:
+        // when exchanging, its own changes from the replica it handed over,
+        // otherwise the changes of the other thread.
+        let ranges = if exchange == self.first {
+            [leader, 0..0]
+        } else {
+            follower_only
+        };
+        if exchange {
+            self.buffer ^= 1;
+            self.exchanges = self.exchanges.wrapping_add(1);
+        }
+        let source = self.shared.buffer(self.buffer ^ 1);
+        let destination = self.shared.buffer(self.buffer);
+        for range in ranges.into_iter().filter(|range| !range.is_empty()) {
+            // SAFETY:
+            // Both replicas have `len` elements, and the ranges were checked
+            // in `get_mut`. This thread writes its replica only at `ranges`,
+            // which the other thread neither reads nor writes in this
+            // swap, and the other thread writes the replica read here only
+            // at ranges disjoint from `ranges`. The second wait keeps both
+            // threads from using their replicas before all copies are done.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    source.add(range.start),
+                    destination.add(range.start),
+                    range.len(),
+                );
+            }
+        }
+        self.rendezvous.wait();
+        self.get()
+    }
+}
+impl<U: Copy + Send + Sync> SyncPoint for DirtyRendezvousData<U> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.swap();
+    }
+}
+
+/// Smallest range containing `a` and `b`, ignoring empty ranges.
+fn union(a: &Range<usize>, b: &Range<usize>) -> Range<usize> {
+    match (a.is_empty(), b.is_empty()) {
+        (true, _) => b.clone(),
+        (_, true) => a.clone(),
+        _ => a.start.min(b.start)..a.end.max(b.end),
+    }
+}
+
+/// The (up to two) parts of `a` not in `b`.
+fn subtract(a: &Range<usize>, b: &Range<usize>) -> [Range<usize>; 2] {
+    if b.is_empty() {
+        return [a.clone(), 0..0];
+    }
+    [a.start..a.end.min(b.start), a.start.max(b.end)..a.end]
+}
@@ -0,0 +1,140 @@
+//! Contains [`RendezvousDataDyn`] and [`WrongType`]
+
+use alloc::boxed::Box;
+use core::any::{type_name, Any};
+use core::fmt;
+
+use crate::{RendezvousData, SwapPoint, SyncPoint};
+
+/// Payload of a [`RendezvousDataDyn`].
+type Payload = Box<dyn Any + Send + Sync>;
+
+/// A [`RendezvousData`] whose payload type is chosen at runtime, for plugin
+/// architectures where host and plugins do not share the payload type at
+/// compile time.
+///
+/// Each buffer holds a value of any type, which is checked against the
+/// expected type ([`core::any::TypeId`]) when accessed. Either thread can
+/// replace its buffer with a value of another type between swaps, the next
+/// swap hands the new type over. Accessing the payload costs a type check
+/// and the buffers are boxed, an indirection a typed pair does not have.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::RendezvousDataDyn;
+/// use std::thread;
+///
+/// let (mut host, mut plugin) = RendezvousDataDyn::new(0_u32, 0_u32);
+/// let handle = thread::spawn(move || {
+///     plugin.swap();
+///     // reconfigure: from now on this side sends strings
+///     plugin.set(String::from("hello"));
+///     plugin.swap();
+/// });
+/// assert_eq!(*host.swap_as::<u32>().unwrap(), 0);
+/// assert!(host.swap_as::<u32>().is_err());
+/// assert_eq!(host.current_as::<String>().unwrap(), "hello");
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct RendezvousDataDyn {
+    /// The typed pair, of boxed values
+    inner: RendezvousData<Payload>,
+}
+impl RendezvousDataDyn {
+    /// Create a linked pair, the first handle starts out with `data1` and
+    /// the second with `data2`.
+    #[must_use]
+    #[inline]
+    pub fn new<A, B>(data1: A, data2: B) -> (Self, Self)
+    where
+        A: Any + Send + Sync,
+        B: Any + Send + Sync,
+    {
+        let (first, second) = RendezvousData::new(Box::new(data1) as Payload, Box::new(data2));
+        (Self { inner: first }, Self { inner: second })
+    }
+
+    /// Swap data with the other thread, see [`RendezvousData::swap`], and get
+    /// the boxed payload, which can be replaced with a value of another
+    /// type.
+    #[inline]
+    pub fn swap(&mut self) -> &mut Box<dyn Any + Send + Sync> {
+        self.inner.swap()
+    }
+
+    /// Swap data with the other thread and get the payload as a `T`.
+    ///
+    /// # Errors
+    /// If the payload is not a `T`. The swap has happened either way, and
+    /// the payload is available from [`RendezvousDataDyn::current`].
+    #[inline]
+    pub fn swap_as<T: Any>(&mut self) -> Result<&mut T, WrongType> {
+        downcast(self.inner.swap())
+    }
+
+    /// The payload returned by the last swap, without swapping.
+    #[inline]
+    pub fn current(&mut self) -> &mut Box<dyn Any + Send + Sync> {
+        self.inner.current_mut()
+    }
+
+    /// The payload returned by the last swap as a `T`, without swapping.
+    ///
+    /// # Errors
+    /// If the payload is not a `T`.
+    #[inline]
+    pub fn current_as<T: Any>(&mut self) -> Result<&mut T, WrongType> {
+        downcast(self.inner.current_mut())
+    }
+
+    /// Replace the payload with `value`, possibly of another type, which the
+    /// next swap hands over.
+    #[inline]
+    pub fn set<T: Any + Send + Sync>(&mut self, value: T) {
+        *self.inner.current_mut() = Box::new(value);
+    }
+
+    /// The typed pair of boxed values.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> RendezvousData<Box<dyn Any + Send + Sync>> {
+        self.inner
+    }
+}
+impl SyncPoint for RendezvousDataDyn {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.inner.swap();
+    }
+}
+impl SwapPoint for RendezvousDataDyn {
+    type Data = Box<dyn Any + Send + Sync>;
+    #[inline]
+    fn swap(&mut self) -> &mut Self::Data {
+        self.inner.swap()
+    }
+}
+
+/// Downcast `payload` to `T`.
+fn downcast<T: Any>(payload: &mut Payload) -> Result<&mut T, WrongType> {
+    // Not `payload.downcast_mut`, which would downcast the `Box` itself.
+    (**payload).downcast_mut::<T>().ok_or(WrongType {
+        expected: type_name::<T>(),
+    })
+}
+
+/// The payload of a [`RendezvousDataDyn`] is not of the expected type.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WrongType {
+    /// Name of the expected type, see [`core::any::type_name`]
+    pub expected: &'static str,
+}
+impl fmt::Display for WrongType {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the payload is not a {}", self.expected)
+    }
+}
+#[cfg(feature = "std")]
+impl crate::os::std::error::Error for WrongType {}
@@ -0,0 +1,113 @@
+//! Contains [`RendezvousExchange`]
+
+use alloc::sync::Arc;
+
+use crate::core_cell::SlotCell;
+use crate::padded::Padded;
+use crate::{Disconnected, Rendezvous};
+
+/// Values sent in one direction, exchange `n` (counting from 0) uses slot
+/// `n % 2`.
+///
+/// The sender fills a slot before arriving at the exchange, and the receiver
+/// empties it right after. The sender only fills the same slot again two
+/// exchanges later, after the receiver has arrived at the exchange in
+/// between, and with that emptied the slot.
+type Mailbox<T> = Arc<[Padded<SlotCell<Option<T>>>; 2]>;
+
+/// Moves owned values between a pair of threads, where the first thread sends
+/// `S` and receives `R`, and the second the other way around, for example
+/// requests and responses of different types.
+///
+/// Unlike [`RendezvousData`](crate::RendezvousData), the two sides need not
+/// hold the same type, and values are moved instead of borrowed, so the
+/// buffers are not reused.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::RendezvousExchange;
+/// use std::thread;
+///
+/// let (mut client, mut server) = RendezvousExchange::<String, usize>::new();
+/// let handle = thread::spawn(move || {
+///     let mut response = 0;
+///     for _ in 0..2 {
+///         let request = server.exchange(response);
+///         response = request.len();
+///     }
+///     server.exchange(response);
+/// });
+/// assert_eq!(client.exchange("hi".into()), 0);
+/// assert_eq!(client.exchange("hello".into()), 2);
+/// assert_eq!(client.exchange(String::new()), 5);
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct RendezvousExchange<S: Send, R: Send> {
+    /// Syncs the exchanges
+    rendezvous: Rendezvous,
+    /// Slot of the next exchange
+    slot: usize,
+    /// Values sent by this thread
+    outgoing: Mailbox<S>,
+    /// Values sent by the other thread
+    incoming: Mailbox<R>,
+}
+impl<S: Send, R: Send> RendezvousExchange<S, R> {
+    /// Create a linked pair, the first handle sends `S` and receives `R`.
+    #[must_use]
+    #[inline]
+    pub fn new() -> (Self, RendezvousExchange<R, S>) {
+        let (first, second) = Rendezvous::new();
+        let outgoing: Mailbox<S> = Arc::new([(); 2].map(|()| Padded::new(SlotCell::new(None))));
+        let incoming: Mailbox<R> = Arc::new([(); 2].map(|()| Padded::new(SlotCell::new(None))));
+        (
+            Self {
+                rendezvous: first,
+                slot: 0,
+                outgoing: Arc::clone(&outgoing),
+                incoming: Arc::clone(&incoming),
+            },
+            RendezvousExchange {
+                rendezvous: second,
+                slot: 0,
+                outgoing: incoming,
+                incoming: outgoing,
+            },
+        )
+    }
+
+    /// Send `value` to the other thread and receive the value it sends at
+    /// the same exchange.
+    ///
+    /// # Panics
+    /// If the other handle was dropped, see
+    /// [`RendezvousExchange::exchange_checked`].
+    #[inline]
+    pub fn exchange(&mut self, value: S) -> R {
+        self.exchange_checked(value)
+            .expect("the other handle of the exchange was dropped")
+    }
+
+    /// [`RendezvousExchange::exchange`] that returns instead of waiting
+    /// forever once the other handle is dropped, see
+    /// [`Rendezvous::wait_checked`].
+    ///
+    /// # Errors
+    /// If the other handle was dropped before sending a value, `value` is
+    /// not delivered.
+    #[inline]
+    pub fn exchange_checked(&mut self, value: S) -> Result<R, Disconnected> {
+        // SAFETY:
+        // The other thread emptied the slot before arriving at the previous
+        // exchange, which this thread has passed, see `Mailbox`.
+        unsafe { *self.outgoing[self.slot].get_mut() = Some(value) };
+        self.rendezvous.wait_checked()?;
+        // SAFETY:
+        // The other thread filled the slot before arriving at this exchange,
+        // and fills it again only after this thread arrived at the next one.
+        let received = unsafe { self.incoming[self.slot].get_mut() }.take();
+        self.slot ^= 1;
+        // A dropped handle arrives without sending.
+        received.ok_or(Disconnected)
+    }
+}
@@ -0,0 +1,195 @@
+//! Contains [`Experiment`]
+
+use core::time::Duration;
+
+use crate::clock::Clock;
+#[cfg(feature = "std")]
+use crate::clock::StdClock;
+use crate::SyncPoint;
+
+/// Time spent in the windows of one strategy of an [`Experiment`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StrategyReport {
+    /// Number of syncs made with the strategy
+    pub syncs: u64,
+    /// Time from the start to the end of the strategy's completed windows,
+    /// including the work between the syncs
+    pub time: Duration,
+}
+impl StrategyReport {
+    /// Average time per sync, zero before the first completed window.
+    #[must_use]
+    #[inline]
+    pub fn mean(&self) -> Duration {
+        match u32::try_from(self.syncs) {
+            Ok(0) => Duration::ZERO,
+            Ok(syncs) => self.time / syncs,
+            #[allow(clippy::cast_precision_loss)]
+            Err(_) => self.time.div_f64(self.syncs as f64),
+        }
+    }
+}
+
+/// Comparison of the two strategies of an [`Experiment`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExperimentReport {
+    /// The first strategy
+    pub first: StrategyReport,
+    /// The second strategy
+    pub second: StrategyReport,
+}
+impl ExperimentReport {
+    /// True if the first strategy took less time per sync.
+    #[must_use]
+    #[inline]
+    pub fn first_is_faster(&self) -> bool {
+        self.first.mean() < self.second.mean()
+    }
+}
+
+/// Runs the real workload of an application over two pairs with different
+/// strategies (for example [`Rendezvous`](crate::Rendezvous) spinning, and
+/// parking or a [`FailoverRendezvous`](crate::FailoverRendezvous)), switching
+/// between them every `window` syncs, and reports the time per sync of
+/// each, so the strategy can be chosen with data from production instead
+/// of a microbenchmark.
+///
+/// Both threads wrap their handles of the two pairs, and switch at the
+/// same sync, since the switch only depends on the number of syncs. Only
+/// the sync is measured, data swapped by the pairs is ignored: the two
+/// pairs have separate buffers. The clock is read once per window.
+#[non_exhaustive]
+pub struct Experiment<A, B, C> {
+    /// Handle of the first pair
+    first: A,
+    /// Handle of the second pair
+    second: B,
+    /// Number of syncs per window
+    window: u64,
+    /// Number of syncs so far
+    syncs: u64,
+    /// When the current window started
+    window_start: Duration,
+    /// Time source
+    clock: C,
+    /// Results so far
+    report: ExperimentReport,
+}
+#[cfg(feature = "std")]
+impl<A: SyncPoint, B: SyncPoint> Experiment<A, B, StdClock> {
+    /// Switch between the handles `first` and `second` of two pairs every
+    /// `window` syncs, starting with `first`.
+    ///
+    /// # Panics
+    /// If `window` is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::{Experiment, FailoverRendezvous, Rendezvous};
+    /// use std::thread;
+    ///
+    /// let (my_spinning, their_spinning) = Rendezvous::new();
+    /// let (my_failover, their_failover) = FailoverRendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     let mut experiment = Experiment::new(their_spinning, their_failover, 64);
+    ///     for _ in 0..1024 {
+    ///         experiment.sync();
+    ///     }
+    /// });
+    /// let mut experiment = Experiment::new(my_spinning, my_failover, 64);
+    /// for _ in 0..1024 {
+    ///     experiment.sync();
+    /// }
+    /// let report = experiment.report();
+    /// assert_eq!(report.first.syncs + report.second.syncs, 1024);
+    /// println!("spinning: {:?}, failover: {:?}", report.first.mean(), report.second.mean());
+    /// # handle.join().unwrap();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn new(first: A, second: B, window: u64) -> Self {
+        Self::with_clock(first, second, window, StdClock)
+    }
+}
+impl<A: SyncPoint, B: SyncPoint, C: Clock> Experiment<A, B, C> {
+    /// [`Experiment::new`], timing with `clock`.
+    ///
+    /// # Panics
+    /// If `window` is zero.
+    #[must_use]
+    #[inline]
+    pub fn with_clock(first: A, second: B, window: u64, clock: C) -> Self {
+        assert!(window > 0, "window needs at least one sync");
+        Self {
+            first,
+            second,
+            window,
+            syncs: 0,
+            window_start: clock.now(),
+            clock,
+            report: ExperimentReport::default(),
+        }
+    }
+
+    /// Synchronize execution with the other thread through the pair of the
+    /// current window.
+    #[inline]
+    pub fn sync(&mut self) {
+        let window = self.syncs / self.window;
+        if window & 1 == 0 {
+            self.first.sync();
+        } else {
+            self.second.sync();
+        }
+        self.syncs = self.syncs.wrapping_add(1);
+        if self.syncs.is_multiple_of(self.window) {
+            let now = self.clock.now();
+            let strategy = if window & 1 == 0 {
+                &mut self.report.first
+            } else {
+                &mut self.report.second
+            };
+            strategy.syncs = strategy.syncs.wrapping_add(self.window);
+            strategy.time = strategy
+                .time
+                .saturating_add(now.saturating_sub(self.window_start));
+            self.window_start = now;
+        }
+    }
+
+    /// Results of the completed windows.
+    #[must_use]
+    #[inline]
+    pub const fn report(&self) -> ExperimentReport {
+        self.report
+    }
+
+    /// True while the current window uses the first pair.
+    #[must_use]
+    #[inline]
+    pub const fn is_first(&self) -> bool {
+        (self.syncs / self.window) & 1 == 0
+    }
+
+    /// The handles of both pairs, to read their statistics.
+    #[must_use]
+    #[inline]
+    pub const fn handles(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+
+    /// End the experiment and take the handles back.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+impl<A: SyncPoint, B: SyncPoint, C: Clock> SyncPoint for Experiment<A, B, C> {
+    #[inline]
+    fn sync(&mut self) {
+        Experiment::sync(self);
+    }
+}
@@ -0,0 +1,168 @@
+//! Contains [`FailoverRendezvous`]
+
+use crate::os::std::sync::mpsc::{channel, Receiver, Sender};
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+use core::time::Duration;
+
+use crate::{Clock, Rendezvous, StdClock, SyncPoint};
+
+/// Number of waits between decisions on the transport.
+const WINDOW: usize = 64;
+
+/// Switch to the channel when more than this many waits of a window used up
+/// the spin budget.
+const MAX_CONTENDED: u64 = (WINDOW / 2) as u64;
+
+/// Switch back to spinning when the channel waits of a window took at most
+/// this long on average.
+const MAX_BLOCKING_WAIT: Duration = Duration::from_micros(50);
+
+/// A [`Rendezvous`] that falls back to a blocking channel when spinning stops
+/// paying off, and switches back when conditions improve.
+///
+/// The first handle decides on the transport: every few waits it checks its
+/// [`ContentionStats`](crate::ContentionStats) and, if most waits used up the
+/// spin budget (for example because there are more threads than cores), the
+/// pair switches to waiting on a channel, which puts the thread to sleep
+/// instead of spinning. While on the channel, once the waits are short again
+/// the pair switches back. The decision is published through the sync
+/// point itself, so both threads switch at the same wait.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::FailoverRendezvous;
+/// use std::thread;
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = FailoverRendezvous::new();
+/// let handle = thread::spawn(move || {
+///     for _ in 0..1000 {
+///         their_rendezvous.wait();
+///     }
+/// });
+/// for _ in 0..1000 {
+///     my_rendezvous.wait();
+/// }
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct FailoverRendezvous {
+    /// Spinning transport
+    rendezvous: Rendezvous,
+    /// Blocking transport, to the other thread
+    sender: Sender<()>,
+    /// Blocking transport, from the other thread
+    receiver: Receiver<()>,
+    /// Number of the wait at which the transport changes, only written by
+    /// the first handle
+    switch_at: Arc<AtomicUsize>,
+    /// True for the handle that decides on the transport
+    leader: bool,
+    /// Number of completed waits
+    generation: usize,
+    /// True while waiting on the channel
+    blocking: bool,
+    /// Number of contended waits at the start of the window
+    window_contended: u64,
+    /// Time spent blocking on the channel in the window
+    window_blocked: Duration,
+}
+impl FailoverRendezvous {
+    /// Create a linked pair of [`FailoverRendezvous`], starting out spinning.
+    #[must_use]
+    #[inline]
+    pub fn new() -> (Self, Self) {
+        let (first, second) = Rendezvous::new();
+        let (first_sender, second_receiver) = channel();
+        let (second_sender, first_receiver) = channel();
+        let switch_at = Arc::new(AtomicUsize::new(0));
+        (
+            Self {
+                rendezvous: first,
+                sender: first_sender,
+                receiver: first_receiver,
+                switch_at: Arc::clone(&switch_at),
+                leader: true,
+                generation: 0,
+                blocking: false,
+                window_contended: 0,
+                window_blocked: Duration::ZERO,
+            },
+            Self {
+                rendezvous: second,
+                sender: second_sender,
+                receiver: second_receiver,
+                switch_at,
+                leader: false,
+                generation: 0,
+                blocking: false,
+                window_contended: 0,
+                window_blocked: Duration::ZERO,
+            },
+        )
+    }
+
+    /// Synchronize execution with other thread.
+    ///
+    /// As a side-effect, memory is also synchronized.
+    ///
+    /// # Panics
+    /// If the other handle was dropped while waiting on the channel.
+    #[inline]
+    pub fn wait(&mut self) {
+        let next_generation = self.generation.wrapping_add(1);
+        if self.switch_at.load(Relaxed) == next_generation {
+            self.blocking = !self.blocking;
+        }
+        if self.blocking {
+            let start = StdClock.now();
+            let arrived = self.sender.send(()).is_ok() && self.receiver.recv().is_ok();
+            assert!(arrived, "other FailoverRendezvous was dropped");
+            let blocked = StdClock.now().saturating_sub(start);
+            self.window_blocked = self.window_blocked.saturating_add(blocked);
+        } else {
+            self.rendezvous.wait();
+        }
+        self.generation = next_generation;
+        if self.leader && self.generation.is_multiple_of(WINDOW) {
+            self.decide();
+        }
+    }
+
+    /// True if the pair currently waits on the channel instead of spinning.
+    #[must_use]
+    #[inline]
+    pub const fn is_blocking(&self) -> bool {
+        self.blocking
+    }
+
+    /// The underlying [`Rendezvous`], used while spinning.
+    #[must_use]
+    #[inline]
+    pub const fn rendezvous(&self) -> &Rendezvous {
+        &self.rendezvous
+    }
+
+    /// Decide on the transport at the end of a window, the other thread
+    /// sees the decision when the next wait completes, so both switch at the
+    /// wait after that.
+    fn decide(&mut self) {
+        let contended = self.rendezvous.contention_stats().contended_waits;
+        let switch = if self.blocking {
+            self.window_blocked <= MAX_BLOCKING_WAIT.saturating_mul(WINDOW as u32)
+        } else {
+            contended.wrapping_sub(self.window_contended) > MAX_CONTENDED
+        };
+        self.window_contended = contended;
+        self.window_blocked = Duration::ZERO;
+        if switch {
+            self.switch_at
+                .store(self.generation.wrapping_add(2), Relaxed);
+        }
+    }
+}
+impl SyncPoint for FailoverRendezvous {
+    #[inline]
+    fn sync(&mut self) {
+        self.wait();
+    }
+}
@@ -0,0 +1,329 @@
+//! Contains [`FanOutPublisher`] and [`FanOutReader`]
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::hint::spin_loop;
+use core::sync::atomic::{AtomicBool, Ordering::Acquire, Ordering::Release};
+
+#[cfg(feature = "std")]
+use crate::contention::{Contention, ContentionStats};
+use crate::core_cell::SlotCell;
+use crate::counter::Counter;
+use crate::padded::Padded;
+use crate::{SwapPoint, SyncPoint};
+
+/// Progress of one [`FanOutReader`].
+struct ReaderState {
+    /// Number of publications the reader has released
+    released: Counter,
+    /// Set when the reader is dropped, the publisher stops waiting for it
+    gone: AtomicBool,
+}
+
+/// Shared between a [`FanOutPublisher`] and its readers.
+struct FanOutShared<T: Send + Sync> {
+    /// Number of buffers published
+    published: Padded<Counter>,
+    /// One entry per reader
+    readers: Box<[Padded<ReaderState>]>,
+    /// Publication `n` (counting from 1) is buffer `(n - 1) % 2`
+    ///
+    /// The publisher writes one buffer while the readers read the other, and
+    /// only starts writing a buffer once every reader has released it, see
+    /// `FanOutPublisher::publish`.
+    buffers: [Padded<SlotCell<T>>; 2],
+}
+
+/// Publishing side of a one producer, many readers double buffer, a
+/// [`RendezvousData`](crate::RendezvousData) whose other side is a group of
+/// threads sharing the published buffer.
+///
+/// The publisher fills a buffer and publishes it, after which every reader
+/// gets a shared reference to it, all at the same time. The publisher gets
+/// the other buffer back once every reader has released it, by reading the
+/// next publication. Like a pair, the group moves in lockstep: readers see
+/// every publication, and the publisher is never more than one publication
+/// ahead of the slowest reader. Each reader only writes its own counter, so
+/// no read-modify-write atomics are needed.
+///
+/// Dropping a reader releases it for good, the publisher stops waiting for
+/// it.
+///
+/// This is also the broadcast rendezvous of the crate: a coordinator handing
+/// a snapshot to several workers at every tick publishes it, and each
+/// worker's [`FanOutReader::read`] is its sync point for that tick.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::FanOutPublisher;
+/// use std::thread;
+///
+/// let (mut publisher, readers) = FanOutPublisher::new(0, 0, 3);
+/// let handles: Vec<_> = readers
+///     .into_iter()
+///     .map(|mut reader| thread::spawn(move || (0..5).map(|_| *reader.read()).sum::<i32>()))
+///     .collect();
+/// for i in 1..=5 {
+///     *publisher.current() = i;
+///     publisher.publish();
+/// }
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), 15);
+/// }
+/// ```
+/// # Example: Broadcast a config every tick
+/// ```rust
+/// use rendezvous_swap::FanOutPublisher;
+/// use std::thread;
+///
+/// #[derive(Clone, Default)]
+/// struct Config {
+///     tick: u32,
+///     gain: f32,
+/// }
+///
+/// let (mut coordinator, workers) = FanOutPublisher::new(Config::default(), Config::default(), 2);
+/// let handles: Vec<_> = workers
+///     .into_iter()
+///     .map(|mut worker| {
+///         thread::spawn(move || {
+///             for tick in 1..=3 {
+///                 let config = worker.read();
+///                 assert_eq!(config.tick, tick);
+///             }
+///         })
+///     })
+///     .collect();
+/// let mut config = Config::default();
+/// for tick in 1..=3 {
+///     config.tick = tick;
+///     config.gain = 1.0 / tick as f32;
+///     coordinator.current().clone_from(&config);
+///     coordinator.publish();
+/// }
+/// # for handle in handles {
+/// #     handle.join().unwrap();
+/// # }
+/// ```
+#[doc(alias = "BroadcastRendezvous")]
+#[non_exhaustive]
+pub struct FanOutPublisher<T: Send + Sync> {
+    /// Number of buffers published
+    published: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// Shared counters and buffers
+    shared: Arc<FanOutShared<T>>,
+}
+
+/// Reading side of a one producer, many readers double buffer, see
+/// [`FanOutPublisher`].
+#[non_exhaustive]
+pub struct FanOutReader<T: Send + Sync> {
+    /// Index of this reader's state
+    index: usize,
+    /// Number of publications read
+    read: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// Shared counters and buffers
+    shared: Arc<FanOutShared<T>>,
+}
+
+impl<T: Send + Sync> FanOutPublisher<T> {
+    /// Create a publisher and `readers` linked readers. Arguments are the
+    /// initial values of the two buffers, the publisher starts out filling
+    /// the first.
+    #[must_use]
+    #[inline]
+    pub fn new(data1: T, data2: T, readers: usize) -> (Self, Vec<FanOutReader<T>>) {
+        let shared = Arc::new(FanOutShared {
+            published: Padded::new(Counter::new()),
+            readers: (0..readers)
+                .map(|_| {
+                    Padded::new(ReaderState {
+                        released: Counter::new(),
+                        gone: AtomicBool::new(false),
+                    })
+                })
+                .collect(),
+            buffers: [data1, data2].map(|data| Padded::new(SlotCell::new(data))),
+        });
+        let readers = (0..readers)
+            .map(|index| FanOutReader {
+                index,
+                read: 0,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                shared: Arc::clone(&shared),
+            })
+            .collect();
+        (
+            Self {
+                published: 0,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                shared,
+            },
+            readers,
+        )
+    }
+
+    /// Get a mutable reference to the buffer being filled.
+    #[must_use]
+    #[inline]
+    pub fn current(&mut self) -> &mut T {
+        // SAFETY:
+        // The buffer is owned by the publisher until it is published, see
+        // `publish`.
+        unsafe { self.shared.buffers[self.published & 1].get_mut() }
+    }
+
+    /// Publish the buffer being filled, and get the other buffer once every
+    /// reader has released it.
+    #[inline]
+    pub fn publish(&mut self) -> &mut T {
+        let previous = self.published;
+        self.published = previous.wrapping_add(1);
+        let shared = &*self.shared;
+        shared.published.store(self.published);
+        // The other buffer holds publication `previous`, readers have
+        // released it once they released `previous` publications. Every
+        // reader has released at least `previous - 1`, which the last
+        // publish waited for, and at most `previous + 1`.
+        let holding = previous.wrapping_sub(1);
+        for reader in &*shared.readers {
+            #[cfg(feature = "std")]
+            let mut spins = 0;
+            while {
+                spin_loop();
+                reader.released.load() == holding && !reader.gone.load(Acquire)
+            } {
+                #[cfg(feature = "std")]
+                if self.contention.spin(&mut spins) {
+                    reader.released.block(holding);
+                }
+            }
+            reader.released.acquire();
+            #[cfg(feature = "std")]
+            self.contention.finish(spins);
+        }
+        self.current()
+    }
+
+    /// Number of readers that have not been dropped.
+    #[must_use]
+    #[inline]
+    pub fn readers(&self) -> usize {
+        self.shared
+            .readers
+            .iter()
+            .filter(|reader| !reader.gone.load(Acquire))
+            .count()
+    }
+
+    /// Statistics on how often [`FanOutPublisher::publish`] gave up spinning
+    /// and yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+}
+
+impl<T: Send + Sync> FanOutReader<T> {
+    /// Release the publication last read, and wait for the next one.
+    #[doc(alias = "recv")]
+    #[inline]
+    pub fn read(&mut self) -> &T {
+        let shared = &*self.shared;
+        shared.readers[self.index].released.store(self.read);
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        while {
+            spin_loop();
+            shared.published.load() == self.read
+        } {
+            // Not `block`, with the `parking` feature only one thread may
+            // park on a counter.
+            #[cfg(feature = "std")]
+            if self.contention.spin(&mut spins) {
+                crate::os::yield_now();
+            }
+        }
+        shared.published.acquire();
+        #[cfg(feature = "std")]
+        self.contention.finish(spins);
+        self.advance()
+    }
+
+    /// Release the publication last read and read the next one, if it has
+    /// been published, without waiting.
+    #[must_use]
+    #[inline]
+    pub fn try_read(&mut self) -> Option<&T> {
+        let shared = &*self.shared;
+        shared.readers[self.index].released.store(self.read);
+        if shared.published.load() == self.read {
+            return None;
+        }
+        shared.published.acquire();
+        Some(self.advance())
+    }
+
+    /// Number of publications read.
+    #[must_use]
+    #[inline]
+    pub const fn generation(&self) -> usize {
+        self.read
+    }
+
+    /// Statistics on how often [`FanOutReader::read`] gave up spinning and
+    /// yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+
+    /// Take the next publication, which must have been published.
+    fn advance(&mut self) -> &T {
+        let buffer = self.read & 1;
+        self.read = self.read.wrapping_add(1);
+        // SAFETY:
+        // The publisher does not write this buffer until this reader has
+        // released it, in the next `read`, which needs `&mut self`.
+        unsafe { self.shared.buffers[buffer].get() }
+    }
+}
+impl<T: Send + Sync> SyncPoint for FanOutPublisher<T> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.publish();
+    }
+}
+impl<T: Send + Sync> SwapPoint for FanOutPublisher<T> {
+    type Data = T;
+    #[inline]
+    fn swap(&mut self) -> &mut T {
+        self.publish()
+    }
+}
+impl<T: Send + Sync> SyncPoint for FanOutReader<T> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.read();
+    }
+}
+impl<T: Send + Sync> Drop for FanOutReader<T> {
+    fn drop(&mut self) {
+        let reader = &self.shared.readers[self.index];
+        reader.gone.store(true, Release);
+        // Wakes the publisher if it is parked on this reader.
+        reader.released.store(self.read);
+    }
+}
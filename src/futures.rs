@@ -0,0 +1,145 @@
+//! Contains [`WaitFuture`] and [`SwapFuture`]
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::{Rendezvous, RendezvousDataN};
+
+/// Future of [`Rendezvous::wait_async`], completes once the other thread
+/// has arrived.
+///
+/// The first poll arrives at the sync point. Dropping the future after that
+/// leaves the wait pending: the next wait completes the same sync, see
+/// [`Rendezvous::wait_timeout`].
+#[must_use = "futures do nothing unless polled"]
+#[non_exhaustive]
+pub struct WaitFuture<'handle> {
+    /// The handle, `None` once the wait has completed
+    handle: Option<&'handle mut Rendezvous>,
+    /// True once this thread has arrived at the sync point
+    arrived: bool,
+}
+impl Future for WaitFuture<'_> {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = &mut *self;
+        let handle = this
+            .handle
+            .as_deref_mut()
+            .expect("WaitFuture polled after completion");
+        if !this.arrived {
+            this.arrived = true;
+            if !handle.arrive() {
+                this.handle = None;
+                return Poll::Ready(());
+            }
+        }
+        if handle.poll_arrived(cx.waker()) {
+            this.handle = None;
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Future of [`RendezvousDataN::swap_async`], completes with the data of
+/// the other thread once it has arrived.
+///
+/// The first poll hands the data over. Dropping the future after that
+/// leaves the swap pending, see [`RendezvousDataN::swap_timeout`].
+#[must_use = "futures do nothing unless polled"]
+#[non_exhaustive]
+pub struct SwapFuture<'handle, T: Send, const SLOTS: usize> {
+    /// The handle, `None` once the swap has completed
+    handle: Option<&'handle mut RendezvousDataN<T, SLOTS>>,
+    /// True once the data has been handed over
+    arrived: bool,
+}
+impl<'handle, T: Send, const SLOTS: usize> Future for SwapFuture<'handle, T, SLOTS> {
+    type Output = &'handle mut T;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<&'handle mut T> {
+        let this = &mut *self;
+        let handle = this
+            .handle
+            .take()
+            .expect("SwapFuture polled after completion");
+        if !this.arrived {
+            this.arrived = true;
+            handle.arrive();
+        }
+        if handle.poll_arrived(cx.waker()) {
+            Poll::Ready(handle.next_slot())
+        } else {
+            this.handle = Some(handle);
+            Poll::Pending
+        }
+    }
+}
+
+impl Rendezvous {
+    /// [`Rendezvous::wait`] for async tasks: instead of spinning, the task
+    /// is woken by the other thread when it arrives, so the executor thread
+    /// is not blocked.
+    ///
+    /// The other thread can use the usual blocking methods, its arrival
+    /// wakes the task. Costs the other thread a fence per sync.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut async_side, mut compute_side) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     for _ in 0..3 {
+    ///         compute_side.wait();
+    ///     }
+    /// });
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// runtime.block_on(async move {
+    ///     for _ in 0..3 {
+    ///         async_side.wait_async().await;
+    ///     }
+    /// });
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn wait_async(&mut self) -> WaitFuture<'_> {
+        WaitFuture {
+            handle: Some(self),
+            arrived: false,
+        }
+    }
+}
+
+impl<T: Send, const SLOTS: usize> RendezvousDataN<T, SLOTS> {
+    /// [`RendezvousDataN::swap`] for async tasks, see
+    /// [`Rendezvous::wait_async`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut async_side, mut compute_side) = RendezvousData::new(0, 0);
+    /// let handle = thread::spawn(move || {
+    ///     let data = compute_side.swap();
+    ///     *data = 42; // computed
+    ///     compute_side.swap();
+    /// });
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// let result = runtime.block_on(async move {
+    ///     async_side.swap_async().await;
+    ///     *async_side.swap_async().await
+    /// });
+    /// assert_eq!(result, 42);
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn swap_async(&mut self) -> SwapFuture<'_, T, SLOTS> {
+        SwapFuture {
+            handle: Some(self),
+            arrived: false,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Contains [`RendezvousGroup`]
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::hint::spin_loop;
+
+#[cfg(feature = "std")]
+use crate::contention::{Contention, ContentionStats};
+use crate::counter::Counter;
+use crate::padded::Padded;
+use crate::SyncPoint;
+
+/// Synchronise execution between a fixed group of threads, the generalization
+/// of [`Rendezvous`](crate::Rendezvous) to more than two.
+///
+/// Every thread publishes its generation in its own padded counter and spins
+/// on the counters of all other threads, like a pair does, so there are no
+/// read-modify-write atomics, and one group replaces the `n * (n - 1) / 2`
+/// pairs it would otherwise take. Each wait reads `n - 1` cache lines that
+/// the other threads write, so this is intended for small groups (up to
+/// about 8 threads).
+/// # Example
+/// ```rust
+/// use rendezvous_swap::RendezvousGroup;
+/// use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let arrived = Arc::new(AtomicUsize::new(0));
+/// let handles: Vec<_> = RendezvousGroup::new(4)
+///     .into_iter()
+///     .map(|mut group| {
+///         let arrived = Arc::clone(&arrived);
+///         thread::spawn(move || {
+///             for i in 1..=10 {
+///                 arrived.fetch_add(1, Relaxed);
+///                 group.wait();
+///                 assert!(arrived.load(Relaxed) >= 4 * i);
+///                 group.wait();
+///             }
+///         })
+///     })
+///     .collect();
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// ```
+#[non_exhaustive]
+pub struct RendezvousGroup {
+    /// Index of this thread's counter
+    index: usize,
+    /// Thread local generation
+    generation: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// One counter per thread
+    counters: Arc<[Padded<Counter>]>,
+}
+impl RendezvousGroup {
+    /// Create `n` linked handles, one for each thread of the group.
+    #[must_use]
+    #[inline]
+    pub fn new(n: usize) -> Vec<Self> {
+        let counters: Arc<[Padded<Counter>]> =
+            (0..n).map(|_| Padded::new(Counter::new())).collect();
+        (0..n)
+            .map(|index| Self {
+                index,
+                generation: 0,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                counters: Arc::clone(&counters),
+            })
+            .collect()
+    }
+
+    /// Synchronize execution with all other threads of the group.
+    ///
+    /// As a side-effect, memory is also synchronized.
+    #[inline]
+    pub fn wait(&mut self) {
+        let next_generation = self.generation.wrapping_add(1);
+        let (before, rest) = self.counters.split_at(self.index);
+        let Some((mine, after)) = rest.split_first() else {
+            return;
+        };
+        mine.store(next_generation);
+        // A thread can only get one generation ahead of the slowest thread,
+        // so every counter is at this generation, the next, or the one
+        // after that.
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        for theirs in before.iter().chain(after) {
+            while {
+                spin_loop();
+                theirs.load() == self.generation
+            } {
+                mine.answer_probe();
+                // Not `block`, with the `parking` feature only one thread may
+                // park on a counter.
+                #[cfg(feature = "std")]
+                if self.contention.spin(&mut spins) {
+                    crate::os::yield_now();
+                }
+            }
+            theirs.acquire();
+        }
+        #[cfg(feature = "std")]
+        self.contention.finish(spins);
+        self.generation = next_generation;
+    }
+
+    /// Number of threads in the group.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// True if the group has no threads, which is never the case for a
+    /// handle.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    /// Index of this handle in the group, from 0 to `len() - 1`, in the
+    /// order [`RendezvousGroup::new`] returned the handles.
+    #[must_use]
+    #[inline]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Number of times this thread has synchronized with the group
+    /// (wrapping).
+    #[must_use]
+    #[inline]
+    pub const fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Statistics on how often [`RendezvousGroup::wait`] gave up spinning
+    /// and yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+}
+impl SyncPoint for RendezvousGroup {
+    #[inline]
+    fn sync(&mut self) {
+        self.wait();
+    }
+}
@@ -0,0 +1,92 @@
+//! Contains [`LastError`]
+
+/// Bits of a packed error holding the kind, the rest is the generation.
+const KIND_BITS: u32 = 3;
+
+/// Kind of a protocol-level error, see [`LastError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// A probe of the other thread timed out, see
+    /// [`ProbeResult::Unresponsive`](crate::ProbeResult::Unresponsive).
+    Unresponsive,
+    /// The threads performed different operations at a sync point (with
+    /// the `strict` feature).
+    StrictViolation,
+    /// Data received by a checked swap did not match its checksum (with the
+    /// `checksum` feature).
+    ChecksumMismatch,
+    /// The threads performed a different number of sync points, found with
+    /// debug assertions or the `checked` feature.
+    Unbalanced,
+}
+impl ErrorKind {
+    /// Encode as a non-zero number below `1 << KIND_BITS`.
+    const fn encode(self) -> usize {
+        match self {
+            Self::Unresponsive => 1,
+            Self::StrictViolation => 2,
+            Self::ChecksumMismatch => 3,
+            Self::Unbalanced => 4,
+        }
+    }
+
+    /// Inverse of [`ErrorKind::encode`].
+    const fn decode(code: usize) -> Option<Self> {
+        match code {
+            1 => Some(Self::Unresponsive),
+            2 => Some(Self::StrictViolation),
+            3 => Some(Self::ChecksumMismatch),
+            4 => Some(Self::Unbalanced),
+            _ => None,
+        }
+    }
+}
+
+/// The most recent protocol-level error of a pair, kept in the shared state
+/// so that either side can find out after the fact what went wrong, for
+/// example why a thread had to resync.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LastError {
+    /// What happened.
+    pub kind: ErrorKind,
+    /// Generation of the thread that ran into the error when it happened,
+    /// modulo `usize::MAX >> 3`.
+    pub generation: usize,
+    /// True if the other thread ran into the error, false if the thread
+    /// asking did.
+    pub by_peer: bool,
+}
+impl LastError {
+    /// Pack `kind` and `generation` into one word, never 0.
+    pub(crate) const fn pack(kind: ErrorKind, generation: usize) -> usize {
+        (generation << KIND_BITS) | kind.encode()
+    }
+
+    /// The later of the errors packed in `mine` and `theirs`, as seen from
+    /// the owner of `mine`.
+    pub(crate) fn unpack(mine: usize, theirs: usize) -> Option<Self> {
+        let unpack = |packed: usize, by_peer| {
+            Some(Self {
+                kind: ErrorKind::decode(packed & ((1 << KIND_BITS) - 1))?,
+                generation: packed >> KIND_BITS,
+                by_peer,
+            })
+        };
+        match (unpack(mine, false), unpack(theirs, true)) {
+            (Some(mine), Some(theirs)) => {
+                // Generations wrap, the later error is less than half of
+                // the range ahead.
+                let ahead =
+                    theirs.generation.wrapping_sub(mine.generation) & (usize::MAX >> KIND_BITS);
+                Some(if ahead != 0 && ahead < usize::MAX >> (KIND_BITS + 1) {
+                    theirs
+                } else {
+                    mine
+                })
+            },
+            (mine, theirs) => mine.or(theirs),
+        }
+    }
+}
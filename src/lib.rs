@@ -4,16 +4,44 @@
 #![allow(clippy::semicolon_inside_block)]
 #![allow(clippy::blanket_clippy_restriction_lints)]
 #![allow(clippy::pub_use)]
+// Without `alloc` only `RendezvousDataRef` uses the counter machinery.
+#![cfg_attr(not(feature = "alloc"), allow(dead_code))]
 //! A rendezvous is an execution barrier between a pair of threads, but this crate also provides the option of swapping data at the synchronisation point. (Terminology is from [The Little Book of Semaphores](https://greenteapress.com/wp/semaphores/))
 //!
-//! This is mainly intended for situations where threads sync frequently. Unlike a normal spinlock, it does not use any CAS instructions, just [`Acquire`] loads and [`Release`] stores which means it can compile to just a handful of non atomic instructions on `x86_64`. Because the crate uses atomics for synchronisation, it is also `no_std`.
+//! This is mainly intended for situations where threads sync frequently. Unlike a normal spinlock, it does not use any CAS instructions to sync, just [`Acquire`] loads and [`Release`] stores which means it can compile to just a handful of non atomic instructions on `x86_64`. Only arriving through a [`Rendezvous::waker`], which can race with the owning thread, takes a CAS. Because the crate uses atomics for synchronisation, it is also `no_std`.
 //!
 //! Data is internally swapped with pointers, so large structures are not costly to swap and therefore do not need to be boxed.
 //!
 //! In microbenchmarks on a `i5-7200U` CPU, it takes less than `100 ns` to swap data.
 //!
+//! # Features
+//! * `std` (default): when a wait has spun for too long, the thread yields to the OS scheduler instead (on Windows with `SwitchToThread`, falling back to `Sleep(0)` so a partner queued on another processor can run), see [`ContentionStats`], or spins and sleeps in a duty cycle set by [`SpinBudget`], or spins up to a preemption threshold and then blocks for that wait only ([`Rendezvous::set_preemption_threshold`], for virtualized hosts), and [`FailoverRendezvous`] switches to a blocking channel when that happens too often. Also enables [`select::select2`] and [`select::select`], [`worker::ComputeWorker`] and handles tied to a [`std::thread::scope`](https://doc.rust-lang.org/std/thread/fn.scope.html) ([`Rendezvous::scoped`]), which [`RendezvousData::run_pair`] uses to run both sides of a pair on scoped threads, [`Rendezvous::migrate`] for handles deliberately moved to another thread, [`Rendezvous::wait_timeout`], which gives up on a sync after a timeout, [`Rendezvous::wait_poisoned`], which fails once the other thread has panicked, and the [`YieldAfter`] wait strategy. [`Experiment::new`] alternates the real workload between two pairs with different strategies and compares their time per sync, and [`self_test()`] checks at startup that swapping works on the current target. Without it the crate does not link `std` at all (timed methods such as [`Rendezvous::probe_with`] take a user supplied [`Clock`] instead of [`StdClock`]), everything that needs an operating system is only reachable through one internal module that is compiled with this feature.
+//! * `alloc` (enabled by `std`): the pair types that allocate their shared state, which is everything except [`RendezvousDataRef`] and [`RendezvousRef`]. Without it the crate does not need an allocator, pairs are created with [`RendezvousDataRef::new_in`] on a [`RendezvousDataStorage`] provided by the caller (on the stack or in an arena), or split once out of a [`StaticRendezvous`] or [`StaticRendezvousData`] placed in a `static`.
+//! * `parking`: once a wait has spun through its budget ([`ContentionStats::spin_limit`]), park the thread instead of yielding (using [`parking_lot_core`](https://docs.rs/parking_lot_core)), and the other thread wakes it when it arrives, so a thread paired with a much slower one (a renderer waiting for frames) does not burn a core. Waits that complete within the budget are unaffected, and without `std` waits always spin.
+//! * `bench`: [`bench::measure_pair`] measures wait and swap latency on the machine running the program, [`bench::PingPongMeter`] estimates how often received data has to be fetched from the other core, and [`bench::time_pair`] is the pair benchmark scaffolding for Criterion's `iter_custom`, to benchmark other payloads and wait strategies.
+//! * `async`: [`Rendezvous::wait_async`] and [`RendezvousDataN::swap_async`] return futures that register the waker of the task, which the other thread wakes when it arrives, so a task on any executor can sync with a dedicated thread without blocking the executor. Costs the other thread a fence per sync.
+//! * `notify`: [`notify::bridge`] signals an `eventfd` (Linux) or pipe when the other thread arrives, so a thread inside an `epoll`/`mio` event loop can include the pair in its poll set (unix only).
+//! * `mio`: register [`notify::RendezvousNotifier`] with a [`mio`](https://docs.rs/mio) poll registry.
+//! * `tokio`: [`notify::TokioRendezvous`] lets a task on a [`tokio`](https://docs.rs/tokio) runtime `.await` the other thread.
+//! * `migration-stats`: the threads of a pair sample the CPU they run on every 64 syncs and count how often the OS moved them to another core, see [`Rendezvous::migrations`] (Linux only).
+//! * `shm`: [`shm::ShmRendezvous`] syncs two processes through a named shared memory segment, and notices when the other process dies (Linux only).
+//! * `rt`: [`rt::spawn_pair`] spawns both threads of a pair with the same real-time scheduling policy and priority (`SCHED_FIFO` or `SCHED_RR`), and only starts them once both settings are verified to have taken effect, since a real-time thread paired with a normal priority spinner is a common misconfiguration (unix only).
+//! * `trace`: [`trace::Traced`] records when each thread arrived at and left every sync point into a preallocated ring, and [`trace::write_csv`] and [`trace::write_chrome_trace`] export the timelines of both threads, to visualize phase alignment and jitter.
+//! * `checksum`: [`RendezvousDataN::checked_swap`] sends a CRC-32 of the data along with it and verifies it on receipt, an end-to-end check against stray writes and bit flips at the exchange boundary.
+//! * `strict`: both threads publish which operation they perform at each sync point (for example [`RendezvousData::swap`] or [`Paused::reinit`]), and panic with both operations if they disagree, catching misuse before it causes aliasing. Costs an extra store and load per sync.
+//! * `checked` (also enabled by debug assertions): a thread that waits for a handle that was dropped or whose thread panicked (so that it synced fewer times) panics with both generations instead of waiting forever, and so does a thread that finds the other thread at a generation no balanced sequence of sync points leads to, for example after resuming handles with [`RendezvousDataN::from_parts`] at different generations. Either is recorded as [`ErrorKind::Unbalanced`]. Costs a load per sync, and one per spin while waiting.
+//! * `stats`: record how many times each wait of [`Rendezvous::wait`] and [`RendezvousDataN::swap`] spun, and with `std` how long it took, in the counter of the waiting thread, see [`WaitStats`]. Both threads can read the statistics of both sides ([`Rendezvous::wait_stats`] and [`Rendezvous::peer_wait_stats`]), to find out which side is the bottleneck. Costs a few stores per wait, and two clock reads with `std`.
+//! * `count-atomics`: count the atomic operations of each thread with [`atomic_counts`], to verify the claims above. Only intended for testing.
+//! * `test-kit`: [`test_kit`] exports the test scenarios of this crate, to test components built on it.
+//! * `test-hooks`: [`test_hooks`] delays counter stores of a thread, to test how the other thread copes with a slow partner. Only intended for testing.
+//! * `explicit-fences`: use [`Relaxed`](core::sync::atomic::Ordering::Relaxed) counter operations with an explicit [`Release`] fence before publishing and a single [`Acquire`] fence once the other thread has arrived, instead of [`Acquire`] loads and [`Release`] stores. On some ARM cores the fence placement measurably changes latency, and it is a fallback for targets whose acquire/release mappings are suspected to be buggy or expensive (older compilers, some embedded toolchains). The litmus tests of this crate (`cargo test test_litmus`, with and without this feature) check that both modes order memory around a sync point.
+//!
 //! # Safety
 //! [`RendezvousData`] contains `unsafe` but all tests pass when running with Miri.
+//! The ordering of the counters and the hand over of the buffers is also model
+//! checked with [loom](https://docs.rs/loom), which explores every interleaving
+//! of two threads syncing and swapping:
+//! `RUSTFLAGS="--cfg loom" cargo test --release --test loom`.
 //!
 //! # Example: Sync thread execution
 //! ```rust
@@ -84,9 +112,210 @@
 //! # handle.join().unwrap();
 //! ```
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
+
+#[cfg(feature = "alloc")]
+mod arena;
+#[cfg(feature = "count-atomics")]
+pub mod atomic_counts;
+#[cfg(feature = "alloc")]
+mod batched;
+mod borrowed;
+#[cfg(feature = "alloc")]
+mod builder;
+#[cfg(feature = "checksum")]
+mod checksum;
+#[cfg(feature = "bench")]
+pub mod bench;
+mod clock;
+pub mod compat;
+#[cfg(doctest)]
+mod compile_fail;
+#[cfg(feature = "std")]
+mod contention;
+mod core_cell;
+mod counter;
+#[cfg(feature = "alloc")]
+mod dirty;
+#[cfg(feature = "alloc")]
+mod dyn_data;
+#[cfg(feature = "alloc")]
+mod exchange;
+mod experiment;
+#[cfg(feature = "std")]
+mod failover;
+#[cfg(feature = "alloc")]
+mod fan_out;
+#[cfg(feature = "async")]
+mod futures;
+#[cfg(feature = "alloc")]
+mod group;
+#[cfg(all(feature = "notify", unix))]
+pub mod notify;
+mod last_error;
+#[cfg(feature = "alloc")]
+mod observer;
+#[cfg(feature = "std")]
+mod os;
+mod padded;
+#[cfg(feature = "alloc")]
+mod pair_arena;
+mod pair_id;
+#[cfg(feature = "alloc")]
+mod paused;
+#[cfg(feature = "alloc")]
+mod pinned;
+mod probe;
+#[cfg(feature = "alloc")]
+pub mod raw;
+#[cfg(feature = "alloc")]
+mod raw_pointer;
+#[cfg(feature = "alloc")]
+mod read_only;
+#[cfg(feature = "alloc")]
 mod rendezvous_data;
+#[cfg(feature = "alloc")]
 mod rendezvous;
+#[cfg(feature = "alloc")]
+mod ring;
+#[cfg(all(feature = "rt", unix))]
+pub mod rt;
+#[cfg(feature = "std")]
+mod scoped;
+#[cfg(feature = "std")]
+pub mod select;
+#[cfg(feature = "std")]
+mod self_test;
+#[cfg(all(feature = "shm", target_os = "linux"))]
+pub mod shm;
+mod single_thread;
+#[cfg(all(feature = "alloc", not(loom)))]
+mod slices;
+#[cfg(feature = "alloc")]
+pub mod spsc;
+#[cfg(feature = "alloc")]
+mod split;
+mod static_rendezvous;
+mod strict;
+#[cfg(feature = "alloc")]
+mod swap_hooks;
+mod sync;
+mod sync_point;
+mod tagged;
+#[cfg(feature = "test-hooks")]
+pub mod test_hooks;
+#[cfg(feature = "test-kit")]
+pub mod test_kit;
+#[cfg(feature = "trace")]
+pub mod trace;
+#[cfg(feature = "alloc")]
+mod triple_buffer;
+#[cfg(feature = "stats")]
+mod wait_stats;
+mod wait_strategy;
+#[cfg(feature = "alloc")]
+mod waker;
+#[cfg(feature = "std")]
+pub mod worker;
+mod zeroable;
+
+#[cfg(feature = "alloc")]
+pub use arena::{Arena, WithArena};
+#[cfg(feature = "alloc")]
+pub use batched::{BatchConsumer, BatchProducer};
+pub use borrowed::{RendezvousDataRef, RendezvousDataStorage, RendezvousRef};
+#[cfg(feature = "alloc")]
+pub use builder::{RendezvousBuilder, RendezvousDataBuilder};
+#[cfg(feature = "checksum")]
+pub use checksum::ChecksumMismatch;
+pub use clock::Clock;
+#[cfg(feature = "std")]
+pub use clock::StdClock;
+#[cfg(feature = "std")]
+pub use contention::{ContentionStats, SpinBudget};
+#[cfg(feature = "alloc")]
+pub use dirty::DirtyRendezvousData;
+#[cfg(feature = "alloc")]
+pub use dyn_data::{RendezvousDataDyn, WrongType};
+#[cfg(feature = "alloc")]
+pub use exchange::RendezvousExchange;
+pub use experiment::{Experiment, ExperimentReport, StrategyReport};
+#[cfg(feature = "std")]
+pub use failover::FailoverRendezvous;
+#[cfg(feature = "alloc")]
+pub use fan_out::{FanOutPublisher, FanOutReader};
+#[cfg(feature = "async")]
+pub use futures::{SwapFuture, WaitFuture};
+#[cfg(feature = "alloc")]
+pub use group::RendezvousGroup;
+pub use last_error::{ErrorKind, LastError};
+#[cfg(feature = "alloc")]
+pub use observer::{Observation, Observer, Watch};
+#[cfg(feature = "alloc")]
+pub use pair_arena::{PairArena, PairArenaSide};
+pub use pair_id::PairId;
+#[cfg(feature = "alloc")]
+pub use paused::Paused;
+#[cfg(feature = "alloc")]
+pub use pinned::PinnedRendezvousData;
+pub use probe::ProbeResult;
+#[cfg(feature = "alloc")]
+pub use read_only::ReadOnly;
+#[cfg(feature = "alloc")]
+pub use rendezvous_data::{PendingSwap, RendezvousData, RendezvousDataN, RendezvousDataParts};
+#[cfg(feature = "alloc")]
+pub use rendezvous::{
+    Disconnected, Rendezvous, RendezvousParts, StartupError, SubRendezvous, TimedOut, WaitResult,
+};
+#[cfg(feature = "std")]
+pub use rendezvous::Poisoned;
+#[cfg(feature = "alloc")]
+pub use ring::RendezvousRing;
+#[cfg(feature = "std")]
+pub use scoped::{Detached, ScopedRendezvous, ScopedRendezvousData};
+#[cfg(feature = "std")]
+pub use self_test::{self_test, SelfTestReport};
+pub use single_thread::SingleThread;
+#[cfg(all(feature = "alloc", not(loom)))]
+pub use slices::RendezvousSlices;
+#[cfg(feature = "alloc")]
+pub use split::SplitRendezvousData;
+pub use static_rendezvous::{StaticRendezvous, StaticRendezvousData};
+#[cfg(feature = "alloc")]
+pub use swap_hooks::Hooked;
+pub use sync_point::{SwapPoint, SyncPoint};
+pub use tagged::Tagged;
+#[cfg(feature = "alloc")]
+pub use triple_buffer::{TripleBuffer, TripleBufferReader, TripleBufferWriter};
+#[cfg(feature = "stats")]
+pub use wait_stats::WaitStats;
+#[cfg(feature = "std")]
+pub use wait_strategy::YieldAfter;
+pub use wait_strategy::{Spin, SpinBackoff, WaitStrategy};
+#[cfg(feature = "alloc")]
+pub use waker::RendezvousCompleter;
+pub use zeroable::Zeroable;
+
+/// Upper bound on the size of a [`Rendezvous`] and of a [`RendezvousDataN`]
+/// handle (for any `T` and slot count), in bytes.
+///
+/// Handles only point to the shared state, so they stay this small however
+/// large the data is, and can be embedded in per-entity structs. Exceeding
+/// this is a compile error in this crate.
+/// ```rust
+/// use rendezvous_swap::{Rendezvous, RendezvousData, MAX_HANDLE_SIZE};
+///
+/// assert!(size_of::<Rendezvous>() <= MAX_HANDLE_SIZE);
+/// assert!(size_of::<RendezvousData<[u8; 4096]>>() <= MAX_HANDLE_SIZE);
+/// ```
+pub const MAX_HANDLE_SIZE: usize = 64;
 
-pub use rendezvous_data::RendezvousData;
-pub use rendezvous::Rendezvous;
+/// Alignment, in bytes, of the counters and buffers of a pair on the
+/// current target, which keeps each of them on its own cache lines.
+///
+/// 128 on `x86_64`, `aarch64` and `powerpc64`, which prefetch adjacent
+/// lines in pairs, 256 on `s390x`, 32 on `arm`, `mips`, `sparc` and
+/// `hexagon`, 16 on `m68k` and 64 elsewhere. Small buffers take up at least
+/// this much memory each.
+pub const CACHE_LINE_PADDING: usize = align_of::<padded::Padded<u8>>();
@@ -0,0 +1,357 @@
+//! Contains [`bridge`], [`Signaling`], [`RendezvousNotifier`] and `TokioRendezvous`
+//!
+//! Lets a thread that lives inside a `poll`/`epoll` based event loop take
+//! part in a pair without spinning: the other thread signals a file
+//! descriptor (an `eventfd` on Linux, a pipe elsewhere) when it arrives at
+//! the sync point, and the event loop only calls `wait` once it is readable.
+
+use crate::os::std::io;
+use crate::os::std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use alloc::sync::Arc;
+
+use crate::{SwapPoint, SyncPoint};
+
+/// The file descriptor(s) used for signaling.
+enum Fd {
+    /// Counter based `eventfd`, read and written through the same descriptor
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    EventFd(OwnedFd),
+    /// Read and write end of a pipe
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    Pipe {
+        /// Read end, polled by the event loop
+        read: OwnedFd,
+        /// Write end, written when the other thread arrives
+        write: OwnedFd,
+    },
+}
+impl Fd {
+    /// Create a non-blocking `eventfd`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn new() -> io::Result<Self> {
+        // SAFETY:
+        // Plain syscall, the returned descriptor is checked before use.
+        let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY:
+        // `fd` is a freshly created descriptor that nothing else owns.
+        Ok(Self::EventFd(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+
+    /// Create a non-blocking pipe.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn new() -> io::Result<Self> {
+        let mut fds = [0; 2];
+        // SAFETY:
+        // `fds` has room for the two descriptors.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY:
+        // Freshly created descriptors that nothing else owns.
+        let (read, write) = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        for fd in [&read, &write] {
+            // SAFETY:
+            // `fd` is a valid descriptor.
+            unsafe {
+                let flags = libc::fcntl(fd.as_raw_fd(), libc::F_GETFL);
+                if flags < 0
+                    || libc::fcntl(fd.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK) < 0
+                {
+                    return Err(io::Error::last_os_error());
+                }
+                if libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+        }
+        Ok(Self::Pipe { read, write })
+    }
+
+    /// Descriptor that becomes readable when signaled.
+    fn read_fd(&self) -> BorrowedFd<'_> {
+        match *self {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Self::EventFd(ref fd) => fd.as_fd(),
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            Self::Pipe { ref read, .. } => read.as_fd(),
+        }
+    }
+
+    /// Descriptor that is written to signal.
+    fn write_fd(&self) -> BorrowedFd<'_> {
+        match *self {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Self::EventFd(ref fd) => fd.as_fd(),
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            Self::Pipe { ref write, .. } => write.as_fd(),
+        }
+    }
+
+    /// Make the read descriptor readable.
+    fn signal(&self) {
+        let one: u64 = 1;
+        // SAFETY:
+        // Writes 8 bytes from a valid `u64`. A full pipe or a saturated
+        // eventfd (`EAGAIN`) is already readable, so errors are ignored.
+        unsafe {
+            libc::write(
+                self.write_fd().as_raw_fd(),
+                (&one as *const u64).cast(),
+                core::mem::size_of::<u64>(),
+            );
+        }
+    }
+
+    /// Drain pending signals, making the read descriptor unreadable.
+    fn clear(&self) {
+        let mut buf = [0_u8; 64];
+        // SAFETY:
+        // Reads into a valid buffer of the given length. The descriptor is
+        // non-blocking, so this stops when it is drained.
+        while unsafe {
+            libc::read(
+                self.read_fd().as_raw_fd(),
+                buf.as_mut_ptr().cast(),
+                buf.len(),
+            )
+        } > 0
+        {}
+    }
+}
+
+/// Wrap the handle of the thread that does *not* run an event loop, so that
+/// every time it arrives at the sync point the returned [`RendezvousNotifier`]
+/// becomes readable.
+///
+/// # Errors
+/// If the `eventfd` or pipe could not be created.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::notify::bridge;
+/// use rendezvous_swap::{Rendezvous, SyncPoint};
+/// use std::thread;
+///
+/// let (mut event_loop_side, compute_side) = Rendezvous::new();
+/// let (mut compute_side, notifier) = bridge(compute_side).unwrap();
+/// let handle = thread::spawn(move || {
+///     // ... compute ...
+///     compute_side.sync();
+/// });
+/// // in the event loop: poll `notifier.as_raw_fd()` for readability, then
+/// # while !notifier.is_signaled() {}
+/// notifier.clear();
+/// event_loop_side.wait(); // does not spin, the other thread has arrived
+/// # handle.join().unwrap();
+/// ```
+#[inline]
+pub fn bridge<H: SyncPoint>(handle: H) -> io::Result<(Signaling<H>, RendezvousNotifier)> {
+    let fd = Arc::new(Fd::new()?);
+    Ok((
+        Signaling {
+            handle,
+            fd: Arc::clone(&fd),
+        },
+        RendezvousNotifier { fd },
+    ))
+}
+
+/// A handle that signals a [`RendezvousNotifier`] whenever it syncs, created
+/// by [`bridge`].
+#[non_exhaustive]
+pub struct Signaling<H> {
+    /// The wrapped handle
+    handle: H,
+    /// Signaled before every sync
+    fd: Arc<Fd>,
+}
+impl<H> Signaling<H> {
+    /// Unwrap the handle, which stops signaling.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> H {
+        self.handle
+    }
+}
+impl<H: SyncPoint> SyncPoint for Signaling<H> {
+    #[inline]
+    fn sync(&mut self) {
+        self.fd.signal();
+        self.handle.sync();
+    }
+}
+impl<H: SwapPoint> SwapPoint for Signaling<H> {
+    type Data = H::Data;
+    #[inline]
+    fn swap(&mut self) -> &mut H::Data {
+        self.fd.signal();
+        self.handle.swap()
+    }
+}
+
+/// File descriptor that becomes readable when the other thread arrives at
+/// the sync point, created by [`bridge`].
+///
+/// With the `mio` feature, this implements `mio::event::Source`, so it can be
+/// registered with `registry.register(&mut notifier, token, Interest::READABLE)`.
+#[non_exhaustive]
+pub struct RendezvousNotifier {
+    /// Signaled by [`Signaling`]
+    fd: Arc<Fd>,
+}
+impl RendezvousNotifier {
+    /// Drain pending signals. Call this before waiting on the own handle, so
+    /// that the descriptor is not readable until the other thread arrives
+    /// again.
+    #[inline]
+    pub fn clear(&self) {
+        self.fd.clear();
+    }
+
+    /// Check for a pending signal without blocking or clearing it.
+    #[must_use]
+    #[inline]
+    pub fn is_signaled(&self) -> bool {
+        let mut poll_fd = libc::pollfd {
+            fd: self.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY:
+        // Polls a single valid descriptor with a zero timeout.
+        unsafe { libc::poll(&mut poll_fd, 1, 0) > 0 }
+    }
+}
+#[cfg(feature = "mio")]
+impl mio::event::Source for RendezvousNotifier {
+    #[inline]
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).register(registry, token, interests)
+    }
+
+    #[inline]
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    #[inline]
+    fn deregister(&mut self, registry: &mio::Registry) -> io::Result<()> {
+        mio::unix::SourceFd(&self.as_raw_fd()).deregister(registry)
+    }
+}
+impl AsFd for RendezvousNotifier {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.read_fd()
+    }
+}
+impl AsRawFd for RendezvousNotifier {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.read_fd().as_raw_fd()
+    }
+}
+
+/// Handle for a task on a tokio runtime, that awaits the other thread
+/// instead of spinning.
+///
+/// Wraps the handle of the async side together with the
+/// [`RendezvousNotifier`] that the other side signals, see [`bridge`].
+/// # Example
+/// ```rust
+/// use rendezvous_swap::notify::{bridge, TokioRendezvous};
+/// use rendezvous_swap::{RendezvousData, SwapPoint};
+/// use std::thread;
+///
+/// let (async_side, compute_side) = RendezvousData::new(0, 0);
+/// let (mut compute_side, notifier) = bridge(compute_side).unwrap();
+/// let handle = thread::spawn(move || *compute_side.swap() = 3);
+/// let runtime = tokio::runtime::Builder::new_current_thread()
+///     .enable_io()
+///     .build()
+///     .unwrap();
+/// runtime.block_on(async move {
+///     let mut async_side = TokioRendezvous::new(async_side, notifier).unwrap();
+///     async_side.swap().await.unwrap();
+///     # handle.join().unwrap();
+/// });
+/// ```
+#[cfg(feature = "tokio")]
+#[non_exhaustive]
+pub struct TokioRendezvous<H> {
+    /// Handle of the async side
+    handle: H,
+    /// Signaled by the other side
+    notifier: tokio::io::unix::AsyncFd<RendezvousNotifier>,
+}
+#[cfg(feature = "tokio")]
+impl<H: SyncPoint> TokioRendezvous<H> {
+    /// Wrap `handle`, where the other thread signals `notifier`.
+    ///
+    /// # Errors
+    /// If the notifier could not be registered with the runtime, for example
+    /// because this is not called within a runtime with IO enabled.
+    #[inline]
+    pub fn new(handle: H, notifier: RendezvousNotifier) -> io::Result<Self> {
+        Ok(Self {
+            handle,
+            notifier: tokio::io::unix::AsyncFd::new(notifier)?,
+        })
+    }
+
+    /// Wait until the other thread has arrived at the sync point, without
+    /// blocking the runtime.
+    async fn arrival(&self) -> io::Result<()> {
+        loop {
+            let mut guard = self.notifier.readable().await?;
+            let signaled = guard.get_inner().is_signaled();
+            guard.get_inner().clear();
+            guard.clear_ready();
+            if signaled {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Asynchronously synchronize execution with the other thread.
+    ///
+    /// # Errors
+    /// If polling the notifier failed.
+    #[inline]
+    pub async fn wait(&mut self) -> io::Result<()> {
+        self.arrival().await?;
+        self.handle.sync();
+        Ok(())
+    }
+
+    /// Unwrap the handle and the notifier.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> (H, RendezvousNotifier) {
+        (self.handle, self.notifier.into_inner())
+    }
+}
+#[cfg(feature = "tokio")]
+impl<H: SwapPoint> TokioRendezvous<H> {
+    /// Asynchronously swap data with the other thread.
+    ///
+    /// # Errors
+    /// If polling the notifier failed.
+    #[inline]
+    pub async fn swap(&mut self) -> io::Result<&mut H::Data> {
+        self.arrival().await?;
+        Ok(self.handle.swap())
+    }
+}
@@ -0,0 +1,203 @@
+//! Contains [`Observer`] and [`Watch`]
+
+#[cfg(feature = "std")]
+use crate::os::std::{thread, time::Instant};
+use alloc::sync::Arc;
+use core::hint::spin_loop;
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+use crate::counter::CounterPair;
+
+/// Read-only view of the progress of a [`Rendezvous`](crate::Rendezvous)
+/// pair, created with [`Rendezvous::observer`](crate::Rendezvous::observer).
+///
+/// Observing never writes to the shared counters, so a monitoring thread can
+/// poll it without invalidating the cache lines the pair is spinning on.
+#[derive(Clone)]
+pub struct Observer {
+    /// Counters of the pair
+    counters: Arc<CounterPair>,
+}
+
+/// Snapshot of the progress of a pair, see [`Observer::observe`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug)]
+pub struct Observation {
+    /// Number of generations completed by both threads (wrapping)
+    pub generation: usize,
+    /// When the snapshot was taken
+    #[cfg(feature = "std")]
+    pub time: Instant,
+}
+impl Observation {
+    /// Generations completed since an `earlier` observation.
+    #[must_use]
+    #[inline]
+    pub const fn generations_since(&self, earlier: &Self) -> usize {
+        self.generation.wrapping_sub(earlier.generation)
+    }
+
+    /// Forward-progress rate in generations per second since an `earlier`
+    /// observation.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn rate_since(&self, earlier: &Self) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let generations = self.generations_since(earlier) as f64;
+        generations / self.time.duration_since(earlier.time).as_secs_f64()
+    }
+}
+
+impl Observer {
+    /// Construct from the counters of a pair.
+    pub(crate) const fn new(counters: Arc<CounterPair>) -> Self {
+        Self { counters }
+    }
+
+    /// Take a consistent snapshot of the progress of the pair.
+    ///
+    /// Reads the counters like a sequence lock: the first counter is read
+    /// again after the second, and the read is retried if it changed.
+    #[must_use]
+    #[inline]
+    pub fn observe(&self) -> Observation {
+        let [first_counter, second_counter] = &*self.counters;
+        let generation = loop {
+            let first = first_counter.load();
+            let second = second_counter.load();
+            if first_counter.load() == first {
+                // The counters are at most one generation apart, the pair has
+                // completed the one that is behind.
+                #[allow(clippy::cast_possible_wrap)]
+                let first_ahead = first.wrapping_sub(second) as isize > 0;
+                break if first_ahead { second } else { first };
+            }
+            spin_loop();
+        };
+        Observation {
+            generation,
+            #[cfg(feature = "std")]
+            time: Instant::now(),
+        }
+    }
+
+    /// Poll for the generations the pair completes from now on, see
+    /// [`Watch`].
+    #[must_use]
+    #[inline]
+    pub fn watch(self) -> Watch {
+        let seen = self.observe().generation;
+        Watch {
+            observer: self,
+            seen,
+        }
+    }
+
+    /// True once the pair will not complete another generation: both
+    /// handles have left, or one has and the other has passed the sync
+    /// point it arrived at when leaving.
+    fn is_closed(&self) -> bool {
+        let [first_counter, second_counter] = &*self.counters;
+        let (first, second) = (first_counter.has_left(), second_counter.has_left());
+        (first && second) || ((first || second) && first_counter.load() == second_counter.load())
+    }
+}
+
+/// Polling watcher that reports how many generations a
+/// [`Rendezvous`](crate::Rendezvous) pair completed since it last looked,
+/// created with [`Observer::watch`] or
+/// [`Rendezvous::watch`](crate::Rendezvous::watch).
+///
+/// For frame-rate counters, debug overlays and the like that must not take
+/// part in or perturb the barrier: like [`Observer`], watching never writes
+/// to the shared counters, and the pair does no extra work for it. The pair
+/// does not wake the watcher, so `Watch::wait` sleeps for a fixed interval
+/// between polls: generations completed in the meantime are reported
+/// together, and a report may come up to one interval late.
+#[non_exhaustive]
+pub struct Watch {
+    /// Reads the counters
+    observer: Observer,
+    /// Last generation reported
+    seen: usize,
+}
+impl Watch {
+    /// Number of generations completed since the last call (or since the
+    /// watch was created), without waiting.
+    #[must_use]
+    #[inline]
+    pub fn poll(&mut self) -> usize {
+        let generation = self.observer.observe().generation;
+        let generations = generation.wrapping_sub(self.seen);
+        self.seen = generation;
+        generations
+    }
+
+    /// Poll every `interval` until the pair has completed a generation, and
+    /// get the number of generations completed since the last poll. `None`
+    /// once the pair has completed its last
+    /// generation, see [`Watch::is_closed`].
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn wait(&mut self, interval: Duration) -> Option<usize> {
+        loop {
+            let closed = self.observer.is_closed();
+            let generations = self.poll();
+            if generations > 0 {
+                return Some(generations);
+            }
+            if closed {
+                return None;
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    /// Poll every `interval` and call `report` with the number of
+    /// generations completed since the last poll, whenever it is not zero,
+    /// until the pair has completed its last generation.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let watch = my_rendezvous.watch();
+    /// let monitor = thread::spawn(move || {
+    ///     let mut frames = 0;
+    ///     watch.for_each(Duration::from_millis(1), |generations| frames += generations);
+    ///     frames
+    /// });
+    /// let handle = thread::spawn(move || {
+    ///     for _ in 0..100 {
+    ///         their_rendezvous.wait();
+    ///     }
+    /// });
+    /// for _ in 0..100 {
+    ///     my_rendezvous.wait();
+    /// }
+    /// # handle.join().unwrap();
+    /// drop(my_rendezvous);
+    /// // leaving arrives at one more sync point
+    /// assert_eq!(monitor.join().unwrap(), 101);
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn for_each<F: FnMut(usize)>(mut self, interval: Duration, mut report: F) {
+        while let Some(generations) = self.wait(interval) {
+            report(generations);
+        }
+    }
+
+    /// True once the pair will not complete another generation: both
+    /// handles have been dropped, or one has and the other has passed the
+    /// sync point the dropped handle arrived at.
+    #[must_use]
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.observer.is_closed()
+    }
+}
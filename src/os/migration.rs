@@ -0,0 +1,19 @@
+//! Detection of threads being moved between CPUs by the OS scheduler
+
+/// A thread samples the CPU it runs on every this many generations.
+pub(crate) const SAMPLE_INTERVAL: usize = 64;
+
+/// The CPU the calling thread is running on right now, if known.
+///
+/// Only implemented on Linux, where it is cheap (`sched_getcpu` is served
+/// by the vDSO).
+pub(crate) fn current_cpu() -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY:
+        // `sched_getcpu` has no preconditions.
+        usize::try_from(unsafe { libc::sched_getcpu() }).ok()
+    }
+    #[cfg(not(target_os = "linux"))]
+    None
+}
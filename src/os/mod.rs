@@ -0,0 +1,26 @@
+//! Everything that needs an operating system, only compiled with the `std`
+//! feature.
+//!
+//! This is the only module that links `std`. The rest of the crate cannot
+//! name it, so it stays `no_std` by construction: code that needs the OS has
+//! to reach it through `crate::os`, which does not exist without `std`.
+
+pub(crate) extern crate std;
+
+#[cfg(feature = "migration-stats")]
+pub(crate) mod migration;
+pub(crate) mod smt;
+#[cfg(windows)]
+mod windows;
+
+/// Give the rest of the time slice to another thread.
+///
+/// On Windows this falls back to `Sleep(0)` when no thread is ready on the
+/// current processor, see `windows::yield_now`.
+#[inline]
+pub(crate) fn yield_now() {
+    #[cfg(windows)]
+    windows::yield_now();
+    #[cfg(not(windows))]
+    std::thread::yield_now();
+}
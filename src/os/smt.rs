@@ -0,0 +1,43 @@
+//! Detection of two threads running on SMT (hyperthread) siblings
+
+use crate::os::std::fs;
+
+/// Number of pause instructions per spin while the threads are siblings.
+///
+/// A spinning hyperthread competes with its sibling for the execution units
+/// of their shared core, so spinning more slowly leaves more of the core to
+/// the thread doing the work.
+pub(crate) const SIBLING_PAUSES: u32 = 16;
+
+/// The CPU the calling thread is running on right now, if known.
+///
+/// Only implemented on Linux, where it is read from `/proc`.
+pub(crate) fn current_cpu() -> Option<usize> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let stat = fs::read_to_string("/proc/thread-self/stat").ok()?;
+    // The command name in field 2 may contain spaces, the processor is
+    // field 39.
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(36)?.parse().ok()
+}
+
+/// True if `a` and `b` are different hardware threads of the same core.
+pub(crate) fn are_siblings(a: usize, b: usize) -> bool {
+    if a == b {
+        return false;
+    }
+    let path =
+        crate::os::std::format!("/sys/devices/system/cpu/cpu{a}/topology/thread_siblings_list");
+    fs::read_to_string(path).is_ok_and(|list| list_contains(&list, b))
+}
+
+/// True if a CPU list such as `0,4` or `0-1` contains `cpu`.
+fn list_contains(list: &str, cpu: usize) -> bool {
+    list.trim().split(',').any(|range| {
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        let parse = |s: &str| s.parse::<usize>().ok();
+        matches!((parse(first), parse(last)), (Some(first), Some(last)) if (first..=last).contains(&cpu))
+    })
+}
@@ -0,0 +1,24 @@
+//! Yielding tuned for the Windows scheduler.
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn SwitchToThread() -> i32;
+    fn Sleep(milliseconds: u32);
+}
+
+/// Give the rest of the time slice to another thread.
+///
+/// `SwitchToThread` (all that `std::thread::yield_now` does) only runs
+/// threads that are ready on the current processor, and returns false if
+/// there are none. A partner that was descheduled is usually queued on
+/// another processor, which `Sleep(0)` lets run by offering the time slice
+/// to ready threads of the same priority on any processor.
+pub(crate) fn yield_now() {
+    // SAFETY:
+    // Neither function has preconditions.
+    unsafe {
+        if SwitchToThread() == 0 {
+            Sleep(0);
+        }
+    }
+}
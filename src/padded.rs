@@ -0,0 +1,82 @@
+//! Contains [`Padded`]
+
+use core::ops::{Deref, DerefMut};
+
+/// Pads and aligns a value to the length of a cache line, so that values on
+/// either side of it never share a line (avoids false sharing).
+///
+/// The alignment depends on the target, see [`crate::CACHE_LINE_PADDING`],
+/// following crossbeam's `CachePadded`: 128 bytes where adjacent lines are
+/// prefetched in pairs (`x86_64`, `aarch64`, `powerpc64`), 256 on `s390x`,
+/// less on embedded targets with short lines, and 64 elsewhere.
+///
+/// The value is at offset 0 (`repr(C)`), so a pointer to a `Padded<T>` can
+/// be cast to a pointer to the `T`.
+#[repr(C)]
+#[cfg_attr(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "arm64ec",
+        target_arch = "powerpc64",
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(target_arch = "s390x", repr(align(256)))]
+#[cfg_attr(
+    any(
+        target_arch = "arm",
+        target_arch = "mips",
+        target_arch = "mips32r6",
+        target_arch = "mips64",
+        target_arch = "mips64r6",
+        target_arch = "sparc",
+        target_arch = "hexagon",
+    ),
+    repr(align(32))
+)]
+#[cfg_attr(target_arch = "m68k", repr(align(16)))]
+#[cfg_attr(
+    not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "arm64ec",
+        target_arch = "powerpc64",
+        target_arch = "s390x",
+        target_arch = "arm",
+        target_arch = "mips",
+        target_arch = "mips32r6",
+        target_arch = "mips64",
+        target_arch = "mips64r6",
+        target_arch = "sparc",
+        target_arch = "hexagon",
+        target_arch = "m68k",
+    )),
+    repr(align(64))
+)]
+pub(crate) struct Padded<T>(T);
+
+impl<T> Padded<T> {
+    /// Wrap `value` in its own cache line.
+    pub(crate) const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Unwrap the value.
+    pub(crate) fn into_inner(self) -> T {
+        self.0
+    }
+}
+impl<T> Deref for Padded<T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+impl<T> DerefMut for Padded<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,173 @@
+//! Contains [`PairArena`] and [`PairArenaSide`]
+
+use alloc::vec::Vec;
+
+use crate::{RendezvousDataRef, RendezvousDataStorage};
+
+/// Many pairs of [`RendezvousDataRef`] with stable indices, their shared
+/// state stored contiguously in one arena, for schedulers that run a pair
+/// per entity or per system.
+///
+/// Pairs are created and destroyed in batches while no handles exist (the
+/// safe points between frames), which the borrow of [`PairArena::split`]
+/// enforces. Each thread then gets a [`PairArenaSide`] with its handle of
+/// every pair, indexed like the arena.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::PairArena;
+/// use std::thread;
+///
+/// let mut arena = PairArena::new();
+/// let indices = arena.insert_batch([(0, 0), (0, 0), (0, 0)]);
+/// let (mut my_side, mut their_side) = arena.split();
+/// thread::scope(|scope| {
+///     scope.spawn(move || {
+///         for (index, handle) in their_side.iter_mut() {
+///             *handle.swap() = index;
+///         }
+///     });
+///     for (_, handle) in my_side.iter_mut() {
+///         handle.swap();
+///     }
+/// });
+/// drop(my_side);
+/// // both sides are gone, so pairs can be added and removed
+/// assert_eq!(arena.remove(indices[1]), Some((1, 0)));
+/// assert_eq!(arena.len(), 2);
+/// ```
+#[non_exhaustive]
+pub struct PairArena<T: Send> {
+    /// Shared state of every pair, `None` for removed pairs
+    storages: Vec<Option<RendezvousDataStorage<T>>>,
+    /// Indices of removed pairs, reused by the next insertions
+    free: Vec<usize>,
+}
+impl<T: Send> PairArena<T> {
+    /// Create an empty arena.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            storages: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Add a pair, the first handle starts out with `data1` and the second
+    /// with `data2`, and return its index, which stays valid until the pair
+    /// is removed.
+    #[inline]
+    pub fn insert(&mut self, data1: T, data2: T) -> usize {
+        let storage = Some(RendezvousDataStorage::new(data1, data2));
+        if let Some(index) = self.free.pop() {
+            self.storages[index] = storage;
+            index
+        } else {
+            self.storages.push(storage);
+            self.storages.len() - 1
+        }
+    }
+
+    /// [`PairArena::insert`] every pair of initial values, and return
+    /// their indices in the same order.
+    #[inline]
+    pub fn insert_batch(&mut self, data: impl IntoIterator<Item = (T, T)>) -> Vec<usize> {
+        data.into_iter()
+            .map(|(data1, data2)| self.insert(data1, data2))
+            .collect()
+    }
+
+    /// Remove the pair at `index`, and return its buffers (the buffer of the
+    /// first handle first), or `None` if there is no pair at `index`.
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> Option<(T, T)> {
+        let storage = self.storages.get_mut(index)?.take()?;
+        self.free.push(index);
+        Some(storage.into_inner())
+    }
+
+    /// Remove every pair for which `f` returns false.
+    #[inline]
+    pub fn retain(&mut self, mut f: impl FnMut(usize) -> bool) {
+        for (index, storage) in self.storages.iter_mut().enumerate() {
+            if storage.is_some() && !f(index) {
+                *storage = None;
+                self.free.push(index);
+            }
+        }
+    }
+
+    /// Number of pairs in the arena.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.storages.len() - self.free.len()
+    }
+
+    /// True if the arena has no pairs.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// True if there is a pair at `index`.
+    #[must_use]
+    #[inline]
+    pub fn contains(&self, index: usize) -> bool {
+        matches!(self.storages.get(index), Some(Some(_)))
+    }
+
+    /// Create the handles of every pair, the first handles on the first
+    /// side and the second handles on the other.
+    ///
+    /// Like [`RendezvousDataRef::new_in`], every pair starts over at
+    /// generation 0 and the buffers keep their contents. The arena stays
+    /// borrowed until both sides are gone.
+    #[must_use]
+    #[inline]
+    pub fn split(&mut self) -> (PairArenaSide<'_, T>, PairArenaSide<'_, T>) {
+        let (first, second) = self
+            .storages
+            .iter_mut()
+            .map(|storage| storage.as_mut().map(RendezvousDataRef::new_in).unzip())
+            .unzip();
+        (
+            PairArenaSide { handles: first },
+            PairArenaSide { handles: second },
+        )
+    }
+}
+impl<T: Send> Default for PairArena<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The handles of one thread for every pair of a [`PairArena`], created
+/// with [`PairArena::split`].
+#[non_exhaustive]
+pub struct PairArenaSide<'arena, T: Send> {
+    /// Handle of every pair, at the index of the pair in the arena
+    handles: Vec<Option<RendezvousDataRef<'arena, T>>>,
+}
+impl<'arena, T: Send> PairArenaSide<'arena, T> {
+    /// The handle of the pair at `index`, or `None` if there is no pair at
+    /// `index`.
+    #[must_use]
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut RendezvousDataRef<'arena, T>> {
+        self.handles.get_mut(index)?.as_mut()
+    }
+
+    /// Iterate over the handles of this side in index order, along with
+    /// the index of each pair.
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut RendezvousDataRef<'arena, T>)> {
+        self.handles
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, handle)| Some((index, handle.as_mut()?)))
+    }
+}
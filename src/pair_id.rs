@@ -0,0 +1,48 @@
+//! Contains [`PairId`]
+
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+/// Last ID handed out.
+static LAST: AtomicUsize = AtomicUsize::new(0);
+
+/// Identifies a linked pair of handles, unique among all pairs created by the
+/// program (until `usize` wraps).
+///
+/// Both handles of a pair report the same ID, which is handy for logging
+/// which pair a message refers to.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::Rendezvous;
+///
+/// let (a, b) = Rendezvous::new();
+/// let (c, _d) = Rendezvous::new();
+/// assert_eq!(a.pair_id(), b.pair_id());
+/// assert_ne!(a.pair_id(), c.pair_id());
+/// assert!(Rendezvous::same_pair(&a, &b));
+/// assert!(!Rendezvous::same_pair(&a, &c));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PairId(usize);
+impl PairId {
+    /// Placeholder for counters that are not part of a pair.
+    pub(crate) const NONE: Self = Self(0);
+
+    /// A new unique ID.
+    pub(crate) fn next() -> Self {
+        Self(LAST.fetch_add(1, Relaxed).wrapping_add(1))
+    }
+
+    /// The ID as a number, IDs are handed out counting up from 1.
+    #[must_use]
+    #[inline]
+    pub const fn get(self) -> usize {
+        self.0
+    }
+}
+impl fmt::Display for PairId {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pair #{}", self.0)
+    }
+}
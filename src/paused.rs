@@ -0,0 +1,123 @@
+//! Contains [`Paused`]
+
+use crate::{Rendezvous, RendezvousData, SyncPoint};
+
+/// A handle that both threads agreed to stop syncing, created by
+/// [`Rendezvous::pause`] or [`RendezvousData::pause`].
+///
+/// While paused, each thread is free-running: it may sleep, work on its own,
+/// or be torn down and replaced (the [`Paused`] handle can be moved to the new
+/// thread). Since neither side can sync while paused, the generations of the
+/// two sides stay reconciled, and [`Paused::resume`] re-establishes lockstep.
+/// # Example: Hot-reload one side
+/// ```rust
+/// use rendezvous_swap::Rendezvous;
+/// use std::thread;
+///
+/// let (mut my_rendezvous, their_rendezvous) = Rendezvous::new();
+/// let worker = thread::spawn(move || their_rendezvous.pause());
+/// let paused = my_rendezvous.pause();
+/// let their_paused = worker.join().unwrap();
+///
+/// // ... reload the worker code ...
+/// let worker = thread::spawn(move || their_paused.resume().wait());
+/// paused.resume().wait();
+/// # worker.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct Paused<H> {
+    /// The handle, which is not allowed to sync until resumed
+    handle: H,
+}
+impl<H: SyncPoint> Paused<H> {
+    /// Sync a last time with the other thread, which must also pause, and
+    /// then stop syncing.
+    #[must_use]
+    #[inline]
+    pub fn pause(mut handle: H) -> Self {
+        handle.sync();
+        Self { handle }
+    }
+
+    /// Re-establish lockstep with the other thread, waiting for it to resume
+    /// as well.
+    #[must_use]
+    #[inline]
+    pub fn resume(mut self) -> H {
+        self.handle.sync();
+        self.handle
+    }
+}
+impl<T: Send> Paused<RendezvousData<T>> {
+    /// Get a mutable reference to this side's data while paused.
+    ///
+    /// This is the data returned by the swap that paused the pair.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.handle.current_mut()
+    }
+
+    /// Cooperation point where each thread gets access to both buffers, for
+    /// example to resize them, before lockstep is restarted.
+    ///
+    /// The closures run one after the other, first the one of the first
+    /// handle returned by [`RendezvousData::new`], and get this thread's data
+    /// first and the other thread's data second. The counters and threads
+    /// are reused, only the buffers change.
+    ///
+    /// # Safety
+    /// The other thread must call this too, before resuming. The first
+    /// handle's `f` runs after a single sync, so if the other thread resumes
+    /// instead and then swaps or accesses its data, it gets a mutable
+    /// reference to a buffer `f` is accessing. The `strict` feature panics on
+    /// such a mismatch, but only after the sync.
+    /// # Example: Grow both buffers
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (my_rendezvous, their_rendezvous) = RendezvousData::new(vec![0; 4], vec![0; 4]);
+    /// let handle = thread::spawn(move || {
+    ///     let mut paused = their_rendezvous.pause();
+    ///     // nothing to do on this side
+    ///     // SAFETY: both threads call `reinit` before resuming
+    ///     unsafe { paused.reinit(|_, _| {}) };
+    ///     paused.resume().swap().len()
+    /// });
+    /// let mut paused = my_rendezvous.pause();
+    /// // SAFETY: both threads call `reinit` before resuming
+    /// unsafe {
+    ///     paused.reinit(|mine, theirs| {
+    ///         mine.resize(1024, 0);
+    ///         theirs.resize(1024, 0);
+    ///     });
+    /// }
+    /// assert_eq!(paused.resume().swap().len(), 1024);
+    /// assert_eq!(handle.join().unwrap(), 1024);
+    /// ```
+    #[inline]
+    pub unsafe fn reinit<F: FnOnce(&mut T, &mut T)>(&mut self, f: F) {
+        // SAFETY:
+        // The caller guarantees that the other thread calls this too.
+        unsafe { self.handle.with_both(f) };
+    }
+}
+
+impl Rendezvous {
+    /// Sync a last time with the other thread, which must also pause, and
+    /// then stop syncing until [`Paused::resume`].
+    #[must_use]
+    #[inline]
+    pub fn pause(self) -> Paused<Self> {
+        Paused::pause(self)
+    }
+}
+impl<T: Send> RendezvousData<T> {
+    /// Swap a last time with the other thread, which must also pause, and
+    /// then stop swapping until [`Paused::resume`].
+    #[must_use]
+    #[inline]
+    pub fn pause(self) -> Paused<Self> {
+        Paused::pause(self)
+    }
+}
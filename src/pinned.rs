@@ -0,0 +1,75 @@
+//! Contains [`PinnedRendezvousData`]
+
+use core::pin::Pin;
+
+use crate::{RendezvousData, SyncPoint};
+
+/// A [`RendezvousData`] for payloads that must never move, such as structs
+/// containing intrusive lists or futures.
+///
+/// [`RendezvousData`] only ever swaps pointers, so the data stays where it
+/// was placed at construction until both handles are dropped, and is dropped
+/// in place. This type exposes that guarantee: it only hands out
+/// [`Pin<&mut T>`](Pin), so `!Unpin` state can legally be kept in the buffers.
+/// Both handles of a pair are pinned, since a plain [`RendezvousData::swap`]
+/// on either side could move the data out.
+/// # Example
+/// ```rust
+/// use core::marker::PhantomPinned;
+/// use core::pin::Pin;
+/// use rendezvous_swap::PinnedRendezvousData;
+/// use std::thread;
+///
+/// #[derive(Default)]
+/// struct NoMove {
+///     value: i32,
+///     _pinned: PhantomPinned,
+/// }
+/// fn set(data: Pin<&mut NoMove>, value: i32) {
+///     // SAFETY: `value` is not structurally pinned
+///     unsafe { data.get_unchecked_mut() }.value = value;
+/// }
+///
+/// let (mut my_rendezvous, mut their_rendezvous) =
+///     PinnedRendezvousData::new(NoMove::default(), NoMove::default());
+/// let handle = thread::spawn(move || {
+///     set(their_rendezvous.swap_pinned(), 3);
+///     their_rendezvous.swap_pinned();
+/// });
+/// set(my_rendezvous.swap_pinned(), 7);
+/// assert_eq!(my_rendezvous.swap_pinned().value, 3);
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct PinnedRendezvousData<T: Send> {
+    /// The handle, which is never exposed
+    inner: RendezvousData<T>,
+}
+impl<T: Send> PinnedRendezvousData<T> {
+    /// Create a linked pair of [`PinnedRendezvousData`]
+    /// Arguments are the initial values for the data that will be swapped,
+    /// they are pinned from here on.
+    #[must_use]
+    #[inline]
+    pub fn new(data1: T, data2: T) -> (Self, Self) {
+        let (first, second) = RendezvousData::new(data1, data2);
+        (Self { inner: first }, Self { inner: second })
+    }
+
+    /// Swap data with other thread and get a pinned mutable reference to the
+    /// data.
+    #[allow(clippy::needless_lifetimes)] // lifetime needs to be restricted here
+    #[inline]
+    pub fn swap_pinned<'lock>(&'lock mut self) -> Pin<&'lock mut T> {
+        // SAFETY:
+        // The data lives in the shared allocation until both handles are
+        // dropped, and no `&mut T` to it is ever exposed by this type.
+        unsafe { Pin::new_unchecked(self.inner.swap()) }
+    }
+}
+impl<T: Send> SyncPoint for PinnedRendezvousData<T> {
+    #[inline]
+    fn sync(&mut self) {
+        self.inner.swap();
+    }
+}
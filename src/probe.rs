@@ -0,0 +1,53 @@
+//! Contains [`ProbeResult`]
+
+use core::time::Duration;
+
+use crate::clock::Clock;
+use crate::counter::Counter;
+use crate::last_error::ErrorKind;
+
+/// Liveness of the other thread of a pair, as determined by `probe` on
+/// [`Rendezvous`](crate::Rendezvous) or [`RendezvousData`](crate::RendezvousData).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbeResult {
+    /// The other thread is waiting at the sync point.
+    Waiting,
+    /// The other thread answered the probe: it is busy, but alive.
+    Alive,
+    /// The other thread did not answer within the timeout: it is stuck,
+    /// or dead. Also returned right away once the other handle is dropped.
+    Unresponsive,
+}
+
+/// Probe the owner of `theirs`, timing out with `clock`, see
+/// `Rendezvous::probe`. A timeout is recorded in `mine`.
+pub(crate) fn probe(
+    theirs: &Counter,
+    mine: &Counter,
+    generation: usize,
+    timeout: Duration,
+    clock: &impl Clock,
+) -> ProbeResult {
+    let deadline = clock.now().checked_add(timeout);
+    let ticket = theirs.post_probe();
+    loop {
+        if theirs.has_left() {
+            mine.record_error(ErrorKind::Unresponsive, generation);
+            return ProbeResult::Unresponsive;
+        }
+        if theirs.load() != generation {
+            return ProbeResult::Waiting;
+        }
+        if theirs.probe_answered(ticket) {
+            return ProbeResult::Alive;
+        }
+        if deadline.is_some_and(|deadline| clock.now() >= deadline) {
+            mine.record_error(ErrorKind::Unresponsive, generation);
+            return ProbeResult::Unresponsive;
+        }
+        #[cfg(feature = "std")]
+        crate::os::yield_now();
+        #[cfg(not(feature = "std"))]
+        core::hint::spin_loop();
+    }
+}
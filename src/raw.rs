@@ -0,0 +1,175 @@
+//! The generation handshake that the rest of the crate is built on, for
+//! building custom structures with user managed data
+//!
+//! Each thread of a pair owns a counter that only it writes. To sync, a
+//! thread at generation `g` publishes `g + 1` to its counter with a release
+//! store ([`GenerationGate::arrive`]), and then spins until the other counter
+//! is no longer `g` and loads it with acquire ([`GenerationGate::complete`]),
+//! after which it is at generation `g + 1`. Because the other thread cannot
+//! get past its own sync point before this one arrives, the threads are
+//! never more than one generation apart, and everything a thread wrote
+//! before arriving is visible to the other thread once it completes.
+//!
+//! Splitting the handshake lets a thread publish its data early and do other
+//! work before waiting for the other thread. What data each thread may touch
+//! in which generation is up to the user, for example [`RendezvousData`](crate::RendezvousData) gives
+//! each thread one of two buffers and switches them every generation.
+
+use alloc::sync::Arc;
+
+#[cfg(feature = "std")]
+use crate::contention::{Contention, ContentionStats};
+use crate::counter::Counter;
+use crate::SyncPoint;
+
+/// One side of the generation handshake, see the [module level
+/// documentation](self).
+/// # Example: Split-phase sync
+/// ```rust
+/// use rendezvous_swap::raw::GenerationGate;
+/// use std::sync::atomic::{AtomicU32, Ordering::Relaxed};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let (mut my_gate, mut their_gate) = GenerationGate::new();
+/// let data = Arc::new(AtomicU32::new(0));
+/// let their_data = Arc::clone(&data);
+/// let handle = thread::spawn(move || {
+///     their_data.store(7, Relaxed);
+///     their_gate.arrive(); // publish
+///     // ... other work ...
+///     their_gate.complete();
+/// });
+/// my_gate.wait();
+/// assert_eq!(data.load(Relaxed), 7);
+/// assert_eq!(my_gate.generation(), 1);
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct GenerationGate {
+    /// Atomic counter for this thread
+    my_counter: Arc<Counter>,
+    /// Atomic counter for other thread
+    their_counter: Arc<Counter>,
+    /// Thread local generation
+    generation: usize,
+    /// True between `arrive` and `complete`
+    arrived: bool,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+}
+impl GenerationGate {
+    /// Create a linked pair of [`GenerationGate`]
+    #[must_use]
+    #[inline]
+    pub fn new() -> (Self, Self) {
+        let first = Arc::new(Counter::new());
+        let second = Arc::new(Counter::new());
+        (
+            Self::from_counters(Arc::clone(&first), Arc::clone(&second)),
+            Self::from_counters(second, first),
+        )
+    }
+
+    /// Gate at generation 0 on the given counters.
+    fn from_counters(my_counter: Arc<Counter>, their_counter: Arc<Counter>) -> Self {
+        Self {
+            my_counter,
+            their_counter,
+            generation: 0,
+            arrived: false,
+            #[cfg(feature = "std")]
+            contention: Contention::new(),
+        }
+    }
+
+    /// Number of completed handshakes (wrapping).
+    #[must_use]
+    #[inline]
+    pub const fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Publish that this thread arrived at the sync point, releasing
+    /// everything written before to the other thread.
+    ///
+    /// # Panics
+    /// If this thread already arrived and has not completed.
+    #[inline]
+    pub fn arrive(&mut self) {
+        assert!(!self.arrived, "arrive called twice without complete");
+        self.arrived = true;
+        self.my_counter.store(self.generation.wrapping_add(1));
+    }
+
+    /// True if the other thread has arrived at the sync point of the
+    /// current generation, so that [`GenerationGate::complete`] would
+    /// return immediately once this thread has arrived.
+    #[must_use]
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        self.their_counter.load() != self.generation
+    }
+
+    /// Complete the handshake if the other thread has arrived, without
+    /// waiting. Returns true if it completed.
+    ///
+    /// # Panics
+    /// If this thread has not arrived.
+    #[inline]
+    pub fn try_complete(&mut self) -> bool {
+        assert!(self.arrived, "complete called without arrive");
+        if !self.is_open() {
+            return false;
+        }
+        self.their_counter.acquire();
+        self.advance();
+        true
+    }
+
+    /// Wait for the other thread to arrive, acquiring everything it wrote
+    /// before arriving, and move on to the next generation.
+    ///
+    /// # Panics
+    /// If this thread has not arrived.
+    #[inline]
+    pub fn complete(&mut self) {
+        assert!(self.arrived, "complete called without arrive");
+        self.their_counter.wait_past(
+            self.generation,
+            &self.my_counter,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+        );
+        self.advance();
+    }
+
+    /// [`GenerationGate::arrive`] followed by [`GenerationGate::complete`].
+    #[inline]
+    pub fn wait(&mut self) {
+        self.arrive();
+        self.complete();
+    }
+
+    /// Statistics on how often [`GenerationGate::complete`] gave up spinning
+    /// and yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+
+    /// Move on to the next generation after completing.
+    fn advance(&mut self) {
+        self.arrived = false;
+        self.generation = self.generation.wrapping_add(1);
+    }
+}
+impl SyncPoint for GenerationGate {
+    #[inline]
+    fn sync(&mut self) {
+        self.wait();
+    }
+}
@@ -0,0 +1,105 @@
+//! Contains [`Rendezvous::into_raw`] and [`RendezvousDataN::into_raw`]
+
+use alloc::boxed::Box;
+use core::ffi::c_void;
+
+use crate::{Rendezvous, RendezvousDataN};
+
+impl Rendezvous {
+    /// Turn the handle into an opaque pointer, to be stored in a `void*` of a
+    /// C program and turned back into the handle with
+    /// [`Rendezvous::from_raw`], possibly on another thread.
+    ///
+    /// The pointer owns the handle (which is moved to the heap with its
+    /// generation and statistics): the shared state of the pair is kept alive
+    /// until the handle is recovered and dropped, and is leaked if it never
+    /// is. The other thread is not affected, it simply waits at the next sync
+    /// point until the handle is recovered and used.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, their_rendezvous) = Rendezvous::new();
+    /// // pointers are not `Send`, C code would pass it along as a `void*`
+    /// let raw = their_rendezvous.into_raw() as usize;
+    /// let handle = thread::spawn(move || {
+    ///     // SAFETY: from `Rendezvous::into_raw`, recovered once
+    ///     let mut their_rendezvous = unsafe { Rendezvous::from_raw(raw as *mut _) };
+    ///     their_rendezvous.wait();
+    /// });
+    /// my_rendezvous.wait();
+    /// # handle.join().unwrap();
+    /// ```
+    #[must_use = "the handle is leaked unless recovered with `from_raw`"]
+    #[inline]
+    pub fn into_raw(self) -> *mut c_void {
+        Box::into_raw(Box::new(self)).cast()
+    }
+
+    /// Recover a handle from [`Rendezvous::into_raw`].
+    ///
+    /// # Safety
+    /// * `raw` must come from [`Rendezvous::into_raw`].
+    /// * Each pointer must be recovered at most once, the handle it owned is
+    ///   freed when the recovered handle is dropped, and recovering it again
+    ///   is a double free.
+    #[must_use]
+    #[inline]
+    pub unsafe fn from_raw(raw: *mut c_void) -> Self {
+        // SAFETY:
+        // The caller guarantees that `raw` is an unrecovered pointer from
+        // `Box::into_raw` of a `Rendezvous`.
+        *unsafe { Box::from_raw(raw.cast::<Self>()) }
+    }
+}
+
+impl<T: Send, const SLOTS: usize> RendezvousDataN<T, SLOTS> {
+    /// Turn the handle into an opaque pointer, to be stored in a `void*` of a
+    /// C program and turned back into the handle with
+    /// [`RendezvousDataN::from_raw`], possibly on another thread, see
+    /// [`Rendezvous::into_raw`].
+    ///
+    /// The pointer owns the handle, so the shared state of the pair (both
+    /// buffers) is kept alive until the handle is recovered and dropped, and
+    /// is leaked if it never is.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, their_rendezvous) = RendezvousData::new(0, 0);
+    /// let raw = their_rendezvous.into_raw() as usize;
+    /// let handle = thread::spawn(move || {
+    ///     // SAFETY: from `RendezvousData::<i32>::into_raw`, recovered once
+    ///     let mut their_rendezvous = unsafe { RendezvousData::<i32>::from_raw(raw as *mut _) };
+    ///     *their_rendezvous.swap() = 3;
+    ///     their_rendezvous.swap();
+    /// });
+    /// my_rendezvous.swap();
+    /// assert_eq!(*my_rendezvous.swap(), 3);
+    /// # handle.join().unwrap();
+    /// ```
+    #[must_use = "the handle is leaked unless recovered with `from_raw`"]
+    #[inline]
+    pub fn into_raw(self) -> *mut c_void {
+        Box::into_raw(Box::new(self)).cast()
+    }
+
+    /// Recover a handle from [`RendezvousDataN::into_raw`].
+    ///
+    /// # Safety
+    /// * `raw` must come from [`RendezvousDataN::into_raw`] of a handle with
+    ///   the same `T` and `SLOTS`.
+    /// * Each pointer must be recovered at most once, the handle it owned is
+    ///   freed when the recovered handle is dropped, and recovering it again
+    ///   is a double free.
+    #[must_use]
+    #[inline]
+    pub unsafe fn from_raw(raw: *mut c_void) -> Self {
+        // SAFETY:
+        // The caller guarantees that `raw` is an unrecovered pointer from
+        // `Box::into_raw` of a handle of this type.
+        *unsafe { Box::from_raw(raw.cast::<Self>()) }
+    }
+}
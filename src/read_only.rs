@@ -0,0 +1,81 @@
+//! Contains [`ReadOnly`]
+
+use core::ops::Deref;
+
+use crate::{RendezvousDataN, SwapPoint, SyncPoint};
+
+/// A handle that only gets shared references to the swapped data, for the
+/// reading side of a pair whose other side writes, created by
+/// [`RendezvousDataN::read_only`].
+///
+/// The writer swaps as usual and gets a mutable reference to its buffer,
+/// [`ReadOnly::read`] swaps and gets a shared reference to the buffer the
+/// writer filled, so the type system rules out writes from the reading side.
+/// The writer gets back the buffer it filled two swaps ago, which the reader
+/// left untouched. Derefs to the handle for the methods that do not swap.
+///
+/// For several readers of one writer, see
+/// [`FanOutPublisher`](crate::FanOutPublisher).
+/// # Example
+/// ```rust
+/// use rendezvous_swap::RendezvousData;
+/// use std::thread;
+///
+/// let (mut writer, reader) = RendezvousData::new(0, 0);
+/// let mut reader = reader.read_only();
+/// let handle = thread::spawn(move || (0..5).map(|_| *reader.read()).sum::<i32>());
+/// for i in 1..=5 {
+///     *writer.swap() = i;
+/// }
+/// // the last value is only read after the next swap
+/// assert_eq!(handle.join().unwrap(), 1 + 2 + 3 + 4);
+/// ```
+#[non_exhaustive]
+pub struct ReadOnly<H> {
+    /// The handle, which is only ever swapped through `read`
+    inner: H,
+}
+impl<H: SwapPoint> ReadOnly<H> {
+    /// Only get shared references to the data swapped by `inner`.
+    #[must_use]
+    #[inline]
+    pub const fn new(inner: H) -> Self {
+        Self { inner }
+    }
+
+    /// Swap with the writer and get a shared reference to the data it wrote.
+    #[inline]
+    pub fn read(&mut self) -> &H::Data {
+        self.inner.swap()
+    }
+
+    /// Get the handle back, with mutable access to the data.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> H {
+        self.inner
+    }
+}
+impl<H> Deref for ReadOnly<H> {
+    type Target = H;
+    #[inline]
+    fn deref(&self) -> &H {
+        &self.inner
+    }
+}
+impl<H: SyncPoint> SyncPoint for ReadOnly<H> {
+    #[inline]
+    fn sync(&mut self) {
+        self.inner.sync();
+    }
+}
+
+impl<T: Send, const SLOTS: usize> RendezvousDataN<T, SLOTS> {
+    /// Only get shared references to the swapped data from now on, see
+    /// [`ReadOnly`].
+    #[must_use]
+    #[inline]
+    pub const fn read_only(self) -> ReadOnly<Self> {
+        ReadOnly::new(self)
+    }
+}
@@ -1,10 +1,32 @@
-//! Contains [`Rendezvous`]
+//! Contains [`Rendezvous`] and [`SubRendezvous`]
 #![forbid(unsafe_code)]
 
+use alloc::boxed::Box;
 use alloc::sync::Arc;
-use core::hint::spin_loop;
+use core::fmt;
+use core::num::NonZeroUsize;
+#[cfg(not(loom))]
 use core::sync::atomic::AtomicUsize;
-use core::sync::atomic::Ordering::{Acquire, Release};
+use core::task::Waker;
+use core::time::Duration;
+
+use crate::clock::Clock;
+#[cfg(feature = "std")]
+use crate::clock::StdClock;
+#[cfg(feature = "std")]
+use crate::contention::{Contention, ContentionStats, SpinBudget};
+use crate::counter::{Counter, CounterPair};
+use crate::last_error::LastError;
+use crate::observer::{Observer, Watch};
+use crate::padded::Padded;
+use crate::pair_id::PairId;
+use crate::probe::{probe, ProbeResult};
+use crate::strict::Op;
+use crate::sync::spin_loop;
+use crate::waker::arrival_waker;
+#[cfg(feature = "stats")]
+use crate::WaitStats;
+use crate::WaitStrategy;
 /// Synchronise execution between threads.
 /// # Example: Sync thread execution
 /// ```rust
@@ -36,13 +58,27 @@ use core::sync::atomic::Ordering::{Acquire, Release};
 /// ```
 #[non_exhaustive]
 pub struct Rendezvous {
-    /// Atomic counter for this thread
-    my_counter: Arc<AtomicUsize>,
-    /// Atomic counter for other thread
-    their_counter: Arc<AtomicUsize>,
+    /// Counters of the first and second handle, in one allocation
+    counters: Arc<CounterPair>,
+    /// True for the first handle of the pair, which uses the first counter
+    first: bool,
     /// Thread local generation
     generation: usize,
+    /// Number of waits to skip between each sync with other thread
+    skip: u32,
+    /// Number of waits skipped since the last sync
+    skipped: u32,
+    /// Set by [`Rendezvous::into_parts`], so that [`Drop`] does not leave
+    /// the pair
+    disarmed: bool,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
 }
+const _: () = assert!(
+    size_of::<Rendezvous>() <= crate::MAX_HANDLE_SIZE,
+    "Rendezvous handle exceeds MAX_HANDLE_SIZE"
+);
 impl Rendezvous {
     /// Synchronize execution with other thread.
     ///
@@ -56,33 +92,1427 @@ impl Rendezvous {
     #[allow(clippy::inline_always)]
     #[inline(always)]
     pub fn wait_inline(&mut self) {
+        if self.skipped < self.skip {
+            self.skipped = self.skipped.wrapping_add(1);
+            return;
+        }
+        self.skipped = 0;
         let next_generation = self.generation.wrapping_add(1);
-        self.my_counter.store(next_generation, Release);
-        while {
-            // Signal to processor (not OS) that we are in a spinloop.
-            // Performance seems to improve by a tiny bit with this.
-            spin_loop();
-            self.their_counter.load(Acquire) == self.generation
-        } {}
+        let (mine, theirs) = (
+            &*self.counters[usize::from(!self.first)],
+            &*self.counters[usize::from(self.first)],
+        );
+        mine.tag(next_generation, Op::Wait);
+        mine.store(next_generation);
+        theirs.wait_past(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+        );
+        theirs.check(next_generation, Op::Wait, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+    }
+
+    /// Call [`Rendezvous::wait`] `n` times, which syncs with the other
+    /// thread `n` times, or only once every `ratio` waits for the first
+    /// handle of [`Rendezvous::new_ratio`].
+    ///
+    /// Skipped waits are counted without looping over them, so the first
+    /// handle of a ratio pair syncs exactly once with `wait_n(ratio)`, and a
+    /// thread that fell behind catches up several generations in one call.
+    /// # Example
+    /// ```rust
+    /// use core::num::NonZeroUsize;
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let ratio = NonZeroUsize::new(4).unwrap();
+    /// let (mut fast, mut slow) = Rendezvous::new_ratio(ratio);
+    /// let handle = thread::spawn(move || {
+    ///     for _ in 0..3 {
+    ///         fast.wait_n(4);
+    ///     }
+    /// });
+    /// slow.wait_n(3);
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn wait_n(&mut self, mut n: usize) {
+        while n > 0 {
+            let pending = self.skip.wrapping_sub(self.skipped) as usize;
+            if n <= pending {
+                // `n` is at most `skip` here, which fits in `u32`.
+                #[allow(clippy::cast_possible_truncation)]
+                let n = n as u32;
+                self.skipped = self.skipped.wrapping_add(n);
+                return;
+            }
+            n -= pending + 1;
+            self.skipped = self.skip;
+            self.wait_inline();
+        }
+    }
+
+    /// [`Rendezvous::wait`], and find out whether this thread leads the
+    /// generation that was just completed, like
+    /// [`Barrier::wait`](https://doc.rust-lang.org/std/sync/struct.Barrier.html#method.wait).
+    ///
+    /// Exactly one handle of the pair leads each generation, the two take
+    /// turns, so once-per-generation work is spread over both threads. Which
+    /// handle leads the first generation is fixed when the pair is created.
+    /// Waits skipped by the first handle of [`Rendezvous::new_ratio`] never
+    /// lead.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || their_rendezvous.wait_leader().is_leader());
+    /// let me = my_rendezvous.wait_leader().is_leader();
+    /// assert_ne!(me, handle.join().unwrap());
+    /// ```
+    #[inline]
+    pub fn wait_leader(&mut self) -> WaitResult {
+        let generation = self.generation;
+        self.wait();
+        WaitResult::new(
+            self.generation != generation && WaitResult::leads(self.first, self.generation),
+        )
+    }
+
+    /// [`Rendezvous::wait`] for fibers (or other user-space threads) that
+    /// are scheduled cooperatively: instead of spinning or yielding to the
+    /// OS, `yield_to` is called until the other side has arrived, and
+    /// should switch to the other fiber (or any other ready fiber).
+    ///
+    /// When both sides run on one OS thread, spinning would never see the
+    /// other side arrive, so the sync must yield to the scheduler. Memory is
+    /// synchronized as with [`Rendezvous::wait`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    ///
+    /// // a scheduler that runs the other fiber to its next sync point
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// my_rendezvous.wait_cooperative(|| their_rendezvous.wait());
+    /// ```
+    #[inline]
+    pub fn wait_cooperative<F: FnMut()>(&mut self, yield_to: F) {
+        if self.skipped < self.skip {
+            self.skipped = self.skipped.wrapping_add(1);
+            return;
+        }
+        self.skipped = 0;
+        let next_generation = self.generation.wrapping_add(1);
+        self.my_counter().tag(next_generation, Op::Wait);
+        self.my_counter().store(next_generation);
+        self.their_counter()
+            .wait_past_with(self.generation, self.my_counter(), yield_to);
+        self.their_counter()
+            .check(next_generation, Op::Wait, self.my_counter());
+        self.my_counter().sample_cpu(next_generation);
+        self.generation = next_generation;
+    }
+
+    /// [`Rendezvous::wait`] that returns instead of spinning forever once
+    /// the other handle is dropped.
+    ///
+    /// Dropping a handle arrives at the next sync point on its behalf, so
+    /// the wait it was dropped before completes, and every wait after that
+    /// fails. A handle dropped while its thread panics does not arrive, and
+    /// the wait it was dropped before fails as well. Yields instead of
+    /// blocking when spinning is counterproductive.
+    ///
+    /// # Errors
+    /// If the other handle has been dropped, see above.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.wait();
+    ///     // dropped here, arriving at the second wait
+    /// });
+    /// my_rendezvous.wait_checked().unwrap();
+    /// my_rendezvous.wait_checked().unwrap();
+    /// assert!(my_rendezvous.wait_checked().is_err());
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn wait_checked(&mut self) -> Result<(), Disconnected> {
+        if self.partner_gone() {
+            return Err(Disconnected);
+        }
+        if self.skipped < self.skip {
+            self.skipped = self.skipped.wrapping_add(1);
+            return Ok(());
+        }
+        self.skipped = 0;
+        let next_generation = self.generation.wrapping_add(1);
+        let (mine, theirs) = (
+            &*self.counters[usize::from(!self.first)],
+            &*self.counters[usize::from(self.first)],
+        );
+        mine.tag(next_generation, Op::Wait);
+        mine.store(next_generation);
+        if !theirs.wait_past_checked(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+        ) {
+            return Err(Disconnected);
+        }
+        theirs.check(next_generation, Op::Wait, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+        Ok(())
+    }
+
+    /// [`Rendezvous::wait`] that returns an error instead of spinning
+    /// forever if the other thread panicked, like the poisoning of a
+    /// [`Mutex`](https://doc.rust-lang.org/std/sync/struct.Mutex.html).
+    ///
+    /// A handle dropped while its thread panics poisons the pair: it does
+    /// not arrive at the next sync point, and from then on this fails
+    /// without waiting. A handle dropped normally does not poison the pair,
+    /// see [`Rendezvous::wait_checked`] for noticing that.
+    ///
+    /// # Errors
+    /// If the other thread panicked before arriving.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.wait();
+    ///     panic!("worker failed");
+    /// });
+    /// my_rendezvous.wait_poisoned().unwrap();
+    /// assert!(my_rendezvous.wait_poisoned().is_err());
+    /// assert!(my_rendezvous.is_poisoned());
+    /// # handle.join().unwrap_err();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn wait_poisoned(&mut self) -> Result<(), Poisoned<()>> {
+        if self.their_counter().hung_up() && self.their_counter().load() == self.generation {
+            return Err(Poisoned::new(()));
+        }
+        if self.skipped < self.skip {
+            self.skipped = self.skipped.wrapping_add(1);
+            return Ok(());
+        }
+        self.skipped = 0;
+        let next_generation = self.generation.wrapping_add(1);
+        let (mine, theirs) = (
+            &*self.counters[usize::from(!self.first)],
+            &*self.counters[usize::from(self.first)],
+        );
+        mine.tag(next_generation, Op::Wait);
+        mine.store(next_generation);
+        if !theirs.wait_past_checked(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+        ) {
+            return Err(Poisoned::new(()));
+        }
+        theirs.check(next_generation, Op::Wait, mine);
+        mine.sample_cpu(next_generation);
         self.generation = next_generation;
+        Ok(())
+    }
+
+    /// True if the other handle was dropped while its thread panicked, see
+    /// [`Rendezvous::wait_poisoned`].
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn is_poisoned(&self) -> bool {
+        self.their_counter().hung_up()
+    }
+
+    /// [`Rendezvous::wait`] if the other thread has already arrived, for a
+    /// loop that polls the sync point while doing other work. Returns true
+    /// if the sync completed.
+    ///
+    /// If the other thread has not arrived, this returns false without
+    /// arriving, so nothing is published that a later wait would have to
+    /// complete. Since only the other thread can complete the sync, it has
+    /// to arrive with a blocking wait (or [`Rendezvous::wait_timeout`],
+    /// ...): if both threads only try, neither ever syncs.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// assert!(!my_rendezvous.try_wait());
+    /// let handle = thread::spawn(move || their_rendezvous.wait());
+    /// while !my_rendezvous.try_wait() {
+    ///     // other work
+    /// }
+    /// # handle.join().unwrap();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn try_wait(&mut self) -> bool {
+        if !self.peer_arrived() {
+            self.my_counter().answer_probe();
+            return false;
+        }
+        self.wait();
+        true
+    }
+
+    /// [`Rendezvous::wait`] that gives up after `timeout`.
+    ///
+    /// On a timeout this thread has still arrived at the sync point, so the
+    /// other thread may pass it, but this thread stays at its generation:
+    /// the next wait (timed or not) completes the same sync instead of
+    /// starting a new one. Yields instead of blocking when spinning is
+    /// counterproductive.
+    ///
+    /// # Errors
+    /// If the other thread has not arrived within `timeout`.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// assert!(my_rendezvous.wait_timeout(Duration::from_millis(1)).is_err());
+    /// let handle = thread::spawn(move || their_rendezvous.wait());
+    /// // retries the same sync
+    /// while my_rendezvous.wait_timeout(Duration::from_millis(1)).is_err() {}
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<(), TimedOut> {
+        self.wait_timeout_with(timeout, &StdClock)
+    }
+
+    /// [`Rendezvous::wait_timeout`], timing with `clock`.
+    ///
+    /// # Errors
+    /// If the other thread has not arrived within `timeout`.
+    #[inline]
+    pub fn wait_timeout_with(
+        &mut self,
+        timeout: Duration,
+        clock: &impl Clock,
+    ) -> Result<(), TimedOut> {
+        if self.skipped < self.skip {
+            self.skipped = self.skipped.wrapping_add(1);
+            return Ok(());
+        }
+        let deadline = clock.now().checked_add(timeout);
+        let next_generation = self.generation.wrapping_add(1);
+        let (mine, theirs) = (
+            &*self.counters[usize::from(!self.first)],
+            &*self.counters[usize::from(self.first)],
+        );
+        mine.tag(next_generation, Op::Wait);
+        mine.store(next_generation);
+        if !theirs.wait_past_until(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+            deadline,
+            clock,
+        ) {
+            return Err(TimedOut);
+        }
+        self.skipped = 0;
+        theirs.check(next_generation, Op::Wait, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+        Ok(())
+    }
+
+    /// [`Rendezvous::wait_timeout`] for the first sync of a pair, which
+    /// confirms that the other thread actually got there, and says why if
+    /// not, instead of spinning forever on a thread that failed to spawn or
+    /// panicked during setup.
+    ///
+    /// Works for any sync, a skipped wait (see [`Rendezvous::new_ratio`])
+    /// succeeds without checking. As with [`Rendezvous::wait_timeout`], a
+    /// wait that fails with [`StartupError::NotArrived`] stays pending and
+    /// can be retried.
+    ///
+    /// # Errors
+    /// See [`StartupError`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::{Rendezvous, StartupError};
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (mut my_rendezvous, their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     let _their_rendezvous = their_rendezvous;
+    ///     // setup fails, dropping the handle
+    /// });
+    /// let result = my_rendezvous.await_partner(Duration::from_secs(10));
+    /// assert_eq!(result, Err(StartupError::Dropped));
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn await_partner(&mut self, timeout: Duration) -> Result<(), StartupError> {
+        self.await_partner_with(timeout, &StdClock)
+    }
+
+    /// [`Rendezvous::await_partner`], timing with `clock`.
+    ///
+    /// # Errors
+    /// See [`StartupError`].
+    #[inline]
+    pub fn await_partner_with(
+        &mut self,
+        timeout: Duration,
+        clock: &impl Clock,
+    ) -> Result<(), StartupError> {
+        let deadline = clock.now().checked_add(timeout);
+        loop {
+            self.check_partner()?;
+            if self
+                .wait_timeout_with(startup_slice(deadline, clock), clock)
+                .is_ok()
+            {
+                return self.check_partner();
+            }
+            if deadline.is_some_and(|deadline| clock.now() >= deadline) {
+                return Err(StartupError::NotArrived);
+            }
+        }
+    }
+
+    /// Fail if the other handle has left, see [`Rendezvous::await_partner`].
+    fn check_partner(&self) -> Result<(), StartupError> {
+        if self.their_counter().hung_up() {
+            Err(StartupError::Panicked)
+        } else if self.partner_gone() {
+            Err(StartupError::Dropped)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Arrive at the next sync point without waiting, see
+    /// [`Rendezvous::wait_async`]. Returns false if this wait is skipped.
+    #[cfg(feature = "async")]
+    pub(crate) fn arrive(&mut self) -> bool {
+        if self.skipped < self.skip {
+            self.skipped = self.skipped.wrapping_add(1);
+            return false;
+        }
+        let next_generation = self.generation.wrapping_add(1);
+        self.my_counter().tag(next_generation, Op::Wait);
+        self.my_counter().store(next_generation);
+        true
+    }
+
+    /// Complete the wait begun by [`Rendezvous::arrive`] if the other thread
+    /// has arrived, otherwise have it wake `waker` when it does.
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_arrived(&mut self, waker: &Waker) -> bool {
+        self.my_counter().answer_probe();
+        if self.their_counter().load() == self.generation {
+            self.their_counter().register_waker(waker);
+            if self.their_counter().load() == self.generation {
+                return false;
+            }
+        }
+        self.their_counter().acquire();
+        self.skipped = 0;
+        let next_generation = self.generation.wrapping_add(1);
+        self.their_counter()
+            .check(next_generation, Op::Wait, self.my_counter());
+        self.my_counter().sample_cpu(next_generation);
+        self.generation = next_generation;
+        true
+    }
+
+    /// Atomic counter for this thread
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn my_counter(&self) -> &Counter {
+        &self.counters[usize::from(!self.first)]
+    }
+
+    /// Atomic counter for other thread
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn their_counter(&self) -> &Counter {
+        &self.counters[usize::from(self.first)]
+    }
+
+    /// Arrive at the next sync point for good, see [`Counter::detach`].
+    pub(crate) fn detach(&mut self) {
+        self.my_counter()
+            .detach(self.generation.wrapping_add(1), Op::Wait);
+    }
+
+    /// True once the other handle has left, and this handle has passed the
+    /// last sync point it arrived at.
+    pub(crate) fn partner_gone(&self) -> bool {
+        self.their_counter().is_gone(self.generation)
+    }
+
+    /// True if [`Rendezvous::wait`] would return without waiting for the
+    /// other thread, because it has already arrived.
+    #[inline]
+    pub(crate) fn peer_arrived(&self) -> bool {
+        self.skipped < self.skip || self.their_counter().load() != self.generation
+    }
+
+    /// Catch up with the other thread after a stall, returns the number of
+    /// generations fast-forwarded.
+    ///
+    /// If the other thread is already waiting at the sync point (this
+    /// thread is lagging), this completes that sync point and returns 1, or,
+    /// for the first handle of [`Rendezvous::new_ratio`], the number of
+    /// calls to [`Rendezvous::wait`] it skipped. If the threads are in
+    /// lockstep this does nothing and returns 0. The other thread can never
+    /// be further ahead than one generation.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || their_rendezvous.wait());
+    /// while my_rendezvous.resync() == 0 {}
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn resync(&mut self) -> usize {
+        if self.their_counter().load() == self.generation {
+            return 0;
+        }
+        // Waits this side still had to do, see `new_ratio`.
+        let lag = self.skip.wrapping_sub(self.skipped) as usize + 1;
+        self.skipped = self.skip;
+        self.wait();
+        lag
+    }
+
+    /// Mark the next sync as urgent, which the other thread can check with
+    /// [`Rendezvous::is_urgent`] as soon as it returns from that sync, for
+    /// example to handle a rare control message before its normal work.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.wait();
+    ///     their_rendezvous.mark_urgent();
+    ///     their_rendezvous.wait();
+    /// });
+    /// my_rendezvous.wait();
+    /// assert!(!my_rendezvous.is_urgent());
+    /// my_rendezvous.wait();
+    /// assert!(my_rendezvous.is_urgent());
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn mark_urgent(&self) {
+        self.my_counter()
+            .mark_urgent(self.generation.wrapping_add(1));
+    }
+
+    /// True if the other thread marked the last sync as urgent with
+    /// [`Rendezvous::mark_urgent`].
+    #[must_use]
+    #[inline]
+    pub fn is_urgent(&self) -> bool {
+        self.generation != 0 && self.their_counter().is_urgent(self.generation)
+    }
+
+    /// Check whether the two threads run on hyperthread siblings of the same
+    /// core, and adapt the wait loop if so.
+    ///
+    /// A spinning thread competes with its sibling for the shared core,
+    /// which can starve the thread doing the actual work. When the threads
+    /// are siblings, this thread spins with a much longer pause between
+    /// checks of the other thread, until a later check finds that they are
+    /// not. Both threads should call this periodically (it reads a few files
+    /// and is not meant for every sync), since threads can migrate between
+    /// cores. Only detects anything on Linux.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.check_smt();
+    ///     their_rendezvous.wait();
+    /// });
+    /// my_rendezvous.check_smt();
+    /// my_rendezvous.wait();
+    /// if my_rendezvous.check_smt() {
+    ///     println!("threads share a core");
+    /// }
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn check_smt(&mut self) -> bool {
+        let mine = crate::os::smt::current_cpu();
+        self.my_counter().set_cpu(mine);
+        let siblings = match (mine, self.their_counter().cpu()) {
+            (Some(mine), Some(theirs)) => crate::os::smt::are_siblings(mine, theirs),
+            _ => false,
+        };
+        self.contention.set_pauses(if siblings {
+            crate::os::smt::SIBLING_PAUSES
+        } else {
+            1
+        });
+        siblings
+    }
+
+    /// Result of the last [`Rendezvous::check_smt`]: true if the threads
+    /// were found to run on hyperthread siblings.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub const fn is_smt_sibling(&self) -> bool {
+        self.contention.has_extra_pauses()
+    }
+
+    /// Get a [`Waker`] that arrives at the next sync point on behalf of this
+    /// thread when woken.
+    ///
+    /// This lets existing async or callback machinery (timers, IO
+    /// completion) act as this side of the rendezvous: the other thread's
+    /// wait completes as soon as the waker is woken, without a thread of
+    /// its own waiting here. This thread then calls [`Rendezvous::wait`] as
+    /// usual, which returns immediately if the waker and the other thread
+    /// both arrived. Waking more than once, or after this thread arrived
+    /// anyway, does nothing, and the waker does nothing for later
+    /// generations.
+    /// # Example: A timer thread arrives for this thread
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || their_rendezvous.wait());
+    /// let waker = my_rendezvous.waker();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(1));
+    ///     waker.wake();
+    /// });
+    /// // the other thread gets past its wait without us
+    /// handle.join().unwrap();
+    /// my_rendezvous.wait();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn waker(&self) -> Waker {
+        arrival_waker(Arc::clone(&self.counters), self.first, self.generation)
+    }
+
+    /// Check whether the other thread is alive, without advancing the
+    /// generation.
+    ///
+    /// The other thread answers probes while it waits at the sync point, and
+    /// whenever it calls [`Rendezvous::answer_probes`], which a thread that is busy
+    /// for a long time should call periodically. Intended for supervisors
+    /// that need to tell "busy but alive" from "dead".
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn probe(&self, timeout: Duration) -> ProbeResult {
+        self.probe_with(timeout, &StdClock)
+    }
+
+    /// [`Rendezvous::probe`], timing out with `clock`. Without `std` the probing
+    /// thread spins instead of yielding while it waits for an answer.
+    #[must_use]
+    #[inline]
+    pub fn probe_with(&self, timeout: Duration, clock: &impl Clock) -> ProbeResult {
+        probe(
+            self.their_counter(),
+            self.my_counter(),
+            self.generation,
+            timeout,
+            clock,
+        )
+    }
+
+    /// Set the tag sent to the other thread with the next
+    /// [`Rendezvous::wait`], an 8 byte side channel for sequence numbers,
+    /// enum discriminants or lengths, next to the counter the other thread
+    /// reads anyway.
+    ///
+    /// A tag goes with one sync only: a sync without a new tag carries the
+    /// tag set two syncs earlier (tags are double-buffered like the
+    /// generations), so set it before every sync if the other thread reads
+    /// it.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.set_tag(42);
+    ///     their_rendezvous.wait();
+    /// });
+    /// my_rendezvous.wait();
+    /// assert_eq!(my_rendezvous.peer_tag(), 42);
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(target_has_atomic = "64")]
+    #[inline]
+    pub fn set_tag(&self, tag: u64) {
+        self.my_counter()
+            .set_tag(self.generation.wrapping_add(1), tag);
+    }
+
+    /// The tag the other thread sent with the last [`Rendezvous::wait`], see
+    /// [`Rendezvous::set_tag`]. 0 before the first sync.
+    #[cfg(target_has_atomic = "64")]
+    #[must_use]
+    #[inline]
+    pub fn peer_tag(&self) -> u64 {
+        self.their_counter().sent_tag(self.generation)
+    }
+
+    /// Number of sync points this handle has passed together with the other
+    /// thread. Waits skipped by the first handle of
+    /// [`Rendezvous::new_ratio`] do not count.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.wait();
+    ///     // arrives at the next sync point, without waiting
+    ///     drop(their_rendezvous);
+    /// });
+    /// my_rendezvous.wait();
+    /// assert_eq!(my_rendezvous.generation(), 1);
+    /// # handle.join().unwrap();
+    /// assert!(my_rendezvous.is_peer_waiting());
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// True if the other thread has arrived at the next sync point, so
+    /// [`Rendezvous::wait`] would not wait for it. Also true once the other
+    /// handle is dropped (see [`Rendezvous::wait_checked`]).
+    ///
+    /// One load of the other thread's counter, which does not synchronize
+    /// memory with it.
+    #[must_use]
+    #[inline]
+    pub fn is_peer_waiting(&self) -> bool {
+        self.their_counter().load() != self.generation
+    }
+
+    /// Answer a pending [`Rendezvous::probe`] from the other thread.
+    ///
+    /// Waiting at the sync point answers probes automatically, call this
+    /// periodically while busy for a long time.
+    #[inline]
+    pub fn answer_probes(&self) {
+        self.my_counter().answer_probe();
+    }
+
+    /// Create a sync point nested within the current generation.
+    ///
+    /// Both threads should call this between the same two calls to
+    /// [`Rendezvous::wait`], and then call [`SubRendezvous::wait`] the same
+    /// number of times, for fine grained phases within a generation.
+    /// The [`SubRendezvous`] borrows this handle, so it cannot escape the
+    /// generation on this thread. For the first handle of
+    /// [`Rendezvous::new_ratio`] a generation spans `ratio` waits, the
+    /// skipped waits do not start a new one.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let handle = thread::spawn(move || {
+    ///     let mut phase = their_rendezvous.sub();
+    ///     phase.wait();
+    ///     phase.wait();
+    ///     their_rendezvous.wait();
+    /// });
+    /// let mut phase = my_rendezvous.sub();
+    /// phase.wait();
+    /// phase.wait();
+    /// my_rendezvous.wait();
+    /// # handle.join().unwrap();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn sub(&mut self) -> SubRendezvous<'_> {
+        SubRendezvous {
+            sub_generation: self.my_counter().load_sub(),
+            parent: self,
+        }
+    }
+
+    /// Number of times this thread was found running on another CPU than
+    /// the last time it checked, sampled every 64 syncs.
+    ///
+    /// Migrations between cores destroy the latency of a pair, a count that
+    /// keeps growing means that the threads should be pinned to cores.
+    #[cfg(feature = "migration-stats")]
+    #[must_use]
+    #[inline]
+    pub fn migrations(&self) -> usize {
+        self.my_counter().migrations()
+    }
+
+    /// [`Rendezvous::migrations`] of the other thread.
+    #[cfg(feature = "migration-stats")]
+    #[must_use]
+    #[inline]
+    pub fn peer_migrations(&self) -> usize {
+        self.their_counter().migrations()
     }
+
+    /// Spins and time of the waits of this thread in [`Rendezvous::wait`], see
+    /// [`WaitStats`]. Timed, checked and cooperative
+    /// variants are not recorded.
+    #[cfg(feature = "stats")]
+    #[must_use]
+    #[inline]
+    pub fn wait_stats(&self) -> WaitStats {
+        self.my_counter().wait_stats()
+    }
+
+    /// [`Rendezvous::wait_stats`] of the other thread.
+    #[cfg(feature = "stats")]
+    #[must_use]
+    #[inline]
+    pub fn peer_wait_stats(&self) -> WaitStats {
+        self.their_counter().wait_stats()
+    }
+
+    /// The most recent protocol-level error either thread of the pair ran
+    /// into, if any.
+    #[must_use]
+    #[inline]
+    pub fn last_error(&self) -> Option<LastError> {
+        LastError::unpack(
+            self.my_counter().last_error(),
+            self.their_counter().last_error(),
+        )
+    }
+
+    /// ID of the pair, shared by both handles.
+    #[must_use]
+    #[inline]
+    pub fn pair_id(&self) -> PairId {
+        self.my_counter().pair()
+    }
+
+    /// Name of the pair given with
+    /// [`RendezvousBuilder::name`](crate::RendezvousBuilder::name), shown in
+    /// the debug output of the handles.
+    #[must_use]
+    #[inline]
+    pub fn name(&self) -> Option<&'static str> {
+        self.my_counter().name()
+    }
+
+    /// True if `a` and `b` are the two handles of one pair.
+    #[must_use]
+    #[inline]
+    pub fn same_pair(a: &Self, b: &Self) -> bool {
+        Arc::ptr_eq(&a.counters, &b.counters) && a.first != b.first
+    }
+
+    /// Addresses of the generation counters of this thread and the other
+    /// thread, in that order, for integrating the pair with external waiting
+    /// machinery (futex, eventfd, eBPF probes, ...).
+    ///
+    /// Each counter holds the number of times its thread has arrived at the
+    /// sync point (wrapping), and is only ever written by that thread with a
+    /// [`Release`](core::sync::atomic::Ordering::Release) store, so a waiter
+    /// can wait for the other counter to change from
+    /// its last value and then load it with
+    /// [`Acquire`](core::sync::atomic::Ordering::Acquire).
+    ///
+    /// # Safety (for users of the pointers)
+    /// * The pointers are valid as long as any handle of the pair is alive.
+    /// * The counters must never be written through these pointers, that
+    ///   breaks the protocol.
+    #[cfg(not(loom))]
+    #[must_use]
+    #[inline]
+    pub fn counter_ptrs(&self) -> (*const AtomicUsize, *const AtomicUsize) {
+        (self.my_counter().as_ptr(), self.their_counter().as_ptr())
+    }
+
+    /// Create an [`Observer`] that a monitoring thread can use to track the
+    /// progress of this pair, without taking part in it.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let observer = my_rendezvous.observer();
+    /// let start = observer.observe();
+    /// let handle = thread::spawn(move || their_rendezvous.wait());
+    /// my_rendezvous.wait();
+    /// # handle.join().unwrap();
+    /// assert_eq!(observer.observe().generations_since(&start), 1);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn observer(&self) -> Observer {
+        Observer::new(Arc::clone(&self.counters))
+    }
+
+    /// Poll for the generations this pair completes, for an observer that
+    /// must not take part in it, see [`Watch`].
+    #[must_use]
+    #[inline]
+    pub fn watch(&self) -> Watch {
+        self.observer().watch()
+    }
+
+    /// Limit how long [`Rendezvous::wait`] spins, see [`SpinBudget`]. `None`
+    /// (the default) spins as long as [`ContentionStats`] allows.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn set_spin_budget(&self, budget: Option<SpinBudget>) {
+        self.my_counter().set_spin_budget(budget);
+    }
+
+    /// The budget set by [`Rendezvous::set_spin_budget`].
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn spin_budget(&self) -> Option<SpinBudget> {
+        self.my_counter().spin_budget()
+    }
+
+    /// Spin without yielding for up to `threshold` in
+    /// [`Rendezvous::wait`], then suspect that the other thread was preempted
+    /// (a vCPU descheduled by the hypervisor, for example) and block until
+    /// it arrives, for this wait only. The next wait spins again, unlike the
+    /// default heuristic, which spins less after every contended wait.
+    /// Suspected preemptions are counted in
+    /// [`ContentionStats::suspected_preemptions`]. `None` (the default)
+    /// disables this, a [`SpinBudget`] takes precedence.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// my_rendezvous.set_preemption_threshold(Some(Duration::from_micros(200)));
+    /// let handle = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(5));
+    ///     their_rendezvous.wait();
+    /// });
+    /// my_rendezvous.wait();
+    /// assert_eq!(my_rendezvous.contention_stats().suspected_preemptions, 1);
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn set_preemption_threshold(&self, threshold: Option<Duration>) {
+        self.my_counter().set_preemption_threshold(threshold);
+    }
+
+    /// The threshold set by [`Rendezvous::set_preemption_threshold`].
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn preemption_threshold(&self) -> Option<Duration> {
+        self.my_counter().preemption_threshold()
+    }
+
+    /// Statistics on how often [`Rendezvous::wait`] gave up spinning and
+    /// yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        let mut stats = self.contention.stats();
+        stats.suspected_preemptions = self.my_counter().preemptions();
+        stats
+    }
+
+    /// Synchronize execution with the other thread after moving this handle
+    /// to another thread, dropping everything learned about the old one.
+    ///
+    /// Call this on the new thread instead of its first
+    /// [`Rendezvous::wait`]. `place` runs first, to reapply pinning (and
+    /// NUMA placement) to the new thread, since the crate does not pin
+    /// threads itself. Then contention statistics and migration counts start
+    /// over, the new CPU is recorded without counting a migration, and
+    /// [`Rendezvous::check_smt`] runs again, before the wait. The other
+    /// thread just waits as usual, and can call [`Rendezvous::check_smt`]
+    /// after this sync to adapt to the new CPU.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let old_worker = thread::spawn(move || {
+    ///     their_rendezvous.wait();
+    ///     their_rendezvous
+    /// });
+    /// my_rendezvous.wait();
+    /// let mut their_rendezvous = old_worker.join().unwrap();
+    /// let new_worker = thread::spawn(move || {
+    ///     their_rendezvous.migrate(|| {
+    ///         // pin this thread, e.g. with the `core_affinity` crate
+    ///     });
+    /// });
+    /// my_rendezvous.wait();
+    /// # new_worker.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn migrate(&mut self, place: impl FnOnce()) {
+        place();
+        self.contention = Contention::new();
+        self.my_counter().rebase_cpu();
+        self.check_smt();
+        self.wait();
+    }
+
+    /// Decompose the handle into its shared state and generation, so that it
+    /// can be stored while the thread using it is torn down, and a new
+    /// thread can resume at the right generation with
+    /// [`Rendezvous::from_parts`].
+    ///
+    /// The other thread is not affected, it simply waits at the next sync
+    /// point until the handle is reassembled and used.
+    /// # Example: Respawn a worker mid-stream
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    /// let worker = thread::spawn(move || {
+    ///     their_rendezvous.wait();
+    ///     their_rendezvous.into_parts()
+    /// });
+    /// my_rendezvous.wait();
+    /// let parts = worker.join().unwrap();
+    /// assert_eq!(parts.generation(), 1);
+    ///
+    /// let worker = thread::spawn(move || Rendezvous::from_parts(parts).wait());
+    /// my_rendezvous.wait();
+    /// # worker.join().unwrap();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn into_parts(mut self) -> RendezvousParts {
+        let parts = RendezvousParts {
+            counters: Arc::clone(&self.counters),
+            first: self.first,
+            generation: self.generation,
+            skip: self.skip,
+            skipped: self.skipped,
+        };
+        // Not a move out of the fields, that is not allowed for a type with
+        // `Drop`, and this module has no unsafe code.
+        self.disarmed = true;
+        parts
+    }
+
+    /// Reassemble a handle from [`Rendezvous::into_parts`].
+    ///
+    /// Contention statistics start over.
+    #[must_use]
+    #[inline]
+    pub fn from_parts(parts: RendezvousParts) -> Self {
+        Self {
+            counters: parts.counters,
+            first: parts.first,
+            generation: parts.generation,
+            skip: parts.skip,
+            skipped: parts.skipped,
+            disarmed: false,
+            #[cfg(feature = "std")]
+            contention: Contention::new(),
+        }
+    }
+
     /// Create a linked pair of [`Rendezvous`]
     #[must_use]
     #[inline]
     pub fn new() -> (Self, Self) {
-        let first = Arc::new(AtomicUsize::new(0));
-        let second = Arc::new(AtomicUsize::new(0));
+        Self::new_ratio(NonZeroUsize::MIN)
+    }
+
+    /// Create a linked pair of [`Rendezvous`] where the first handle calls
+    /// [`Rendezvous::wait`] `ratio` times for every call on the second handle.
+    ///
+    /// Only every `ratio`:th wait on the first handle synchronizes with the
+    /// other thread, the others return immediately (and do not synchronize
+    /// memory). This is useful if one side legitimately performs several
+    /// operations per operation of the other side, for example an audio
+    /// callback that syncs every 4 buffers. The ratio is fixed here so that
+    /// the two sides can never disagree on it.
+    ///
+    /// # Panics
+    /// If `ratio` is larger than `2^32`, which keeps the handle small.
+    /// # Example
+    /// ```rust
+    /// use core::num::NonZeroUsize;
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let ratio = NonZeroUsize::new(4).unwrap();
+    /// let (mut fast, mut slow) = Rendezvous::new_ratio(ratio);
+    /// let handle = thread::spawn(move || {
+    ///     for _ in 0..8 {
+    ///         fast.wait();
+    ///     }
+    /// });
+    /// slow.wait();
+    /// slow.wait();
+    /// # handle.join().unwrap();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn new_ratio(ratio: NonZeroUsize) -> (Self, Self) {
+        let skip = u32::try_from(ratio.get() - 1).expect("ratio larger than 2^32");
+        let pair = PairId::next();
+        Self::from_counters(Counter::for_pair(pair), Counter::for_pair(pair), skip)
+    }
+
+    /// Create a linked pair of [`Rendezvous`] whose waits use `strategy`
+    /// (each handle a clone of it) instead of spinning and yielding, see
+    /// [`WaitStrategy`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::{Rendezvous, SpinBackoff};
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) =
+    ///     Rendezvous::new_with_strategy(SpinBackoff::new(16));
+    /// let handle = thread::spawn(move || their_rendezvous.wait());
+    /// my_rendezvous.wait();
+    /// # handle.join().unwrap();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn new_with_strategy<S: WaitStrategy + Clone + 'static>(strategy: S) -> (Self, Self) {
+        let pair = PairId::next();
+        let (mut first, mut second) = (Counter::for_pair(pair), Counter::for_pair(pair));
+        first.set_strategy(Box::new(strategy.clone()));
+        second.set_strategy(Box::new(strategy));
+        Self::from_counters(first, second, 0)
+    }
+
+    /// Create the pair of handles for the counters `first` and `second`,
+    /// where the first handle skips `skip` waits between syncs.
+    pub(crate) fn from_counters(first: Counter, second: Counter, skip: u32) -> (Self, Self) {
+        let counters = Arc::new([Padded::new(first), Padded::new(second)]);
         (
             Self {
-                my_counter: Arc::clone(&first),
-                their_counter: Arc::clone(&second),
+                counters: Arc::clone(&counters),
+                first: true,
                 generation: 0,
+                skip,
+                skipped: 0,
+                disarmed: false,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
             },
             Self {
-                my_counter: second,
-                their_counter: first,
+                counters,
+                first: false,
                 generation: 0,
+                skip: 0,
+                skipped: 0,
+                disarmed: false,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
             },
         )
     }
 }
+
+impl Drop for Rendezvous {
+    /// Arrive at the next sync point without waiting, and mark the handle as
+    /// gone, see [`Rendezvous::wait_checked`]. While panicking only marks it
+    /// as gone.
+    fn drop(&mut self) {
+        if self.disarmed {
+            return;
+        }
+        #[cfg(feature = "std")]
+        if crate::os::std::thread::panicking() {
+            self.my_counter().hang_up();
+            return;
+        }
+        self.detach();
+    }
+}
+
+impl fmt::Debug for Rendezvous {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rendezvous")
+            .field("name", &self.name())
+            .field("pair", &self.pair_id())
+            .field("first", &self.first)
+            .field("generation", &self.generation)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returned by [`Rendezvous::wait_leader`] and
+/// [`RendezvousDataN::swap_leader`](crate::RendezvousDataN::swap_leader),
+/// like `std::sync::BarrierWaitResult`.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WaitResult {
+    /// See [`WaitResult::is_leader`]
+    is_leader: bool,
+}
+impl WaitResult {
+    /// Result for the leader, or not.
+    pub(crate) const fn new(is_leader: bool) -> Self {
+        Self { is_leader }
+    }
+
+    /// True if the `first` handle of a pair leads `generation`: the first
+    /// handle leads odd generations and the second even ones.
+    pub(crate) const fn leads(first: bool, generation: usize) -> bool {
+        (generation % 2 == 1) == first
+    }
+
+    /// True for exactly one of the two threads of each generation.
+    #[must_use]
+    #[inline]
+    pub const fn is_leader(&self) -> bool {
+        self.is_leader
+    }
+}
+
+/// The other handle of the pair was dropped, so there is nobody left to sync
+/// with, see [`Rendezvous::wait_checked`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Disconnected;
+impl fmt::Display for Disconnected {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the other handle of the pair was dropped")
+    }
+}
+#[cfg(feature = "std")]
+impl crate::os::std::error::Error for Disconnected {}
+
+/// The other thread panicked, and did not arrive at the sync point, see
+/// [`Rendezvous::wait_poisoned`].
+///
+/// Like [`PoisonError`](https://doc.rust-lang.org/std/sync/struct.PoisonError.html),
+/// holds what the sync would have returned (for
+/// [`RendezvousDataN::swap_poisoned`](crate::RendezvousDataN::swap_poisoned),
+/// the buffer the other thread was writing), for callers that want to
+/// inspect or salvage it anyway.
+#[cfg(feature = "std")]
+#[non_exhaustive]
+pub struct Poisoned<G> {
+    /// What the sync would have returned
+    inner: G,
+}
+#[cfg(feature = "std")]
+impl<G> Poisoned<G> {
+    /// Error holding `inner`.
+    pub(crate) const fn new(inner: G) -> Self {
+        Self { inner }
+    }
+
+    /// What the sync would have returned.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    /// Reference to what the sync would have returned.
+    #[must_use]
+    #[inline]
+    pub const fn get_ref(&self) -> &G {
+        &self.inner
+    }
+
+    /// Mutable reference to what the sync would have returned.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut G {
+        &mut self.inner
+    }
+}
+#[cfg(feature = "std")]
+impl<G> fmt::Debug for Poisoned<G> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Poisoned").finish_non_exhaustive()
+    }
+}
+#[cfg(feature = "std")]
+impl<G> fmt::Display for Poisoned<G> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the other thread of the pair panicked")
+    }
+}
+#[cfg(feature = "std")]
+impl<G> crate::os::std::error::Error for Poisoned<G> {}
+
+/// Why the other thread did not reach a sync point, see
+/// [`Rendezvous::await_partner`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StartupError {
+    /// The other thread did not arrive in time: it is still in its setup,
+    /// or its handle was never handed to a running thread
+    NotArrived,
+    /// The other handle was dropped before it arrived, for example because
+    /// spawning its thread failed or the thread returned early
+    Dropped,
+    /// The thread holding the other handle panicked before it arrived
+    /// (only detected with the `std` feature)
+    Panicked,
+}
+impl fmt::Display for StartupError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Self::NotArrived => "the other thread did not arrive in time",
+            Self::Dropped => "the other handle was dropped before it arrived",
+            Self::Panicked => "the other thread panicked before it arrived",
+        })
+    }
+}
+#[cfg(feature = "std")]
+impl crate::os::std::error::Error for StartupError {}
+
+/// How long [`Rendezvous::await_partner`] waits between checks whether the
+/// other handle has left, which a hang up does not interrupt.
+const STARTUP_CHECK_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Timeout of the next wait of [`Rendezvous::await_partner`], which gives
+/// up at `deadline`.
+pub(crate) fn startup_slice(deadline: Option<Duration>, clock: &impl Clock) -> Duration {
+    deadline.map_or(STARTUP_CHECK_INTERVAL, |deadline| {
+        deadline
+            .saturating_sub(clock.now())
+            .min(STARTUP_CHECK_INTERVAL)
+    })
+}
+
+/// The other thread did not arrive in time, see
+/// [`Rendezvous::wait_timeout`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimedOut;
+impl fmt::Display for TimedOut {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the other thread did not arrive in time")
+    }
+}
+#[cfg(feature = "std")]
+impl crate::os::std::error::Error for TimedOut {}
+
+/// A [`Rendezvous`] taken apart with [`Rendezvous::into_parts`].
+#[non_exhaustive]
+pub struct RendezvousParts {
+    /// Counters of the first and second handle
+    counters: Arc<CounterPair>,
+    /// True for the first handle of the pair
+    first: bool,
+    /// Thread local generation
+    generation: usize,
+    /// Number of waits to skip between each sync with other thread
+    skip: u32,
+    /// Number of waits skipped since the last sync
+    skipped: u32,
+}
+impl RendezvousParts {
+    /// Number of times the handle has synced with the other thread
+    /// (wrapping).
+    #[must_use]
+    #[inline]
+    pub const fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+/// Sync point nested within a generation of a [`Rendezvous`], created with
+/// [`Rendezvous::sub`].
+#[non_exhaustive]
+pub struct SubRendezvous<'parent> {
+    /// The parent, which is borrowed for the duration of the generation
+    parent: &'parent mut Rendezvous,
+    /// Thread local sub-barrier generation
+    sub_generation: usize,
+}
+impl SubRendezvous<'_> {
+    /// Synchronize execution with the [`SubRendezvous`] of the other thread.
+    ///
+    /// As a side-effect, memory is also synchronized.
+    ///
+    /// # Panics
+    /// If the other thread has already moved on to the next generation of
+    /// the parent, which means the two threads disagree on the number of
+    /// sub-barrier waits in this generation.
+    #[inline]
+    pub fn wait(&mut self) {
+        let next_sub_generation = self.sub_generation.wrapping_add(1);
+        let parent = &*self.parent;
+        parent.my_counter().store_sub(next_sub_generation);
+        while {
+            spin_loop();
+            parent.their_counter().load_sub() == self.sub_generation
+        } {
+            if parent.their_counter().load() != parent.generation {
+                // Their sub-barrier wait happens before their parent wait.
+                parent.their_counter().acquire();
+                assert!(
+                    parent.their_counter().load_sub() != self.sub_generation,
+                    "sub-barrier misuse: other thread left generation {} with fewer sub-barrier waits",
+                    parent.generation
+                );
+            }
+        }
+        parent.their_counter().acquire();
+        self.sub_generation = next_sub_generation;
+    }
+}
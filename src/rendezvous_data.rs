@@ -1,44 +1,71 @@
-//! Contains [`RendezvousData`]
-
-use cache_padded::CachePadded;
+//! Contains [`RendezvousDataN`] and [`RendezvousData`]
 
+use alloc::boxed::Box;
 use alloc::sync::Arc;
-use core::cell::UnsafeCell;
-use core::hint::spin_loop;
-use core::mem::swap;
+use alloc::task::Wake;
+use core::fmt;
+use core::mem::ManuallyDrop;
 use core::pin::Pin;
-use core::ptr::NonNull;
+use core::ptr;
+#[cfg(not(loom))]
 use core::sync::atomic::AtomicUsize;
-use core::sync::atomic::Ordering::{Acquire, Release};
+use core::task::Waker;
+use core::time::Duration;
+
+#[cfg(feature = "checksum")]
+use crate::checksum::{crc32, ChecksumMismatch};
+use crate::clock::Clock;
+#[cfg(feature = "std")]
+use crate::clock::StdClock;
+#[cfg(feature = "std")]
+use crate::contention::{Contention, ContentionStats, SpinBudget};
+use crate::core_cell::SlotCell;
+use crate::counter::Counter;
+#[cfg(feature = "checksum")]
+use crate::last_error::ErrorKind;
+use crate::last_error::LastError;
+use crate::padded::Padded;
+use crate::pair_id::PairId;
+use crate::probe::{probe, ProbeResult};
+#[cfg(feature = "std")]
+use crate::rendezvous::Poisoned;
+use crate::rendezvous::{startup_slice, Disconnected, StartupError, TimedOut, WaitResult};
+use crate::strict::Op;
+use crate::sync::spin_loop;
+use crate::waker::RendezvousCompleter;
+#[cfg(feature = "stats")]
+use crate::WaitStats;
+use crate::WaitStrategy;
+#[cfg(not(loom))]
+use crate::Zeroable;
 
-/// A pointer to this will be shared for the two [`RendezvousData`]
+/// A pointer to this will be shared for the two [`RendezvousDataN`]
 /// Note that this has no indirection.
-struct RendezvousDataShared<T: Send + Sync> {
-    /// First counter
-    c1: CachePadded<AtomicUsize>,
-    /// Second counter
-    c2: CachePadded<AtomicUsize>,
-    /// First shared data (not a pointer)
-    p1: CachePadded<UnsafeCell<T>>,
-    /// Second shared data (not a pointer)
-    p2: CachePadded<UnsafeCell<T>>,
+struct RendezvousDataShared<T: Send, const SLOTS: usize> {
+    /// Counters of the first and second handle
+    counters: [Padded<Counter>; 2],
+    /// Shared data (not pointers)
+    slots: [Padded<SlotCell<T>>; SLOTS],
 }
-// SAFETY:
-// UnsafeCell needs special consideration
-unsafe impl<T: Send + Sync> Sync for RendezvousDataShared<T> {}
-impl<T: Send + Sync> RendezvousDataShared<T> {
+impl<T: Send, const SLOTS: usize> RendezvousDataShared<T, SLOTS> {
     /// Constructs a new [`RendezvousDataShared`] from the provided data
-    const fn new(data1: T, data2: T) -> Self {
+    fn new(data: [T; SLOTS]) -> Self {
         Self {
-            c1: CachePadded::new(AtomicUsize::new(0)),
-            c2: CachePadded::new(AtomicUsize::new(0)),
-            p1: CachePadded::new(UnsafeCell::new(data1)),
-            p2: CachePadded::new(UnsafeCell::new(data2)),
+            counters: {
+                let pair = PairId::next();
+                [
+                    Padded::new(Counter::for_pair(pair)),
+                    Padded::new(Counter::for_pair(pair)),
+                ]
+            },
+            slots: data.map(|data| Padded::new(SlotCell::new(data))),
         }
     }
 }
 
 /// Synchronise execution and swap data between threads.
+///
+/// This is a [`RendezvousDataN`] with two slots.
 /// # Example: Swap thread data
 /// ```rust
 /// use std::thread;
@@ -79,56 +106,586 @@ impl<T: Send + Sync> RendezvousDataShared<T> {
 ///
 /// # handle.join().unwrap();
 /// ```
+pub type RendezvousData<T> = RendezvousDataN<T, 2>;
+
+/// Synchronise execution and rotate `SLOTS` buffers between threads.
+///
+/// At every swap each thread moves on to the next slot of a ring of `SLOTS`
+/// buffers, the first handle one slot behind the second. With two slots
+/// (see [`RendezvousData`]) the threads swap buffers, with more slots the
+/// remaining buffers are in flight: the data written by the first thread is
+/// read by the second `SLOTS - 1` swaps later, for a pipeline of that depth,
+/// while the second thread's data reaches the first thread at the next swap.
+/// The slot count is a compile time constant, so two slots cost the same as
+/// a dedicated implementation.
+///
+/// Dropping a handle arrives at the next swap on its behalf, so the data it
+/// wrote last is not lost: the other thread's next swap returns it.
+/// # Example: Pipeline of depth 2
+/// ```rust
+/// use rendezvous_swap::RendezvousDataN;
+/// use std::thread;
+///
+/// let (mut producer, mut consumer) = RendezvousDataN::new_n([0, 0, 0]);
+/// let handle = thread::spawn(move || {
+///     consumer.swap();
+///     (0..4).map(|_| *consumer.swap()).collect::<Vec<_>>()
+/// });
+/// for i in 1..=5 {
+///     *producer.swap() = i;
+/// }
+/// assert_eq!(handle.join().unwrap(), [0, 1, 2, 3]);
+/// ```
 #[non_exhaustive]
-pub struct RendezvousData<T: Send + Sync> {
+pub struct RendezvousDataN<T: Send, const SLOTS: usize> {
     /// Thread local generation
     generation: usize,
 
-    /// Atomic counter for this thread
-    my_counter: NonNull<AtomicUsize>,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
 
-    /// Atomic counter for other thread
-    their_counter: NonNull<AtomicUsize>,
+    /// Index of the slot this thread currently owns
+    slot: usize,
+
+    /// True for the first handle of the pair, which uses the first counter
+    first: bool,
+
+    /// Counters and data, the counters and slots are found at fixed offsets
+    /// from this pointer, so the handle does not store pointers to them
+    shared: Pin<Arc<RendezvousDataShared<T, SLOTS>>>,
+}
+/// A [`RendezvousDataN`] taken apart with [`RendezvousDataN::into_parts`].
+#[non_exhaustive]
+pub struct RendezvousDataParts<T: Send, const SLOTS: usize> {
+    /// True for the first handle of the pair
+    first: bool,
+    /// Thread local generation
+    generation: usize,
+    /// Index of the slot the handle owns
+    slot: usize,
+    /// Counters and data
+    shared: Pin<Arc<RendezvousDataShared<T, SLOTS>>>,
+}
+impl<T: Send, const SLOTS: usize> RendezvousDataParts<T, SLOTS> {
+    /// Number of times the handle has swapped with the other thread
+    /// (wrapping).
+    #[must_use]
+    #[inline]
+    pub const fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+/// A swap that a [`RendezvousCompleter`] arrives at, created by
+/// [`RendezvousDataN::arrive_later`].
+#[must_use = "call `finish` to complete the swap"]
+#[non_exhaustive]
+pub struct PendingSwap<'handle, T: Send, const SLOTS: usize> {
+    /// The handle, which cannot be used until the swap is finished
+    handle: &'handle mut RendezvousDataN<T, SLOTS>,
+}
+impl<'handle, T: Send, const SLOTS: usize> PendingSwap<'handle, T, SLOTS> {
+    /// Wait for the [`RendezvousCompleter`] to arrive and for the other
+    /// thread, and get a mutable reference to the swapped data.
+    #[inline]
+    pub fn finish(self) -> &'handle mut T {
+        let handle = self.handle;
+        let mine = &*handle.shared.counters[usize::from(!handle.first)];
+        // Not `wait_past`, the other thread may be parked on this counter.
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        while {
+            spin_loop();
+            mine.load() == handle.generation
+        } {
+            #[cfg(feature = "std")]
+            if handle.contention.spin(&mut spins) {
+                crate::os::yield_now();
+            }
+        }
+        handle.swap()
+    }
+}
 
-    /// A pair of pointers to the underlying data.
-    /// Needs sync to enforce correctness
-    data: (NonNull<UnsafeCell<T>>, NonNull<UnsafeCell<T>>),
+/// Arrival of one handle at one generation, performed by waking, see
+/// [`RendezvousCompleter`].
+struct DataArrival<T: Send, const SLOTS: usize> {
+    /// True for the first handle of the pair
+    first: bool,
+    /// Generation the handle is at before arriving
+    generation: usize,
+    /// Counters and data
+    shared: Pin<Arc<RendezvousDataShared<T, SLOTS>>>,
+}
+impl<T: Send, const SLOTS: usize> Wake for DataArrival<T, SLOTS> {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // Only the completer wakes, once, and it borrows the handle until
+        // then, so the owning thread cannot arrive at the same time.
+        let next_generation = self.generation.wrapping_add(1);
+        let counter = &self.shared.counters[usize::from(!self.first)];
+        counter.tag(next_generation, Op::Swap);
+        counter.store(next_generation);
+    }
+}
+
+impl<T: Send, const SLOTS: usize> Drop for RendezvousDataN<T, SLOTS> {
+    /// Arrive at the next swap without waiting, so that the data written
+    /// since the last swap reaches the other thread: its next swap returns
+    /// that data instead of waiting forever. While panicking, the data is
+    /// likely incomplete, so the handle only hangs up, and the next
+    /// [`RendezvousDataN::swap_checked`] of the other thread fails.
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        if crate::os::std::thread::panicking() {
+            self.my_counter().hang_up();
+            return;
+        }
+        self.detach();
+    }
+}
 
-    /// Let Arc handle dropping shared data so that everything is alive long enough
-    /// TODO: decide on cache stuff
-    _handle: Pin<Arc<RendezvousDataShared<T>>>,
+impl<T: Send, const SLOTS: usize> fmt::Debug for RendezvousDataN<T, SLOTS> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RendezvousDataN")
+            .field("name", &self.name())
+            .field("pair", &self.pair_id())
+            .field("first", &self.first)
+            .field("generation", &self.generation)
+            .field("slot", &self.slot)
+            .finish_non_exhaustive()
+    }
 }
-// SAFETY:
-// The act of sending pointers between threads is not unsafe.
-// UnsafeCell requires special consideration
-unsafe impl<T: Sync + Send> Send for RendezvousData<T> {}
-impl<T: Send + Sync> RendezvousData<T> {
+
+impl<T: Send> RendezvousData<T> {
     /// Create a linked pair of [`RendezvousData`]
     /// Arguments are the initial values for the data that will be swapped.
     #[must_use]
     #[inline]
     pub fn new(data1: T, data2: T) -> (Self, Self) {
-        let a = Arc::pin(RendezvousDataShared::new(data1, data2));
+        Self::new_n([data1, data2])
+    }
+
+    /// Create a linked pair of [`RendezvousData`] whose swaps use `strategy`
+    /// (each handle a clone of it) instead of spinning and yielding, see
+    /// [`WaitStrategy`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::{RendezvousData, SpinBackoff};
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) =
+    ///     RendezvousData::new_with_strategy(0, 0, SpinBackoff::default());
+    /// let handle = thread::spawn(move || {
+    ///     *their_rendezvous.swap() = 3;
+    ///     their_rendezvous.swap();
+    /// });
+    /// my_rendezvous.swap();
+    /// assert_eq!(*my_rendezvous.swap(), 3);
+    /// # handle.join().unwrap();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn new_with_strategy<S: WaitStrategy + Clone + 'static>(
+        data1: T,
+        data2: T,
+        strategy: S,
+    ) -> (Self, Self) {
+        Self::new_configured(data1, data2, |first, second| {
+            first.set_strategy(Box::new(strategy.clone()));
+            second.set_strategy(Box::new(strategy));
+        })
+    }
+
+    /// Create a linked pair of [`RendezvousData`] whose counters (of the
+    /// first and second handle) are set up by `configure` before they are
+    /// shared.
+    pub(crate) fn new_configured<F: FnOnce(&mut Counter, &mut Counter)>(
+        data1: T,
+        data2: T,
+        configure: F,
+    ) -> (Self, Self) {
+        let mut shared = RendezvousDataShared::new([data1, data2]);
+        let [first, second] = &mut shared.counters;
+        configure(first, second);
+        Self::from_shared(Arc::pin(shared))
+    }
+
+    /// Let both threads, one after the other, access both buffers.
+    ///
+    /// The first handle of the pair calls `f` while the second waits, then
+    /// the other way around. `f` gets this thread's data first and the other
+    /// thread's data second.
+    ///
+    /// # Safety
+    /// The other thread must call this at the same point.
+    pub(crate) unsafe fn with_both<F: FnOnce(&mut T, &mut T)>(&mut self, f: F) {
+        let first = self.first;
+        // SAFETY:
+        // The caller guarantees that both threads make three calls to wait
+        // here.
+        // Between the first and second wait only the first handle accesses
+        // the data, and between the second and third only the second does.
+        unsafe {
+            self.wait(Op::Reinit);
+            if !first {
+                self.wait(Op::Reinit);
+            }
+            f(
+                self.shared.slots[self.slot].get_mut(),
+                self.shared.slots[self.slot ^ 1].get_mut(),
+            );
+            if first {
+                self.wait(Op::Reinit);
+            }
+            self.wait(Op::Reinit);
+        }
+    }
+
+    /// Take the data out of the pair once the other handle is gone,
+    /// returning this thread's data first and the other thread's data
+    /// second, or the handle if the other handle is still alive.
+    ///
+    /// Data written by the other thread before dropping its handle is
+    /// included, even if it never swapped it over.
+    /// # Errors
+    /// Returns the handle if the other handle (or a pending
+    /// [`RendezvousCompleter`]) is still alive.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    /// let handle = thread::spawn(move || {
+    ///     *their_rendezvous.swap() = 3; // final result, never swapped
+    /// });
+    /// *my_rendezvous.swap() = 7;
+    /// handle.join().unwrap();
+    /// let Ok((mine, theirs)) = my_rendezvous.try_into_inner() else {
+    ///     panic!("other handle is alive");
+    /// };
+    /// assert_eq!((mine, theirs), (7, 3));
+    /// ```
+    #[inline]
+    pub fn try_into_inner(self) -> Result<(T, T), Self> {
+        let parts = self.into_parts();
+        // SAFETY:
+        // The data is only pinned for `PinnedRendezvousData`, which never
+        // exposes its handle, so the data may be moved out of a plain
+        // `RendezvousData`.
+        let shared = unsafe { Pin::into_inner_unchecked(parts.shared) };
+        match Arc::try_unwrap(shared) {
+            Ok(shared) => {
+                let [first, second] = shared
+                    .slots
+                    .map(|slot| Padded::into_inner(slot).into_inner());
+                Ok(if parts.slot == 0 {
+                    (first, second)
+                } else {
+                    (second, first)
+                })
+            },
+            Err(shared) => Err(Self::from_parts(RendezvousDataParts {
+                // SAFETY:
+                // Same Arc as before.
+                shared: unsafe { Pin::new_unchecked(shared) },
+                ..parts
+            })),
+        }
+    }
+
+    /// Take the data out of the pair, given both handles, returning the data
+    /// of `self` first and the data of `other` second, see
+    /// [`RendezvousData::try_into_inner`].
+    ///
+    /// # Panics
+    /// If `self` and `other` are not the two handles of one pair, or a
+    /// [`RendezvousCompleter`] of the pair is still pending.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    /// let handle = thread::spawn(move || {
+    ///     *their_rendezvous.swap() += 2;
+    ///     their_rendezvous
+    /// });
+    /// *my_rendezvous.swap() += 1;
+    /// let their_rendezvous = handle.join().unwrap();
+    /// assert_eq!(my_rendezvous.into_inner(their_rendezvous), (1, 2));
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self, other: Self) -> (T, T) {
+        assert!(
+            Self::same_pair(&self, &other),
+            "into_inner with handles of different pairs"
+        );
+        drop(other);
+        let Ok(data) = self.try_into_inner() else {
+            panic!("into_inner with a pending RendezvousCompleter");
+        };
+        data
+    }
+
+    /// Swap, but let the first handle of the pair (the leader) access both
+    /// buffers before the exchange completes, for example to merge the
+    /// regions both threads changed in place.
+    ///
+    /// On the leader `f` gets the data it is about to send first and the
+    /// data it is about to receive second, and runs while the other thread
+    /// waits inside this call, so it is not touching either buffer. The `f`
+    /// of the second handle is never called. Costs one extra sync.
+    ///
+    /// # Safety
+    /// The other thread must call this at the same point. The leader's `f`
+    /// runs after a single sync, so if the other thread makes any other call
+    /// there (for example [`RendezvousDataN::swap`]), it gets a mutable
+    /// reference to a buffer `f` is accessing. The `strict` feature panics on
+    /// such a mismatch, but only after the sync.
+    /// # Example: Reconcile before swapping
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![], vec![]);
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.swap().push(2);
+    ///     // SAFETY: both threads call `swap_with_both` here
+    ///     unsafe { their_rendezvous.swap_with_both(|_, _| {}) }.clone()
+    /// });
+    /// my_rendezvous.swap().push(1);
+    /// // SAFETY: both threads call `swap_with_both` here
+    /// let received = unsafe {
+    ///     my_rendezvous.swap_with_both(|outgoing, incoming| {
+    ///         // both sides end up with the merged data
+    ///         outgoing.extend_from_slice(incoming);
+    ///         incoming.clone_from(outgoing);
+    ///     })
+    /// };
+    /// assert_eq!(*received, [1, 2]);
+    /// assert_eq!(handle.join().unwrap(), [1, 2]);
+    /// ```
+    #[inline]
+    pub unsafe fn swap_with_both<F: FnOnce(&mut T, &mut T)>(&mut self, f: F) -> &mut T {
+        // SAFETY:
+        // The caller guarantees that both threads make this extra wait.
+        // Until the wait of the swap below only the leader accesses the
+        // data.
+        unsafe {
+            self.wait(Op::SwapWithBoth);
+            if self.first {
+                f(
+                    self.shared.slots[self.slot].get_mut(),
+                    self.shared.slots[self.slot ^ 1].get_mut(),
+                );
+            }
+        }
+        self.swap()
+    }
+}
+
+impl<T: Send, const SLOTS: usize> RendezvousDataN<T, SLOTS> {
+    /// Create a linked pair of [`RendezvousDataN`]
+    /// Arguments are the initial values for the slots, the first handle
+    /// starts out with the first slot and the second handle with the second.
+    ///
+    /// Fails to compile if `SLOTS` is less than two.
+    #[must_use]
+    #[inline]
+    pub fn new_n(data: [T; SLOTS]) -> (Self, Self) {
+        Self::from_shared(Arc::pin(RendezvousDataShared::new(data)))
+    }
+
+    /// Create a linked pair of [`RendezvousDataN`] with all buffers zeroed
+    /// by the allocator, without writing initial values or moving them
+    /// through the stack, for large "plain old data" buffers.
+    ///
+    /// For large buffers the allocator hands out fresh pages from the OS
+    /// that are only touched (and zeroed) when they are first used, so all
+    /// buffers start out as all zero bytes at practically no cost. For types
+    /// of your own, see [`Zeroable`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    ///
+    /// // 16 MB per buffer, far more than fits on a stack
+    /// let (mut my_rendezvous, their_rendezvous) = RendezvousData::<[f32; 4_000_000]>::new_zeroed();
+    /// # drop(their_rendezvous);
+    /// ```
+    #[cfg(not(loom))]
+    #[must_use]
+    #[inline]
+    pub fn new_zeroed() -> (Self, Self)
+    where
+        T: Zeroable,
+    {
+        let mut shared = Arc::<RendezvousDataShared<T, SLOTS>>::new_zeroed();
+        let uninit = Arc::get_mut(&mut shared)
+            .expect("the allocation is not shared yet")
+            .as_mut_ptr();
+        let pair = PairId::next();
+        // SAFETY:
+        // `uninit` points to the allocation, which only this function
+        // accesses. The counters (which hold a mutex and a waker with
+        // `async`) are not valid when zeroed, so they are written without
+        // reading or dropping the bytes there.
+        unsafe {
+            ptr::addr_of_mut!((*uninit).counters).write([
+                Padded::new(Counter::for_pair(pair)),
+                Padded::new(Counter::for_pair(pair)),
+            ]);
+        }
+        // SAFETY:
+        // The counters were written above, and `Zeroable` guarantees that
+        // the zeroed slots are valid.
+        let shared = unsafe { shared.assume_init() };
+        // SAFETY:
+        // The shared data is never moved out of the Arc, same as `Arc::pin`.
+        Self::from_shared(unsafe { Pin::new_unchecked(shared) })
+    }
 
-        let p1: NonNull<UnsafeCell<T>> = (&*a.p1).into();
-        let p2: NonNull<UnsafeCell<T>> = (&*a.p2).into();
+    /// Create a linked pair of [`RendezvousDataN`] whose buffers are built
+    /// by `init`, one call per buffer, written into the shared allocation.
+    ///
+    /// Unlike [`RendezvousDataN::new_n`], the buffers are not gathered in
+    /// an array first, and with optimizations `init` usually builds its
+    /// value in place, so large buffers do not overflow the stack. Only
+    /// [`RendezvousDataN::new_zeroed`] guarantees that though.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    ///
+    /// let (mut my_rendezvous, their_rendezvous) = RendezvousData::new_with(|| vec![0_u8; 1 << 20]);
+    /// # drop(their_rendezvous);
+    /// ```
+    #[cfg(not(loom))]
+    #[must_use]
+    #[inline]
+    pub fn new_with<F: FnMut() -> T>(mut init: F) -> (Self, Self) {
+        let mut shared = Arc::<RendezvousDataShared<T, SLOTS>>::new_uninit();
+        let uninit = Arc::get_mut(&mut shared)
+            .expect("the allocation is not shared yet")
+            .as_mut_ptr();
+        let pair = PairId::next();
+        // SAFETY:
+        // `uninit` points to the allocation, which only this function
+        // accesses. The slot pointers stay within the `slots` array, and
+        // point to the `T` of each slot, as `Padded` (`repr(C)`), `SlotCell`
+        // and `UnsafeCell` (`repr(transparent)`) put their value at offset
+        // 0. If `init` panics, the allocation is freed and the values
+        // written so far are leaked.
+        unsafe {
+            ptr::addr_of_mut!((*uninit).counters).write([
+                Padded::new(Counter::for_pair(pair)),
+                Padded::new(Counter::for_pair(pair)),
+            ]);
+            let slots = ptr::addr_of_mut!((*uninit).slots).cast::<Padded<SlotCell<T>>>();
+            for slot in 0..SLOTS {
+                slots.add(slot).cast::<T>().write(init());
+            }
+        }
+        // SAFETY:
+        // The counters and every slot were written above.
+        let shared = unsafe { shared.assume_init() };
+        // SAFETY:
+        // The shared data is never moved out of the Arc, same as `Arc::pin`.
+        Self::from_shared(unsafe { Pin::new_unchecked(shared) })
+    }
+
+    /// Create the pair of handles for `a`.
+    fn from_shared(a: Pin<Arc<RendezvousDataShared<T, SLOTS>>>) -> (Self, Self) {
+        const { assert!(SLOTS >= 2, "RendezvousDataN needs at least two slots") };
+        const {
+            assert!(
+                size_of::<Self>() <= crate::MAX_HANDLE_SIZE,
+                "RendezvousDataN handle exceeds MAX_HANDLE_SIZE"
+            );
+        };
         (
             Self {
                 generation: 0,
-                my_counter: (&*a.c1).into(),
-                their_counter: (&*a.c2).into(),
-                data: (p1, p2),
-                _handle: a.clone(),
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                slot: 0,
+                first: true,
+                shared: a.clone(),
             },
             Self {
                 generation: 0,
-                my_counter: (&*a.c2).into(),
-                their_counter: (&*a.c1).into(),
-                data: (p2, p1),
-                _handle: a.clone(),
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                slot: 1,
+                first: false,
+                shared: a,
             },
         )
     }
+    /// Decompose the handle into its shared state, generation and slot, so
+    /// that it can be stored while the thread using it is torn down, and a
+    /// new thread can resume at the right generation with
+    /// [`RendezvousDataN::from_parts`].
+    ///
+    /// The other thread is not affected, it simply waits at the next swap
+    /// until the handle is reassembled and used. Data stays in the shared
+    /// buffers meanwhile.
+    /// # Example: Respawn a worker mid-stream
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    /// let worker = thread::spawn(move || {
+    ///     *their_rendezvous.swap() = 3;
+    ///     their_rendezvous.into_parts()
+    /// });
+    /// my_rendezvous.swap();
+    /// let parts = worker.join().unwrap();
+    ///
+    /// let worker = thread::spawn(move || {
+    ///     RendezvousData::from_parts(parts).swap();
+    /// });
+    /// assert_eq!(*my_rendezvous.swap(), 3);
+    /// # worker.join().unwrap();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn into_parts(self) -> RendezvousDataParts<T, SLOTS> {
+        // Not dropped, that would arrive at the next swap.
+        let this = ManuallyDrop::new(self);
+        RendezvousDataParts {
+            first: this.first,
+            generation: this.generation,
+            slot: this.slot,
+            // SAFETY:
+            // `this` is never dropped, so the Arc is moved, not duplicated.
+            shared: unsafe { ptr::read(&this.shared) },
+        }
+    }
+
+    /// Reassemble a handle from [`RendezvousDataN::into_parts`].
+    ///
+    /// Contention statistics start over.
+    #[must_use]
+    #[inline]
+    pub fn from_parts(parts: RendezvousDataParts<T, SLOTS>) -> Self {
+        Self {
+            generation: parts.generation,
+            #[cfg(feature = "std")]
+            contention: Contention::new(),
+            slot: parts.slot,
+            first: parts.first,
+            shared: parts.shared,
+        }
+    }
+
     /// Swap data with other thread and get a mutable reference to the data.
     #[allow(clippy::needless_lifetimes)] // lifetime needs to be restricted here
     #[inline]
@@ -136,23 +693,945 @@ impl<T: Send + Sync> RendezvousData<T> {
         self.swap_inline()
     }
 
-    /// Always inlined version of [`RendezvousData::swap`]
+    /// Always inlined version of [`RendezvousDataN::swap`]
     #[allow(clippy::needless_lifetimes)] // lifetime needs to be restricted here
     #[allow(clippy::inline_always)]
     #[inline(always)]
     pub fn swap_inline<'lock>(&'lock mut self) -> &'lock mut T {
         // SAFETY:
         // Number of swaps must stay the same between threads
-        unsafe { self.wait() };
+        unsafe { self.wait(Op::Swap) };
+        self.next_slot()
+    }
+
+    /// [`RendezvousDataN::swap`], then call `f` with the received data and
+    /// return its result.
+    ///
+    /// The reference to the data does not outlive the call, so a stale
+    /// reference cannot be held across later control flow, which is easier
+    /// to work with in generic code than the borrow of the handle returned
+    /// by [`RendezvousDataN::swap`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![1, 2], vec![3]);
+    /// let handle = thread::spawn(move || their_rendezvous.swap_with(|data| data.len()));
+    /// let sum: i32 = my_rendezvous.swap_with(|data| data.iter().sum());
+    /// assert_eq!((sum, handle.join().unwrap()), (3, 2));
+    /// ```
+    #[doc(alias = "map_swap")]
+    #[inline]
+    pub fn swap_with<R, F: FnOnce(&mut T) -> R>(&mut self, f: F) -> R {
+        f(self.swap())
+    }
 
-        // Swap the **pointers** to the underlying data.
-        swap(&mut self.data.0, &mut self.data.1);
+    /// [`RendezvousDataN::swap`], and find out whether this thread leads the
+    /// swap, see [`Rendezvous::wait_leader`](crate::Rendezvous::wait_leader).
+    /// The first handle leads the first swap.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    /// let handle = thread::spawn(move || {
+    ///     for frame in 1..=4 {
+    ///         let (data, result) = their_rendezvous.swap_leader();
+    ///         if result.is_leader() {
+    ///             println!("frame {frame}");
+    ///         }
+    ///         *data = frame;
+    ///     }
+    /// });
+    /// for _ in 1..=4 {
+    ///     let (_, result) = my_rendezvous.swap_leader();
+    ///     if result.is_leader() {
+    ///         println!("frame {}", my_rendezvous.generation());
+    ///     }
+    /// }
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn swap_leader(&mut self) -> (&mut T, WaitResult) {
+        let result = WaitResult::new(WaitResult::leads(
+            self.first,
+            self.generation.wrapping_add(1),
+        ));
+        (self.swap(), result)
+    }
+
+    /// [`RendezvousDataN::swap`] if the other thread has already arrived,
+    /// see [`Rendezvous::try_wait`](crate::Rendezvous::try_wait). Returns
+    /// `None`, without handing the data over, if it has not.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    /// assert!(my_rendezvous.try_swap().is_none());
+    /// let handle = thread::spawn(move || {
+    ///     *their_rendezvous.swap() = 3;
+    ///     their_rendezvous.swap();
+    /// });
+    /// while my_rendezvous.try_swap().is_none() {
+    ///     // other work
+    /// }
+    /// assert_eq!(*my_rendezvous.swap(), 3);
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn try_swap(&mut self) -> Option<&mut T> {
+        if self.their_counter().load() == self.generation {
+            self.my_counter().answer_probe();
+            return None;
+        }
+        Some(self.swap())
+    }
+
+    /// [`RendezvousDataN::swap`] that sends a CRC-32 of the data along with
+    /// it, and verifies the data received against the checksum the other
+    /// thread sent.
+    ///
+    /// Both threads must use this for the swap. Within one address space a
+    /// mismatch means that something wrote to a buffer it did not own, or
+    /// that memory was corrupted. Costs one pass over the outgoing and the
+    /// incoming data.
+    /// # Errors
+    /// If the received data does not match its checksum, which is also
+    /// recorded as [`ErrorKind::ChecksumMismatch`](crate::ErrorKind::ChecksumMismatch)
+    /// (see [`RendezvousDataN::last_error`]). The swap has completed, the
+    /// corrupt data is not handed out.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![0_u8; 64], vec![0; 64]);
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.checked_swap().unwrap().fill(7);
+    ///     their_rendezvous.checked_swap().unwrap();
+    /// });
+    /// my_rendezvous.checked_swap().unwrap();
+    /// assert_eq!(my_rendezvous.checked_swap().unwrap()[0], 7);
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(feature = "checksum")]
+    #[inline]
+    pub fn checked_swap(&mut self) -> Result<&mut T, ChecksumMismatch>
+    where
+        T: AsRef<[u8]>,
+    {
+        let next_generation = self.generation.wrapping_add(1);
+        let sent = crc32(self.current_mut().as_ref());
+        self.my_counter().set_checksum(next_generation, sent);
+        // SAFETY:
+        // Number of swaps must stay the same between threads
+        unsafe { self.wait(Op::CheckedSwap) };
+        let expected = self.their_counter().checksum(self.generation);
+        let actual = crc32(self.next_slot().as_ref());
+        if actual != expected {
+            self.my_counter()
+                .record_error(ErrorKind::ChecksumMismatch, self.generation);
+            return Err(ChecksumMismatch {
+                generation: self.generation,
+                expected,
+                actual,
+            });
+        }
+        Ok(self.current_mut())
+    }
+
+    /// Run `generations` iterations of swapping and then calling `f` with the
+    /// received data, followed by one final swap that hands the data written
+    /// in the last iteration over, and return what the other thread wrote in
+    /// its last iteration.
+    ///
+    /// Both threads must call this with the same `generations`, both then
+    /// leave after the same number of swaps, so the loop can end without
+    /// extra `swap` calls to drain the data.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    /// let handle = thread::spawn(move || *their_rendezvous.for_generations(10, |x| *x += 1));
+    /// let last = *my_rendezvous.for_generations(10, |x| *x += 1);
+    /// // every swap hands the value over, and both threads increment it
+    /// assert_eq!((last, handle.join().unwrap()), (10, 10));
+    /// ```
+    #[inline]
+    pub fn for_generations<F: FnMut(&mut T)>(&mut self, generations: usize, mut f: F) -> &mut T {
+        for _ in 0..generations {
+            f(self.swap());
+        }
+        self.swap()
+    }
+
+    /// [`RendezvousDataN::swap`] for fibers that are scheduled cooperatively,
+    /// see [`Rendezvous::wait_cooperative`](crate::Rendezvous::wait_cooperative).
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(1, 2);
+    /// let data = my_rendezvous.swap_cooperative(|| *their_rendezvous.swap() += 1);
+    /// assert_eq!(*data, 2);
+    /// ```
+    #[inline]
+    pub fn swap_cooperative<F: FnMut()>(&mut self, yield_to: F) -> &mut T {
+        let next_generation = self.generation.wrapping_add(1);
+        let (mine, theirs) = (
+            &*self.shared.counters[usize::from(!self.first)],
+            &*self.shared.counters[usize::from(self.first)],
+        );
+        mine.tag(next_generation, Op::Swap);
+        mine.store(next_generation);
+        theirs.wait_past_with(self.generation, mine, yield_to);
+        theirs.check(next_generation, Op::Swap, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+        self.next_slot()
+    }
+
+    /// [`RendezvousDataN::swap`] that returns instead of spinning forever
+    /// once the other handle is dropped, see
+    /// [`Rendezvous::wait_checked`](crate::Rendezvous::wait_checked).
+    ///
+    /// The swap the other handle was dropped before returns the data it
+    /// wrote, unless its thread was panicking.
+    ///
+    /// # Errors
+    /// If the other handle has been dropped, and the swap returning the data
+    /// it handed over has completed, or it was dropped while panicking.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    /// let handle = thread::spawn(move || {
+    ///     *their_rendezvous.swap() = 5; // handed over on drop
+    /// });
+    /// my_rendezvous.swap_checked().unwrap();
+    /// assert_eq!(my_rendezvous.swap_checked(), Ok(&mut 5));
+    /// assert!(my_rendezvous.swap_checked().is_err());
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn swap_checked(&mut self) -> Result<&mut T, Disconnected> {
+        if self.partner_gone() {
+            return Err(Disconnected);
+        }
+        let next_generation = self.generation.wrapping_add(1);
+        let (mine, theirs) = (
+            &*self.shared.counters[usize::from(!self.first)],
+            &*self.shared.counters[usize::from(self.first)],
+        );
+        mine.tag(next_generation, Op::Swap);
+        mine.store(next_generation);
+        if !theirs.wait_past_checked(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+        ) {
+            return Err(Disconnected);
+        }
+        theirs.check(next_generation, Op::Swap, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+        Ok(self.next_slot())
+    }
+
+    /// [`RendezvousDataN::swap`] that returns an error instead of spinning
+    /// forever if the other thread panicked, see
+    /// [`Rendezvous::wait_poisoned`](crate::Rendezvous::wait_poisoned).
+    ///
+    /// The error holds the buffer the other thread was writing when it
+    /// panicked, likely half written, which [`Poisoned::into_inner`]
+    /// salvages. Every later call returns the same buffer.
+    ///
+    /// # Errors
+    /// If the other thread panicked before arriving.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new([0; 2], [0; 2]);
+    /// let handle = thread::spawn(move || {
+    ///     let data = their_rendezvous.swap();
+    ///     data[0] = 1;
+    ///     panic!("failed before writing data[1]");
+    /// });
+    /// my_rendezvous.swap_poisoned().unwrap();
+    /// let error = my_rendezvous.swap_poisoned().unwrap_err();
+    /// assert_eq!(*error.into_inner(), [1, 0]);
+    /// # handle.join().unwrap_err();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn swap_poisoned(&mut self) -> Result<&mut T, Poisoned<&mut T>> {
+        let next_generation = self.generation.wrapping_add(1);
+        let (mine, theirs) = (
+            &*self.shared.counters[usize::from(!self.first)],
+            &*self.shared.counters[usize::from(self.first)],
+        );
+        if theirs.hung_up() && theirs.load() == self.generation {
+            return Err(Poisoned::new(self.poisoned_slot()));
+        }
+        mine.tag(next_generation, Op::Swap);
+        mine.store(next_generation);
+        if !theirs.wait_past_checked(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+        ) {
+            return Err(Poisoned::new(self.poisoned_slot()));
+        }
+        theirs.check(next_generation, Op::Swap, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+        Ok(self.next_slot())
+    }
+
+    /// True if the other handle was dropped while its thread panicked, see
+    /// [`RendezvousDataN::swap_poisoned`].
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn is_poisoned(&self) -> bool {
+        self.their_counter().hung_up()
+    }
+
+    /// The slot the other thread was writing when it panicked, without
+    /// moving on to it.
+    #[cfg(feature = "std")]
+    fn poisoned_slot(&mut self) -> &mut T {
+        // The second handle is one slot ahead of the first.
+        let slot = if self.first {
+            (self.slot + 1) % SLOTS
+        } else {
+            (self.slot + SLOTS - 1) % SLOTS
+        };
+        // SAFETY:
+        // The other thread holds the slot after this one until it arrives at
+        // the next swap. It hung up instead (observed with acquire ordering),
+        // so its handle, and every reference to the slot, is gone.
+        unsafe { self.shared.slots[slot].get_mut() }
+    }
+
+    /// [`RendezvousDataN::swap`] that gives up after `timeout`, see
+    /// [`Rendezvous::wait_timeout`](crate::Rendezvous::wait_timeout).
+    ///
+    /// On a timeout the data has still been handed over, and the swap is
+    /// pending: the next swap (timed or not) completes it instead of
+    /// starting a new one, and methods that access the data without
+    /// swapping panic until then.
+    ///
+    /// # Errors
+    /// If the other thread has not arrived within `timeout`.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(1, 2);
+    /// assert!(my_rendezvous.swap_timeout(Duration::from_millis(1)).is_err());
+    /// let handle = thread::spawn(move || *their_rendezvous.swap());
+    /// assert_eq!(*my_rendezvous.swap(), 2);
+    /// assert_eq!(handle.join().unwrap(), 1);
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn swap_timeout(&mut self, timeout: Duration) -> Result<&mut T, TimedOut> {
+        self.swap_timeout_with(timeout, &StdClock)
+    }
+
+    /// [`RendezvousDataN::swap_timeout`], timing with `clock`.
+    ///
+    /// # Errors
+    /// If the other thread has not arrived within `timeout`.
+    #[inline]
+    pub fn swap_timeout_with(
+        &mut self,
+        timeout: Duration,
+        clock: &impl Clock,
+    ) -> Result<&mut T, TimedOut> {
+        let deadline = clock.now().checked_add(timeout);
+        let next_generation = self.generation.wrapping_add(1);
+        let (mine, theirs) = (
+            &*self.shared.counters[usize::from(!self.first)],
+            &*self.shared.counters[usize::from(self.first)],
+        );
+        mine.tag(next_generation, Op::Swap);
+        mine.store(next_generation);
+        if !theirs.wait_past_until(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+            deadline,
+            clock,
+        ) {
+            return Err(TimedOut);
+        }
+        theirs.check(next_generation, Op::Swap, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+        Ok(self.next_slot())
+    }
+
+    /// [`RendezvousDataN::swap`] for the first swap of a pair, which
+    /// confirms that the other thread actually got there, see
+    /// [`Rendezvous::await_partner`](crate::Rendezvous::await_partner).
+    ///
+    /// # Errors
+    /// See [`StartupError`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(1, 2);
+    /// let handle = thread::spawn(move || *their_rendezvous.swap());
+    /// assert_eq!(my_rendezvous.await_partner(Duration::from_secs(10)), Ok(&mut 2));
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn await_partner(&mut self, timeout: Duration) -> Result<&mut T, StartupError> {
+        self.await_partner_with(timeout, &StdClock)
+    }
+
+    /// [`RendezvousDataN::await_partner`], timing with `clock`.
+    ///
+    /// # Errors
+    /// See [`StartupError`].
+    #[inline]
+    pub fn await_partner_with(
+        &mut self,
+        timeout: Duration,
+        clock: &impl Clock,
+    ) -> Result<&mut T, StartupError> {
+        let deadline = clock.now().checked_add(timeout);
+        loop {
+            self.check_partner()?;
+            if self
+                .swap_timeout_with(startup_slice(deadline, clock), clock)
+                .is_ok()
+            {
+                self.check_partner()?;
+                return Ok(self.current_mut());
+            }
+            if deadline.is_some_and(|deadline| clock.now() >= deadline) {
+                return Err(StartupError::NotArrived);
+            }
+        }
+    }
+
+    /// Fail if the other handle has left, see
+    /// [`RendezvousDataN::await_partner`].
+    fn check_partner(&self) -> Result<(), StartupError> {
+        if self.their_counter().hung_up() {
+            Err(StartupError::Panicked)
+        } else if self.partner_gone() {
+            Err(StartupError::Dropped)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// True after a [`RendezvousDataN::swap_timeout`] timed out, until the
+    /// swap is completed: the data of this thread has been handed over.
+    fn is_pending(&self) -> bool {
+        // Only this thread stores to its counter, between swaps it holds
+        // the generation.
+        self.my_counter().load() != self.generation
+    }
+
+    /// Hand the data over without waiting, see
+    /// [`RendezvousDataN::swap_async`].
+    #[cfg(feature = "async")]
+    pub(crate) fn arrive(&mut self) {
+        let next_generation = self.generation.wrapping_add(1);
+        let mine = self.my_counter();
+        mine.tag(next_generation, Op::Swap);
+        mine.store(next_generation);
+    }
+
+    /// Complete the swap begun by [`RendezvousDataN::arrive`] if the other
+    /// thread has arrived, otherwise have it wake `waker` when it does. Get
+    /// the data with [`RendezvousDataN::next_slot`] once this returns true.
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_arrived(&mut self, waker: &Waker) -> bool {
+        let (mine, theirs) = (self.my_counter(), self.their_counter());
+        mine.answer_probe();
+        if theirs.load() == self.generation {
+            theirs.register_waker(waker);
+            if theirs.load() == self.generation {
+                return false;
+            }
+        }
+        theirs.acquire();
+        let next_generation = self.generation.wrapping_add(1);
+        theirs.check(next_generation, Op::Swap, mine);
+        mine.sample_cpu(next_generation);
+        self.generation = next_generation;
+        true
+    }
+
+    /// Arrive at the next swap for good, handing the current data over, see
+    /// [`Counter::detach`].
+    pub(crate) fn detach(&mut self) {
+        self.my_counter()
+            .detach(self.generation.wrapping_add(1), Op::Swap);
+    }
+
+    /// True once the other handle has left, and this handle has received the
+    /// data it handed over on leaving.
+    pub(crate) fn partner_gone(&self) -> bool {
+        self.their_counter().is_gone(self.generation)
+    }
+
+    /// Move on to the next slot after a swap, and get its data.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn next_slot(&mut self) -> &mut T {
+        // Move on to the next slot, only the **pointer** changes.
+        self.slot = self.slot.wrapping_add(1);
+        if self.slot == SLOTS {
+            self.slot = 0;
+        }
 
         // SAFETY:
         // we know that the mutable reference in the other thread
         // is destroyed after calling wait(), and we can therefore create
         // a new mutable reference to that data without causing UB
-        unsafe { &mut *(self.data.0.as_ref()).get() }
+        unsafe { self.shared.slots[self.slot].get_mut() }
+    }
+
+    /// Get a mutable reference to the data returned by the last swap, without
+    /// swapping.
+    #[inline]
+    pub(crate) fn current_mut(&mut self) -> &mut T {
+        assert!(
+            !self.is_pending(),
+            "the data was handed over by a swap that timed out, swap first"
+        );
+        // SAFETY:
+        // The two handles are never at the same slot, see `from_shared`
+        // and `swap_inline`.
+        unsafe { self.shared.slots[self.slot].get_mut() }
+    }
+
+    /// Catch up with the other thread after a stall, returns the number of
+    /// generations fast-forwarded.
+    ///
+    /// If the other thread is already waiting at the sync point (this
+    /// thread is lagging), this completes that swap, and `stale` decides what
+    /// to do with the received data, which was produced for a generation
+    /// this thread missed (keep it, clear it, ...). If the threads are in
+    /// lockstep this does nothing and returns 0. The other thread can never
+    /// be further ahead than one generation.
+    /// # Example: Take data from a missed generation
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![], vec![]);
+    /// let handle = thread::spawn(move || {
+    ///     their_rendezvous.swap().push(1);
+    ///     their_rendezvous.swap();
+    /// });
+    /// my_rendezvous.swap();
+    /// // ... stall ...
+    /// let mut missed = Vec::new();
+    /// while my_rendezvous.resync(|data| missed.append(data)) == 0 {}
+    /// assert_eq!(missed, [1]);
+    /// # handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn resync<F: FnOnce(&mut T)>(&mut self, stale: F) -> usize {
+        let their_generation = self.their_counter().load();
+        let lag = their_generation.wrapping_sub(self.generation);
+        if lag == 0 {
+            return 0;
+        }
+        stale(self.swap());
+        lag
+    }
+
+    /// Mark the next swap as urgent, which the other thread can check with
+    /// [`RendezvousDataN::is_urgent`] as soon as it returns from that swap,
+    /// for example to handle a control message embedded in the data before
+    /// its normal work.
+    #[inline]
+    pub fn mark_urgent(&self) {
+        self.my_counter()
+            .mark_urgent(self.generation.wrapping_add(1));
+    }
+
+    /// True if the other thread marked the last swap as urgent with
+    /// [`RendezvousDataN::mark_urgent`].
+    #[must_use]
+    #[inline]
+    pub fn is_urgent(&self) -> bool {
+        self.generation != 0 && self.their_counter().is_urgent(self.generation)
+    }
+
+    /// Decouple arriving at the next swap from the thread that owns the
+    /// handle.
+    ///
+    /// The [`RendezvousCompleter`] can be handed to a callback (signal
+    /// handler, audio callback, GUI event), which marks this side's arrival
+    /// by completing it, so that the other thread can continue. The owner
+    /// later calls [`PendingSwap::finish`] to wait for the arrival and the
+    /// other thread, and collect the swapped data. Data written before this
+    /// call is what the other thread receives.
+    ///
+    /// The handle stays borrowed until both the completer and the
+    /// [`PendingSwap`] are gone. If the [`PendingSwap`] is dropped without
+    /// finishing, the next swap completes the swap the completer arrived at.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(5, 7);
+    /// let handle = thread::spawn(move || *their_rendezvous.swap());
+    ///
+    /// thread::scope(|scope| {
+    ///     let (completer, pending) = my_rendezvous.arrive_later();
+    ///     scope.spawn(move || completer.complete()); // some event
+    ///     assert_eq!(*pending.finish(), 7);
+    /// });
+    /// // the other thread swapped without this thread
+    /// assert_eq!(handle.join().unwrap(), 5);
+    /// ```
+    #[inline]
+    pub fn arrive_later(&mut self) -> (RendezvousCompleter<'_>, PendingSwap<'_, T, SLOTS>)
+    where
+        T: 'static,
+    {
+        let waker = Waker::from(Arc::new(DataArrival {
+            first: self.first,
+            generation: self.generation,
+            shared: self.shared.clone(),
+        }));
+        (
+            RendezvousCompleter::new(waker),
+            PendingSwap { handle: self },
+        )
+    }
+
+    /// Number of times this thread was found running on another CPU than
+    /// the last time it checked, sampled every 64 syncs.
+    ///
+    /// Migrations between cores destroy the latency of a pair, a count that
+    /// keeps growing means that the threads should be pinned to cores.
+    #[cfg(feature = "migration-stats")]
+    #[must_use]
+    #[inline]
+    pub fn migrations(&self) -> usize {
+        self.my_counter().migrations()
+    }
+
+    /// [`RendezvousDataN::migrations`] of the other thread.
+    #[cfg(feature = "migration-stats")]
+    #[must_use]
+    #[inline]
+    pub fn peer_migrations(&self) -> usize {
+        self.their_counter().migrations()
+    }
+
+    /// Spins and time of the waits of this thread in [`RendezvousDataN::swap`], see
+    /// [`WaitStats`]. Timed, checked and cooperative
+    /// variants are not recorded.
+    #[cfg(feature = "stats")]
+    #[must_use]
+    #[inline]
+    pub fn wait_stats(&self) -> WaitStats {
+        self.my_counter().wait_stats()
+    }
+
+    /// [`RendezvousDataN::wait_stats`] of the other thread.
+    #[cfg(feature = "stats")]
+    #[must_use]
+    #[inline]
+    pub fn peer_wait_stats(&self) -> WaitStats {
+        self.their_counter().wait_stats()
+    }
+
+    /// The most recent protocol-level error either thread of the pair ran
+    /// into, if any.
+    #[must_use]
+    #[inline]
+    pub fn last_error(&self) -> Option<LastError> {
+        LastError::unpack(
+            self.my_counter().last_error(),
+            self.their_counter().last_error(),
+        )
+    }
+
+    /// ID of the pair, shared by both handles.
+    #[must_use]
+    #[inline]
+    pub fn pair_id(&self) -> PairId {
+        self.my_counter().pair()
+    }
+
+    /// Name of the pair given with
+    /// [`RendezvousDataBuilder::name`](crate::RendezvousDataBuilder::name),
+    /// shown in the debug output of the handles.
+    #[must_use]
+    #[inline]
+    pub fn name(&self) -> Option<&'static str> {
+        self.my_counter().name()
+    }
+
+    /// True if `a` and `b` are the two handles of one pair.
+    #[must_use]
+    #[inline]
+    pub fn same_pair(a: &Self, b: &Self) -> bool {
+        ptr::eq(&*a.shared, &*b.shared) && a.first != b.first
+    }
+
+    /// Addresses of the generation counters of this thread and the other
+    /// thread, in that order, for integrating the pair with external waiting
+    /// machinery (futex, eventfd, eBPF probes, ...).
+    ///
+    /// Each counter holds the number of times its thread has arrived at the
+    /// sync point (wrapping), and is only ever written by that thread with a
+    /// [`Release`](core::sync::atomic::Ordering::Release) store, so a waiter
+    /// can wait for the other counter to change from
+    /// its last value and then load it with
+    /// [`Acquire`](core::sync::atomic::Ordering::Acquire).
+    ///
+    /// # Safety (for users of the pointers)
+    /// * The pointers are valid as long as any handle of the pair is alive.
+    /// * The counters must never be written through these pointers, that
+    ///   breaks the protocol and causes aliasing mutable references.
+    #[cfg(not(loom))]
+    #[must_use]
+    #[inline]
+    pub fn counter_ptrs(&self) -> (*const AtomicUsize, *const AtomicUsize) {
+        (self.my_counter().as_ptr(), self.their_counter().as_ptr())
+    }
+
+    /// Check whether the other thread is alive, without advancing the
+    /// generation.
+    ///
+    /// The other thread answers probes while it waits at the sync point, and
+    /// whenever it calls [`RendezvousDataN::answer_probes`], which a thread that is busy
+    /// for a long time should call periodically. Intended for supervisors
+    /// that need to tell "busy but alive" from "dead".
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn probe(&self, timeout: Duration) -> ProbeResult {
+        self.probe_with(timeout, &StdClock)
+    }
+
+    /// [`RendezvousDataN::probe`], timing out with `clock`. Without `std` the probing
+    /// thread spins instead of yielding while it waits for an answer.
+    #[must_use]
+    #[inline]
+    pub fn probe_with(&self, timeout: Duration, clock: &impl Clock) -> ProbeResult {
+        probe(
+            self.their_counter(),
+            self.my_counter(),
+            self.generation,
+            timeout,
+            clock,
+        )
+    }
+
+    /// Set the tag sent to the other thread with the next
+    /// [`RendezvousDataN::swap`], an 8 byte side channel for sequence numbers,
+    /// enum discriminants or lengths, next to the counter the other thread
+    /// reads anyway.
+    ///
+    /// A tag goes with one sync only: a sync without a new tag carries the
+    /// tag set two syncs earlier (tags are double-buffered like the
+    /// generations), so set it before every sync if the other thread reads
+    /// it.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new([0_u8; 64], [0; 64]);
+    /// let handle = thread::spawn(move || {
+    ///     let frame = their_rendezvous.swap();
+    ///     frame[..5].copy_from_slice(b"hello");
+    ///     their_rendezvous.set_tag(5); // length of the frame
+    ///     their_rendezvous.swap();
+    /// });
+    /// my_rendezvous.swap();
+    /// let frame = *my_rendezvous.swap();
+    /// let len = my_rendezvous.peer_tag() as usize;
+    /// assert_eq!(&frame[..len], b"hello");
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(target_has_atomic = "64")]
+    #[inline]
+    pub fn set_tag(&self, tag: u64) {
+        self.my_counter()
+            .set_tag(self.generation.wrapping_add(1), tag);
+    }
+
+    /// The tag the other thread sent with the last [`RendezvousDataN::swap`], see
+    /// [`RendezvousDataN::set_tag`]. 0 before the first sync.
+    #[cfg(target_has_atomic = "64")]
+    #[must_use]
+    #[inline]
+    pub fn peer_tag(&self) -> u64 {
+        self.their_counter().sent_tag(self.generation)
+    }
+
+    /// Number of swaps this handle has completed, see
+    /// [`Rendezvous::generation`](crate::Rendezvous::generation).
+    #[must_use]
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// True if the other thread has arrived at the next swap, so
+    /// [`RendezvousDataN::swap`] would not wait for it, see
+    /// [`Rendezvous::is_peer_waiting`](crate::Rendezvous::is_peer_waiting).
+    #[must_use]
+    #[inline]
+    pub fn is_peer_waiting(&self) -> bool {
+        self.their_counter().load() != self.generation
+    }
+
+    /// Answer a pending [`RendezvousDataN::probe`] from the other thread.
+    ///
+    /// Waiting at the sync point answers probes automatically, call this
+    /// periodically while busy for a long time.
+    #[inline]
+    pub fn answer_probes(&self) {
+        self.my_counter().answer_probe();
+    }
+
+    /// Limit how long [`RendezvousDataN::swap`] spins, see [`SpinBudget`]. `None`
+    /// (the default) spins as long as [`ContentionStats`] allows.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn set_spin_budget(&self, budget: Option<SpinBudget>) {
+        self.my_counter().set_spin_budget(budget);
+    }
+
+    /// The budget set by [`RendezvousDataN::set_spin_budget`].
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn spin_budget(&self) -> Option<SpinBudget> {
+        self.my_counter().spin_budget()
+    }
+
+    /// Spin without yielding for up to `threshold` in
+    /// [`RendezvousDataN::swap`], then suspect that the other thread was preempted
+    /// (a vCPU descheduled by the hypervisor, for example) and block until
+    /// it arrives, for this wait only. The next wait spins again, unlike the
+    /// default heuristic, which spins less after every contended wait.
+    /// Suspected preemptions are counted in
+    /// [`ContentionStats::suspected_preemptions`]. `None` (the default)
+    /// disables this, a [`SpinBudget`] takes precedence.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    /// my_rendezvous.set_preemption_threshold(Some(Duration::from_micros(200)));
+    /// let handle = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(5));
+    ///     their_rendezvous.swap();
+    /// });
+    /// my_rendezvous.swap();
+    /// assert_eq!(my_rendezvous.contention_stats().suspected_preemptions, 1);
+    /// # handle.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn set_preemption_threshold(&self, threshold: Option<Duration>) {
+        self.my_counter().set_preemption_threshold(threshold);
+    }
+
+    /// The threshold set by [`RendezvousDataN::set_preemption_threshold`].
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn preemption_threshold(&self) -> Option<Duration> {
+        self.my_counter().preemption_threshold()
+    }
+
+    /// Statistics on how often [`RendezvousDataN::swap`] gave up spinning and
+    /// yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        let mut stats = self.contention.stats();
+        stats.suspected_preemptions = self.my_counter().preemptions();
+        stats
+    }
+
+    /// Swap data with the other thread after moving this handle to another
+    /// thread, see [`Rendezvous::migrate`](crate::Rendezvous::migrate).
+    ///
+    /// `place` also gets the data this thread is about to hand over, to
+    /// refresh its placement on the new thread's NUMA node (by touching or
+    /// reallocating it) where that matters.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![0; 4], vec![0; 4]);
+    /// let old_worker = thread::spawn(move || {
+    ///     their_rendezvous.swap();
+    ///     their_rendezvous
+    /// });
+    /// my_rendezvous.swap();
+    /// let mut their_rendezvous = old_worker.join().unwrap();
+    /// let new_worker = thread::spawn(move || {
+    ///     // reallocate on the NUMA node of this thread
+    ///     their_rendezvous.migrate(|data| *data = data.clone());
+    /// });
+    /// my_rendezvous.swap();
+    /// # new_worker.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn migrate(&mut self, place: impl FnOnce(&mut T)) -> &mut T {
+        place(self.current_mut());
+        self.contention = Contention::new();
+        self.my_counter().rebase_cpu();
+        self.swap()
+    }
+
+    /// Atomic counter for this thread
+    #[inline(always)]
+    fn my_counter(&self) -> &Counter {
+        &self.shared.counters[usize::from(!self.first)]
+    }
+
+    /// Atomic counter for other thread
+    #[inline(always)]
+    fn their_counter(&self) -> &Counter {
+        &self.shared.counters[usize::from(self.first)]
     }
 
     /// Synchronize execution with other thread.
@@ -163,21 +1642,23 @@ impl<T: Send + Sync> RendezvousData<T> {
     /// memory is created
     #[allow(clippy::inline_always)]
     #[inline(always)]
-    unsafe fn wait(&mut self) {
+    unsafe fn wait(&mut self, op: Op) {
         let next_generation = self.generation.wrapping_add(1);
 
-        // SAFETY:
-        // Pointer is valid as long as the Arc is not dropped
-        unsafe { self.my_counter.as_ref() }.store(next_generation, Release);
-        while {
-            // Signal to processor (not OS) that we are in a spinloop.
-            // Performance seems to improve by a tiny bit with this.
-            spin_loop();
-
-            // SAFETY:
-            // Pointer is valid as long as the Arc is not dropped
-            unsafe { self.their_counter.as_ref() }.load(Acquire) == self.generation
-        } {}
+        let (mine, theirs) = (
+            &*self.shared.counters[usize::from(!self.first)],
+            &*self.shared.counters[usize::from(self.first)],
+        );
+        mine.tag(next_generation, op);
+        mine.store(next_generation);
+        theirs.wait_past(
+            self.generation,
+            mine,
+            #[cfg(feature = "std")]
+            &mut self.contention,
+        );
+        theirs.check(next_generation, op, mine);
+        mine.sample_cpu(next_generation);
         self.generation = next_generation;
     }
 }
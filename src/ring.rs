@@ -0,0 +1,202 @@
+//! Contains [`RendezvousRing`]
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::hint::spin_loop;
+
+#[cfg(feature = "std")]
+use crate::contention::{Contention, ContentionStats};
+use crate::core_cell::SlotCell;
+use crate::counter::Counter;
+use crate::padded::Padded;
+use crate::{SwapPoint, SyncPoint};
+
+/// Shared between the stages of a [`RendezvousRing`].
+struct RingShared<T: Send> {
+    /// One counter per stage
+    counters: Box<[Padded<Counter>]>,
+    /// One buffer per stage, stage `i` owns buffer `(i - g) % n` in
+    /// generation `g`
+    buffers: Box<[Padded<SlotCell<T>>]>,
+}
+
+/// One stage of a pipeline whose buffers rotate around a ring of stages, the
+/// generalization of [`RendezvousData`](crate::RendezvousData) to more than
+/// two threads.
+///
+/// Every stage owns one buffer at a time. At each
+/// [`RendezvousRing::advance`] it passes its buffer to the next stage and
+/// gets the buffer of the previous stage, and the last stage passes its
+/// buffer back to the first, so a buffer filled by the first stage (capture)
+/// visits every stage in order (transform, output) and then returns to be
+/// refilled. Like with a pair, the buffers are only pointers that change
+/// hands, nothing is copied.
+///
+/// Every stage publishes its generation in its own padded counter and only
+/// waits for its two neighbors, so adjacent stages move in lockstep, never
+/// more than one generation apart, and there are no read-modify-write
+/// atomics. A ring of two stages is a [`RendezvousData`](crate::RendezvousData).
+/// # Example: Three stage pipeline
+/// ```rust
+/// use rendezvous_swap::RendezvousRing;
+/// use std::thread;
+///
+/// let mut stages = RendezvousRing::new(3, || 0).into_iter();
+/// let (mut capture, mut transform, mut output) =
+///     (stages.next().unwrap(), stages.next().unwrap(), stages.next().unwrap());
+/// let transform = thread::spawn(move || {
+///     for _ in 0..5 {
+///         *transform.advance() *= 10;
+///     }
+/// });
+/// let output = thread::spawn(move || {
+///     (0..5).map(|_| *output.advance()).collect::<Vec<_>>()
+/// });
+/// for frame in 1..=5 {
+///     *capture.advance() = frame;
+/// }
+/// # transform.join().unwrap();
+/// // the output gets each frame two generations after it was captured
+/// assert_eq!(output.join().unwrap(), [0, 0, 10, 20, 30]);
+/// ```
+#[non_exhaustive]
+pub struct RendezvousRing<T: Send> {
+    /// Index of this stage in the ring
+    index: usize,
+    /// Index of the buffer this stage owns
+    slot: usize,
+    /// Thread local generation
+    generation: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// Shared counters and buffers
+    shared: Arc<RingShared<T>>,
+}
+impl<T: Send> RendezvousRing<T> {
+    /// Create a ring of `n` linked stages, in pipeline order, where each
+    /// stage starts out with a buffer returned by `init`.
+    #[must_use]
+    #[inline]
+    pub fn new<F: FnMut() -> T>(n: usize, mut init: F) -> Vec<Self> {
+        let shared = Arc::new(RingShared {
+            counters: (0..n).map(|_| Padded::new(Counter::new())).collect(),
+            buffers: (0..n).map(|_| Padded::new(SlotCell::new(init()))).collect(),
+        });
+        (0..n)
+            .map(|index| Self {
+                index,
+                slot: index,
+                generation: 0,
+                #[cfg(feature = "std")]
+                contention: Contention::new(),
+                shared: Arc::clone(&shared),
+            })
+            .collect()
+    }
+
+    /// Pass the buffer of this stage to the next stage, wait for both
+    /// neighbors, and get a mutable reference to the buffer of the previous
+    /// stage.
+    #[inline]
+    pub fn advance(&mut self) -> &mut T {
+        let len = self.len();
+        if len > 1 {
+            let next_generation = self.generation.wrapping_add(1);
+            let shared = &*self.shared;
+            let previous = (self.index + len - 1) % len;
+            let next = (self.index + 1) % len;
+            shared.counters[self.index].store(next_generation);
+            // The previous stage releases the buffer this stage takes, and the
+            // next stage is waited for to stay in lockstep. Neighbors are at
+            // this generation, the next, or the one after that.
+            #[cfg(feature = "std")]
+            let mut spins = 0;
+            for theirs in [previous, next] {
+                let theirs = &shared.counters[theirs];
+                while {
+                    spin_loop();
+                    theirs.load() == self.generation
+                } {
+                    // Not `block`, with the `parking` feature only one thread
+                    // may park on a counter.
+                    #[cfg(feature = "std")]
+                    if self.contention.spin(&mut spins) {
+                        crate::os::yield_now();
+                    }
+                }
+                theirs.acquire();
+            }
+            #[cfg(feature = "std")]
+            self.contention.finish(spins);
+            self.generation = next_generation;
+            self.slot = (self.slot + len - 1) % len;
+        }
+        self.current()
+    }
+
+    /// Get a mutable reference to the buffer this stage owns, without
+    /// advancing.
+    #[must_use]
+    #[inline]
+    pub fn current(&mut self) -> &mut T {
+        // SAFETY:
+        // A buffer moves from one stage to the next only when the stage
+        // holding it advances, and the next stage takes it only after that,
+        // so exactly one stage owns it. That stage's handle is borrowed
+        // mutably for as long as the reference lives.
+        unsafe { self.shared.buffers[self.slot].get_mut() }
+    }
+
+    /// Number of stages in the ring.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shared.counters.len()
+    }
+
+    /// True if the ring has no stages, which is never the case for a handle.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shared.counters.is_empty()
+    }
+
+    /// Index of this stage in the ring, from 0 to `len() - 1`, in the order
+    /// [`RendezvousRing::new`] returned the handles.
+    #[must_use]
+    #[inline]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Number of times this stage has advanced (wrapping).
+    #[must_use]
+    #[inline]
+    pub const fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Statistics on how often [`RendezvousRing::advance`] gave up spinning
+    /// and yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+}
+impl<T: Send> SyncPoint for RendezvousRing<T> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.advance();
+    }
+}
+impl<T: Send> SwapPoint for RendezvousRing<T> {
+    type Data = T;
+    #[inline]
+    fn swap(&mut self) -> &mut T {
+        self.advance()
+    }
+}
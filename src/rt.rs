@@ -0,0 +1,258 @@
+//! Contains [`spawn_pair`], [`PairThreads`], [`Priority`] and [`PriorityError`]
+//!
+//! A real-time thread paired with a normal priority thread gains nothing:
+//! every sync waits for the partner, which the scheduler preempts in favor
+//! of the real-time thread, in the worst case on the same core. Spawning
+//! both sides through [`spawn_pair`] gives them the same scheduling policy
+//! and priority, and checks that the OS actually applied it before either
+//! side starts.
+
+use crate::os::std::io;
+use crate::os::std::sync::mpsc;
+use crate::os::std::thread::{self, JoinHandle};
+use core::fmt;
+
+/// Scheduling policy of a [`Priority`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// The default time sharing policy (`SCHED_OTHER`)
+    Normal,
+    /// Real-time, runs until it blocks or yields (`SCHED_FIFO`)
+    Fifo,
+    /// Real-time, time sliced among threads of equal priority (`SCHED_RR`)
+    RoundRobin,
+}
+impl Policy {
+    /// The `libc` constant.
+    const fn raw(self) -> libc::c_int {
+        match self {
+            Self::Normal => libc::SCHED_OTHER,
+            Self::Fifo => libc::SCHED_FIFO,
+            Self::RoundRobin => libc::SCHED_RR,
+        }
+    }
+}
+
+/// Scheduling policy and priority requested for both threads of a pair.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Priority {
+    /// Scheduling policy
+    pub policy: Policy,
+    /// Static priority within the policy, 0 for [`Policy::Normal`], and
+    /// usually 1 to 99 for the real-time policies
+    pub level: i32,
+}
+impl Priority {
+    /// The default policy, for pairs that deliberately stay off real-time
+    /// scheduling.
+    #[must_use]
+    #[inline]
+    pub const fn normal() -> Self {
+        Self {
+            policy: Policy::Normal,
+            level: 0,
+        }
+    }
+
+    /// `SCHED_FIFO` at `level`.
+    #[must_use]
+    #[inline]
+    pub const fn fifo(level: i32) -> Self {
+        Self {
+            policy: Policy::Fifo,
+            level,
+        }
+    }
+
+    /// `SCHED_RR` at `level`.
+    #[must_use]
+    #[inline]
+    pub const fn round_robin(level: i32) -> Self {
+        Self {
+            policy: Policy::RoundRobin,
+            level,
+        }
+    }
+
+    /// Apply to the calling thread, and read the settings back.
+    fn apply(self) -> Result<(), PriorityError> {
+        // SAFETY:
+        // `sched_param` is plain data, for which zero is a valid value.
+        let mut param: libc::sched_param = unsafe { core::mem::zeroed() };
+        param.sched_priority = self.level;
+        // SAFETY:
+        // `pthread_self` is always a valid thread, and `param` is
+        // initialized.
+        let error =
+            unsafe { libc::pthread_setschedparam(libc::pthread_self(), self.policy.raw(), &param) };
+        if error != 0 {
+            return Err(PriorityError::Rejected(io::Error::from_raw_os_error(error)));
+        }
+        let mut policy = 0;
+        // SAFETY:
+        // As above, both out pointers are valid.
+        let error =
+            unsafe { libc::pthread_getschedparam(libc::pthread_self(), &mut policy, &mut param) };
+        if error != 0 {
+            return Err(PriorityError::Rejected(io::Error::from_raw_os_error(error)));
+        }
+        if policy != self.policy.raw() || param.sched_priority != self.level {
+            return Err(PriorityError::NotApplied {
+                policy,
+                level: param.sched_priority,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The scheduling settings of a [`spawn_pair`] could not be applied.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum PriorityError {
+    /// The OS refused the settings, typically `EPERM` for real-time
+    /// policies without `CAP_SYS_NICE` or an `RLIMIT_RTPRIO` allowing the
+    /// level, or `EINVAL` for a level outside the range of the policy
+    Rejected(io::Error),
+    /// The OS accepted the settings, but the thread runs with these
+    /// (raw `libc`) ones
+    NotApplied {
+        /// Policy the thread runs with
+        policy: i32,
+        /// Priority the thread runs with
+        level: i32,
+    },
+}
+impl fmt::Display for PriorityError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Rejected(ref error) => write!(f, "scheduling settings rejected: {error}"),
+            Self::NotApplied { policy, level } => write!(
+                f,
+                "scheduling settings not applied, running with policy {policy} at priority {level}"
+            ),
+        }
+    }
+}
+impl crate::os::std::error::Error for PriorityError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn crate::os::std::error::Error + 'static)> {
+        match *self {
+            Self::Rejected(ref error) => Some(error),
+            Self::NotApplied { .. } => None,
+        }
+    }
+}
+
+/// The two threads started by [`spawn_pair`].
+#[non_exhaustive]
+pub struct PairThreads<RA, RB> {
+    /// Runs the first closure
+    first: JoinHandle<Option<RA>>,
+    /// Runs the second closure
+    second: JoinHandle<Option<RB>>,
+}
+impl<RA, RB> PairThreads<RA, RB> {
+    /// Wait for both threads to finish, the first one first, and get the
+    /// results of the closures.
+    ///
+    /// # Errors
+    /// If a thread panicked, with its panic payload.
+    #[inline]
+    pub fn join(self) -> thread::Result<(RA, RB)> {
+        let first = self.first.join();
+        let second = self.second.join();
+        Ok((first?.expect("closure ran"), second?.expect("closure ran")))
+    }
+}
+
+/// Spawn the two threads of a pair, running `first` and `second`, both with
+/// `priority`.
+///
+/// Each thread applies the settings to itself and reads them back, and
+/// neither closure runs unless both succeeded, so a pair never runs with
+/// mismatched priorities. The closures take the handles of the pair by
+/// move.
+///
+/// # Errors
+/// If the settings could not be applied to a thread, the error of the first
+/// one that failed. Both threads have exited and the closures have been
+/// dropped without running.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::rt::{spawn_pair, Priority, PriorityError};
+/// use rendezvous_swap::Rendezvous;
+///
+/// let (mut first, mut second) = Rendezvous::new();
+/// match spawn_pair(Priority::fifo(10), move || first.wait(), move || second.wait()) {
+///     Ok(threads) => {
+///         threads.join().unwrap();
+///     }
+///     // no permission to use real-time scheduling
+///     Err(PriorityError::Rejected(error)) => println!("{error}"),
+///     Err(error) => panic!("{error}"),
+/// }
+/// ```
+#[inline]
+pub fn spawn_pair<A, B, RA, RB>(
+    priority: Priority,
+    first: A,
+    second: B,
+) -> Result<PairThreads<RA, RB>, PriorityError>
+where
+    A: FnOnce() -> RA + Send + 'static,
+    B: FnOnce() -> RB + Send + 'static,
+    RA: Send + 'static,
+    RB: Send + 'static,
+{
+    let (first_applied, first_thread, first_go) = spawn_prioritized(priority, first);
+    let (second_applied, second_thread, second_go) = spawn_prioritized(priority, second);
+    let applied = first_applied
+        .recv()
+        .expect("thread reports before exiting")
+        .and(
+            second_applied
+                .recv()
+                .expect("thread reports before exiting"),
+        );
+    let go = applied.is_ok();
+    // Both threads wait for this, so the sends succeed.
+    let _ = first_go.send(go);
+    let _ = second_go.send(go);
+    let threads = PairThreads {
+        first: first_thread,
+        second: second_thread,
+    };
+    if let Err(error) = applied {
+        // Neither closure ran, so neither thread panicked.
+        let _ = threads.first.join();
+        let _ = threads.second.join();
+        return Err(error);
+    }
+    Ok(threads)
+}
+
+/// Receives whether applying the settings succeeded.
+type Applied = mpsc::Receiver<Result<(), PriorityError>>;
+
+/// Spawn a thread that applies `priority`, reports the outcome, and runs `f`
+/// once told to go.
+fn spawn_prioritized<F, R>(
+    priority: Priority,
+    f: F,
+) -> (Applied, JoinHandle<Option<R>>, mpsc::Sender<bool>)
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let (report, applied) = mpsc::channel();
+    let (go, go_receiver) = mpsc::channel();
+    let thread = thread::spawn(move || {
+        let _ = report.send(priority.apply());
+        go_receiver.recv().unwrap_or(false).then(f)
+    });
+    (applied, thread, go)
+}
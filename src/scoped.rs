@@ -0,0 +1,270 @@
+//! Contains [`ScopedRendezvous`] and [`ScopedRendezvousData`]
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::os::std::panic::resume_unwind;
+use crate::os::std::thread::{self, Scope, ScopedJoinHandle};
+use crate::{Rendezvous, RendezvousData};
+
+/// The other handle of a scoped pair was dropped, so there is nobody left to
+/// sync with.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Detached;
+impl fmt::Display for Detached {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the other handle of the pair was dropped")
+    }
+}
+impl crate::os::std::error::Error for Detached {}
+
+/// Ties a handle to a scope: `'scope` is invariant, so the handle can be
+/// moved into threads spawned on the scope, but never out of it.
+type ScopeMarker<'scope> = PhantomData<&'scope mut &'scope ()>;
+
+/// A [`Rendezvous`] that cannot outlive the [`std::thread::scope`](https://doc.rust-lang.org/std/thread/fn.scope.html) it was
+/// created in, created by [`Rendezvous::scoped`].
+///
+/// Dropping a handle arrives at the next sync point on its behalf and marks
+/// it as gone, so the other thread is never left spinning: its next wait
+/// completes, and every wait after that returns [`Detached`]. Since the
+/// scope joins all of its threads, both handles are dropped, and both
+/// threads done with the pair, when it exits. Leaking a handle (with
+/// [`core::mem::forget`]) defeats this.
+#[non_exhaustive]
+pub struct ScopedRendezvous<'scope> {
+    /// The handle
+    inner: Rendezvous,
+    /// Ties the handle to the scope
+    scope: ScopeMarker<'scope>,
+}
+impl ScopedRendezvous<'_> {
+    /// Synchronize execution with the other thread, see
+    /// [`Rendezvous::wait`].
+    ///
+    /// # Errors
+    /// If the other handle has been dropped, and the wait it arrived at when
+    /// dropped has completed.
+    #[inline]
+    pub fn wait(&mut self) -> Result<(), Detached> {
+        if self.inner.partner_gone() {
+            return Err(Detached);
+        }
+        self.inner.wait();
+        Ok(())
+    }
+}
+impl Drop for ScopedRendezvous<'_> {
+    fn drop(&mut self) {
+        self.inner.detach();
+    }
+}
+
+/// A [`RendezvousData`] that cannot outlive the [`std::thread::scope`](https://doc.rust-lang.org/std/thread/fn.scope.html) it was
+/// created in, created by [`RendezvousData::scoped`].
+///
+/// Dropping a handle hands the data written since the last swap over, see
+/// [`ScopedRendezvous`].
+#[non_exhaustive]
+pub struct ScopedRendezvousData<'scope, T: Send> {
+    /// The handle
+    inner: RendezvousData<T>,
+    /// Ties the handle to the scope
+    scope: ScopeMarker<'scope>,
+}
+impl<T: Send> ScopedRendezvousData<'_, T> {
+    /// Swap data with the other thread, see [`RendezvousData::swap`].
+    ///
+    /// # Errors
+    /// If the other handle has been dropped, and the swap returning the data
+    /// it handed over when dropped has completed.
+    #[inline]
+    pub fn swap(&mut self) -> Result<&mut T, Detached> {
+        if self.inner.partner_gone() {
+            return Err(Detached);
+        }
+        Ok(self.inner.swap())
+    }
+}
+impl<T: Send> Drop for ScopedRendezvousData<'_, T> {
+    fn drop(&mut self) {
+        // Also while panicking, unlike the unscoped handle, the scope would
+        // never exit otherwise.
+        self.inner.detach();
+    }
+}
+
+impl Rendezvous {
+    /// Create a linked pair of [`ScopedRendezvous`] that cannot outlive
+    /// `scope`, and never leave a thread of the scope spinning.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    /// use std::thread;
+    ///
+    /// thread::scope(|scope| {
+    ///     let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::scoped(scope);
+    ///     scope.spawn(move || {
+    ///         for _ in 0..3 {
+    ///             their_rendezvous.wait().unwrap();
+    ///         }
+    ///         // dropped here, one wait early
+    ///     });
+    ///     for _ in 0..4 {
+    ///         my_rendezvous.wait().unwrap();
+    ///     }
+    ///     assert!(my_rendezvous.wait().is_err());
+    /// });
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn scoped<'scope>(
+        scope: &'scope Scope<'scope, '_>,
+    ) -> (ScopedRendezvous<'scope>, ScopedRendezvous<'scope>) {
+        let _ = scope;
+        let (first, second) = Self::new();
+        let scoped = |inner| ScopedRendezvous {
+            inner,
+            scope: PhantomData,
+        };
+        (scoped(first), scoped(second))
+    }
+
+    /// Run `first` and `second` on two scoped threads, each with one handle
+    /// of a [`ScopedRendezvous`] pair, and return the results of both.
+    ///
+    /// The closures can borrow from the calling function. A panic in either
+    /// closure is resumed on the calling thread once both threads have
+    /// finished: the handle of the panicking thread is dropped, so the
+    /// other thread's next wait fails instead of spinning forever.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::Rendezvous;
+    ///
+    /// let mut log = Vec::new();
+    /// let (waits, ()) = Rendezvous::run_pair(
+    ///     |mut rendezvous| (0..).take_while(|_| rendezvous.wait().is_ok()).count(),
+    ///     |mut rendezvous| {
+    ///         for i in 0..3 {
+    ///             log.push(i);
+    ///             rendezvous.wait().unwrap();
+    ///         }
+    ///     },
+    /// );
+    /// assert_eq!((waits, log), (4, vec![0, 1, 2]));
+    /// ```
+    #[inline]
+    pub fn run_pair<A, B, RA, RB>(first: A, second: B) -> (RA, RB)
+    where
+        A: for<'scope> FnOnce(ScopedRendezvous<'scope>) -> RA + Send,
+        B: for<'scope> FnOnce(ScopedRendezvous<'scope>) -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        thread::scope(|scope| {
+            let (first_handle, second_handle) = Self::scoped(scope);
+            join_pair(
+                scope.spawn(move || first(first_handle)),
+                scope.spawn(move || second(second_handle)),
+            )
+        })
+    }
+}
+
+impl<T: Send> RendezvousData<T> {
+    /// Create a linked pair of [`ScopedRendezvousData`] that cannot outlive
+    /// `scope`, and never leave a thread of the scope spinning.
+    /// Arguments are the initial values for the data that will be swapped.
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    /// use std::thread;
+    ///
+    /// thread::scope(|scope| {
+    ///     let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::scoped(scope, 0, 0);
+    ///     scope.spawn(move || {
+    ///         *their_rendezvous.swap().unwrap() = 3;
+    ///     });
+    ///     my_rendezvous.swap().unwrap();
+    ///     // the data written before the other handle was dropped
+    ///     assert_eq!(my_rendezvous.swap(), Ok(&mut 3));
+    ///     assert!(my_rendezvous.swap().is_err());
+    /// });
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn scoped<'scope>(
+        scope: &'scope Scope<'scope, '_>,
+        data1: T,
+        data2: T,
+    ) -> (
+        ScopedRendezvousData<'scope, T>,
+        ScopedRendezvousData<'scope, T>,
+    ) {
+        let _ = scope;
+        let (first, second) = Self::new(data1, data2);
+        let scoped = |inner| ScopedRendezvousData {
+            inner,
+            scope: PhantomData,
+        };
+        (scoped(first), scoped(second))
+    }
+
+    /// Run `first` and `second` on two scoped threads, each with one handle
+    /// of a [`ScopedRendezvousData`] pair with the initial values `data1`
+    /// and `data2`, and return the results of both, see
+    /// [`Rendezvous::run_pair`].
+    /// # Example
+    /// ```rust
+    /// use rendezvous_swap::RendezvousData;
+    ///
+    /// let input = [1, 2, 3];
+    /// let (sum, ()) = RendezvousData::run_pair(
+    ///     0,
+    ///     0,
+    ///     |mut consumer| {
+    ///         let mut sum = 0;
+    ///         while let Ok(value) = consumer.swap() {
+    ///             sum += *value;
+    ///         }
+    ///         sum
+    ///     },
+    ///     |mut producer| {
+    ///         for value in &input {
+    ///             *producer.swap().unwrap() = *value;
+    ///         }
+    ///     },
+    /// );
+    /// assert_eq!(sum, 6);
+    /// ```
+    #[inline]
+    pub fn run_pair<A, B, RA, RB>(data1: T, data2: T, first: A, second: B) -> (RA, RB)
+    where
+        A: for<'scope> FnOnce(ScopedRendezvousData<'scope, T>) -> RA + Send,
+        B: for<'scope> FnOnce(ScopedRendezvousData<'scope, T>) -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        thread::scope(|scope| {
+            let (first_handle, second_handle) = Self::scoped(scope, data1, data2);
+            join_pair(
+                scope.spawn(move || first(first_handle)),
+                scope.spawn(move || second(second_handle)),
+            )
+        })
+    }
+}
+
+/// Join both threads of a `run_pair`, and resume the panic of the first one
+/// that panicked.
+fn join_pair<RA, RB>(
+    first: ScopedJoinHandle<'_, RA>,
+    second: ScopedJoinHandle<'_, RB>,
+) -> (RA, RB) {
+    match (first.join(), second.join()) {
+        (Ok(first), Ok(second)) => (first, second),
+        (Err(payload), _) | (_, Err(payload)) => resume_unwind(payload),
+    }
+}
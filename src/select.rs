@@ -0,0 +1,162 @@
+//! Contains [`select2`] and [`select`]
+
+use crate::os::std::sync::mpsc::Receiver;
+use crate::os::yield_now;
+use core::borrow::BorrowMut;
+use core::hint::spin_loop;
+
+use crate::Rendezvous;
+
+/// Number of spins between checks before [`select2`] and [`select`] start
+/// yielding.
+const SPIN_LIMIT: u32 = 6;
+
+/// Non-blocking receiving end of a channel, see [`select2`].
+///
+/// Implemented for [`Receiver`] and for closures, so other
+/// channels can be used with for example `|| receiver.try_recv().ok()`.
+pub trait TryReceive {
+    /// The type of the messages.
+    type Message;
+
+    /// Receive a message if one is available, without blocking.
+    fn try_receive(&mut self) -> Option<Self::Message>;
+}
+impl<T> TryReceive for Receiver<T> {
+    type Message = T;
+    #[inline]
+    fn try_receive(&mut self) -> Option<T> {
+        self.try_recv().ok()
+    }
+}
+impl<T> TryReceive for &Receiver<T> {
+    type Message = T;
+    #[inline]
+    fn try_receive(&mut self) -> Option<T> {
+        self.try_recv().ok()
+    }
+}
+impl<T, F: FnMut() -> Option<T>> TryReceive for F {
+    type Message = T;
+    #[inline]
+    fn try_receive(&mut self) -> Option<T> {
+        self()
+    }
+}
+
+/// Result of [`select2`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Selected<M> {
+    /// The other thread arrived, and the rendezvous has completed.
+    Rendezvous,
+    /// A message arrived first. The rendezvous has not started, and the
+    /// other thread keeps waiting until [`Rendezvous::wait`] or [`select2`]
+    /// is called again.
+    Message(M),
+}
+
+/// Wait for either the other thread to arrive at the sync point, or for a
+/// message on a channel, whichever happens first.
+///
+/// Both are checked in a loop that spins with exponential backoff, and then
+/// yields to the OS scheduler. Messages are checked first, so a steady stream
+/// of messages delays the rendezvous.
+/// # Example: React to commands while waiting
+/// ```rust
+/// use rendezvous_swap::select::{select2, Selected};
+/// use rendezvous_swap::Rendezvous;
+/// use std::sync::mpsc::channel;
+/// use std::thread;
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+/// let (sender, receiver) = channel();
+/// sender.send("stop").unwrap();
+/// assert_eq!(select2(&mut my_rendezvous, &receiver), Selected::Message("stop"));
+///
+/// let handle = thread::spawn(move || their_rendezvous.wait());
+/// assert_eq!(select2(&mut my_rendezvous, &receiver), Selected::Rendezvous);
+/// # handle.join().unwrap();
+/// ```
+#[inline]
+pub fn select2<R: TryReceive>(
+    rendezvous: &mut Rendezvous,
+    mut receiver: R,
+) -> Selected<R::Message> {
+    let mut step = 0;
+    loop {
+        if let Some(message) = receiver.try_receive() {
+            return Selected::Message(message);
+        }
+        if rendezvous.peer_arrived() {
+            rendezvous.wait();
+            return Selected::Rendezvous;
+        }
+        if step < SPIN_LIMIT {
+            for _ in 0..1_u32 << step {
+                spin_loop();
+            }
+            step += 1;
+        } else {
+            yield_now();
+        }
+    }
+}
+
+/// Wait for the other thread of any of `handles` to arrive at the sync point,
+/// complete the rendezvous of that handle, and return its index.
+///
+/// Exactly one rendezvous completes. The other handles are untouched, their
+/// other threads keep waiting until their handles are waited on or selected
+/// again. Handles are checked in order in a loop that spins with exponential
+/// backoff, and then yields to the OS scheduler, so when several threads have
+/// arrived, the first of them in `handles` is selected.
+///
+/// # Panics
+/// If `handles` is empty.
+/// # Example: Serve whichever worker is done first
+/// ```rust
+/// use rendezvous_swap::select::select;
+/// use rendezvous_swap::Rendezvous;
+/// use std::sync::mpsc::channel;
+/// use std::thread;
+///
+/// let (slow, mut slow_worker) = Rendezvous::new();
+/// let (fast, mut fast_worker) = Rendezvous::new();
+/// let mut workers = [slow, fast];
+/// let (done, wait_for_fast) = channel();
+/// let fast_handle = thread::spawn(move || {
+///     fast_worker.wait();
+///     done.send(()).unwrap();
+///     fast_worker // a dropped handle would arrive once more
+/// });
+/// let slow_handle = thread::spawn(move || {
+///     wait_for_fast.recv().unwrap();
+///     slow_worker.wait();
+/// });
+/// assert_eq!(select(&mut workers), 1);
+/// assert_eq!(select(&mut workers), 0);
+/// # drop(fast_handle.join().unwrap());
+/// # slow_handle.join().unwrap();
+/// ```
+#[inline]
+pub fn select<H: BorrowMut<Rendezvous>>(handles: &mut [H]) -> usize {
+    assert!(!handles.is_empty(), "select on no handles");
+    let mut step = 0;
+    loop {
+        if let Some(index) = handles
+            .iter()
+            .position(|handle| handle.borrow().peer_arrived())
+        {
+            handles[index].borrow_mut().wait();
+            return index;
+        }
+        if step < SPIN_LIMIT {
+            for _ in 0..1_u32 << step {
+                spin_loop();
+            }
+            step += 1;
+        } else {
+            yield_now();
+        }
+    }
+}
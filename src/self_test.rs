@@ -0,0 +1,121 @@
+//! Contains [`self_test`] and [`SelfTestReport`]
+
+use crate::os::std::thread;
+use crate::os::std::time::Instant;
+use core::time::Duration;
+
+use crate::RendezvousData;
+
+/// Number of swaps made by each thread of [`self_test`].
+const SELF_TEST_SWAPS: usize = 4096;
+
+/// Written before and after the payload of a [`Frame`].
+const CANARY: u64 = 0xDEAD_BEEF_CAFE_F00D;
+
+/// Buffer swapped by [`self_test`], large enough to span several cache
+/// lines so torn or stale copies show up in the payload.
+#[derive(Clone, Copy)]
+struct Frame {
+    /// Always [`CANARY`]
+    head: u64,
+    /// Thread that wrote the frame
+    side: u64,
+    /// Swap the frame was written for
+    sequence: u64,
+    /// Derived from `side`, `sequence` and the index
+    payload: [u64; 28],
+    /// Always [`CANARY`]
+    tail: u64,
+}
+impl Frame {
+    /// Frame `side` writes for swap `sequence`.
+    fn new(side: u64, sequence: u64) -> Self {
+        let mut payload = [0; 28];
+        for (i, word) in (0..).zip(payload.iter_mut()) {
+            *word = (sequence ^ side.rotate_left(32)).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ i;
+        }
+        Self {
+            head: CANARY,
+            side,
+            sequence,
+            payload,
+            tail: CANARY,
+        }
+    }
+}
+
+/// Outcome of a [`self_test`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SelfTestReport {
+    /// Number of swaps made by each thread
+    pub swaps: usize,
+    /// Number of received buffers with a damaged canary or payload
+    pub corrupted: usize,
+    /// Number of received buffers that were intact, but not the one the
+    /// other thread wrote for that swap
+    pub out_of_order: usize,
+    /// Wall clock time of the test, including spawning the threads
+    pub elapsed: Duration,
+}
+impl SelfTestReport {
+    /// True if every received buffer was intact and in order.
+    #[must_use]
+    #[inline]
+    pub const fn passed(&self) -> bool {
+        self.corrupted == 0 && self.out_of_order == 0
+    }
+}
+
+/// Check that swapping works on the current target: two short lived threads
+/// swap a few thousand buffers over a [`RendezvousData`], each checking
+/// that every buffer it receives is intact (canaries around a payload that
+/// depends on the sender and the swap) and is the one the other thread
+/// wrote for that swap.
+///
+/// Meant to be called once at startup where no CI covers the target
+/// (embedded-ish deployments, game launchers), so a miscompiled or exotic
+/// target is caught before it corrupts real data. Takes well under a second
+/// on a multi-core machine.
+/// # Example
+/// ```rust
+/// let report = rendezvous_swap::self_test();
+/// assert!(report.passed(), "{report:?}");
+/// ```
+#[must_use]
+#[inline]
+pub fn self_test() -> SelfTestReport {
+    let start = Instant::now();
+    let (first, second) = RendezvousData::new(Frame::new(0, 0), Frame::new(1, 0));
+    let (first, second) = thread::scope(|scope| {
+        let other = scope.spawn(move || run_side(second, 1));
+        let mine = run_side(first, 0);
+        (mine, other.join().expect("self test thread panicked"))
+    });
+    SelfTestReport {
+        swaps: SELF_TEST_SWAPS,
+        corrupted: first.0 + second.0,
+        out_of_order: first.1 + second.1,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Swap [`SELF_TEST_SWAPS`] times as `side`, and count the corrupted and out
+/// of order buffers received.
+fn run_side(mut rendezvous: RendezvousData<Frame>, side: u64) -> (usize, usize) {
+    let (mut corrupted, mut out_of_order) = (0, 0);
+    for sequence in (1..).take(SELF_TEST_SWAPS) {
+        // Data written before swap `n` arrives at the other thread's swap `n`.
+        *rendezvous.current_mut() = Frame::new(side, sequence);
+        let received = *rendezvous.swap();
+        let intact = received.head == CANARY
+            && received.tail == CANARY
+            && received.payload == Frame::new(received.side, received.sequence).payload;
+        if !intact {
+            corrupted += 1;
+        } else if received.side != side ^ 1 || received.sequence != sequence {
+            out_of_order += 1;
+        }
+    }
+    (corrupted, out_of_order)
+}
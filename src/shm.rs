@@ -0,0 +1,397 @@
+//! A rendezvous between two processes, over a named shared memory segment
+//! (Linux only).
+//!
+//! One process creates the segment with [`ShmRendezvous::create`], the other
+//! attaches to it by name with [`ShmRendezvous::open`]. The segment only
+//! holds plain atomics in a `repr(C)` layout, so the processes do not even
+//! have to run the same binary, as long as both use the same version of this
+//! crate.
+//!
+//! Each process publishes its pid and a heartbeat word in the segment, so a
+//! waiting process notices when its peer exits or crashes instead of
+//! waiting forever, see [`ShmRendezvous::peer_status`].
+
+use crate::os::std::ffi::CString;
+use crate::os::std::io;
+use crate::os::std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use crate::os::std::time::Instant;
+use crate::os::yield_now;
+use core::hint::spin_loop;
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{
+    AtomicUsize,
+    Ordering::{Acquire, Relaxed, Release},
+};
+use core::time::Duration;
+
+/// Written last by the creator, identifies an initialized segment of this
+/// layout.
+const MAGIC: usize = 0x7265_6e64_657a_0001;
+
+/// Pid of a side that has not attached yet.
+const NOT_ATTACHED: usize = 0;
+
+/// Pid of a side that has detached.
+const DETACHED: usize = usize::MAX;
+
+/// Spins between checks whether the peer is still alive.
+const SPINS_PER_CHECK: u32 = 1 << 10;
+
+/// State of one process in the segment.
+#[repr(C, align(128))]
+struct Side {
+    /// Generation, as in `Counter`
+    generation: AtomicUsize,
+    /// Process id, or [`NOT_ATTACHED`] or [`DETACHED`]
+    pid: AtomicUsize,
+    /// Incremented while waiting and by [`ShmRendezvous::heartbeat`]
+    heartbeat: AtomicUsize,
+}
+
+/// Layout of the shared memory segment, all zero is a valid (uninitialized)
+/// state.
+#[repr(C)]
+struct Segment {
+    /// [`MAGIC`] once initialized
+    magic: AtomicUsize,
+    /// Creator and opener
+    sides: [Side; 2],
+}
+
+/// Liveness of the peer process, see [`ShmRendezvous::peer_status`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerStatus {
+    /// The peer has not attached yet.
+    NotAttached,
+    /// The peer is waiting at the sync point, or its heartbeat changed.
+    Alive,
+    /// The peer process exists, but did not wait or beat within the timeout.
+    Unresponsive,
+    /// The peer detached or its process no longer exists.
+    Gone,
+}
+
+/// One side of a rendezvous between two processes.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::shm::ShmRendezvous;
+/// use std::thread;
+///
+/// let name = format!("/rendezvous_swap_doc_{}", std::process::id());
+/// let mut creator = ShmRendezvous::create(&name).unwrap();
+/// // usually in another process
+/// let mut opener = ShmRendezvous::open(&name).unwrap();
+/// let handle = thread::spawn(move || {
+///     for _ in 0..100 {
+///         opener.wait().unwrap();
+///     }
+/// });
+/// for _ in 0..100 {
+///     creator.wait().unwrap();
+/// }
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct ShmRendezvous {
+    /// The mapped segment
+    segment: NonNull<Segment>,
+    /// Index of this process in `Segment::sides`
+    side: usize,
+    /// Thread local generation
+    generation: usize,
+    /// Name of the segment, unlinked by the creator on drop
+    name: CString,
+}
+// SAFETY:
+// The segment only holds atomics and stays mapped until the handle is
+// dropped.
+unsafe impl Send for ShmRendezvous {}
+impl ShmRendezvous {
+    /// Create the named segment and attach to it as the first process.
+    ///
+    /// The name must start with `/` and contain no other `/`, see
+    /// `shm_open(3)`. The segment is removed when this handle is dropped.
+    ///
+    /// # Errors
+    /// If the segment already exists, or cannot be created and mapped.
+    #[inline]
+    pub fn create(name: &str) -> io::Result<Self> {
+        let name = c_name(name)?;
+        // SAFETY:
+        // `name` is a valid C string.
+        let fd = unsafe {
+            libc::shm_open(
+                name.as_ptr(),
+                libc::O_RDWR | libc::O_CREAT | libc::O_EXCL,
+                0o600,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY:
+        // `fd` was just opened, and is owned by nothing else.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let mapped = i64::try_from(size_of::<Segment>())
+            .map_err(io::Error::other)
+            // SAFETY:
+            // `fd` is open.
+            .and_then(|size| cvt(unsafe { libc::ftruncate(fd.as_raw_fd(), size) }))
+            .and_then(|()| map(fd));
+        let segment = match mapped {
+            Ok(segment) => segment,
+            Err(error) => {
+                // SAFETY:
+                // `name` is a valid C string.
+                unsafe { libc::shm_unlink(name.as_ptr()) };
+                return Err(error);
+            },
+        };
+        let handle = Self {
+            segment,
+            side: 0,
+            generation: 0,
+            name,
+        };
+        handle.sides()[0].pid.store(current_pid(), Relaxed);
+        handle.segment().magic.store(MAGIC, Release);
+        Ok(handle)
+    }
+
+    /// Attach to the named segment as the second process.
+    ///
+    /// # Errors
+    /// If the segment does not exist, is not a rendezvous segment, or
+    /// another process is already attached as the second process.
+    #[inline]
+    pub fn open(name: &str) -> io::Result<Self> {
+        let name = c_name(name)?;
+        // SAFETY:
+        // `name` is a valid C string.
+        let fd = unsafe { libc::shm_open(name.as_ptr(), libc::O_RDWR, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY:
+        // `fd` was just opened, and is owned by nothing else.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let mut stat = core::mem::MaybeUninit::<libc::stat>::uninit();
+        // SAFETY:
+        // `fd` is open and `stat` is large enough.
+        let size = cvt(unsafe { libc::fstat(fd.as_raw_fd(), stat.as_mut_ptr()) })
+            // SAFETY:
+            // Initialized by `fstat`.
+            .map(|()| unsafe { stat.assume_init() }.st_size);
+        size.and_then(|size| {
+            if usize::try_from(size).is_ok_and(|size| size >= size_of::<Segment>()) {
+                Ok(())
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "segment is too small",
+                ))
+            }
+        })?;
+        let segment = map(fd)?;
+        // SAFETY:
+        // Just mapped, see `segment`.
+        let attached = match unsafe { segment.as_ref() } {
+            shared if shared.magic.load(Acquire) != MAGIC => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a rendezvous segment",
+            )),
+            shared => shared.sides[1]
+                .pid
+                .compare_exchange(NOT_ATTACHED, current_pid(), Relaxed, Relaxed)
+                .map(drop)
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        "segment already has two processes",
+                    )
+                }),
+        };
+        if let Err(error) = attached {
+            // Not through `Drop`, which would detach the side of the process
+            // that is attached.
+            unmap(segment);
+            return Err(error);
+        }
+        let handle = Self {
+            segment,
+            side: 1,
+            generation: 0,
+            name,
+        };
+        Ok(handle)
+    }
+
+    /// Synchronize execution with the other process.
+    ///
+    /// As a side-effect, memory written through other shared mappings is
+    /// also synchronized.
+    ///
+    /// # Errors
+    /// [`io::ErrorKind::BrokenPipe`] if the other process detached or died
+    /// while this process was waiting for it.
+    #[inline]
+    pub fn wait(&mut self) -> io::Result<()> {
+        let next_generation = self.generation.wrapping_add(1);
+        let [mine, theirs] = self.ends();
+        mine.generation.store(next_generation, Release);
+        let mut spins: u32 = 0;
+        while {
+            spin_loop();
+            theirs.generation.load(Acquire) == self.generation
+        } {
+            spins = spins.wrapping_add(1);
+            if spins.is_multiple_of(SPINS_PER_CHECK) {
+                mine.heartbeat.fetch_add(1, Relaxed);
+                if self.peer_gone() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::BrokenPipe,
+                        "other process is gone",
+                    ));
+                }
+                yield_now();
+            }
+        }
+        self.generation = next_generation;
+        Ok(())
+    }
+
+    /// Signal that this process is alive while it is busy for a long time,
+    /// waiting does this automatically.
+    #[inline]
+    pub fn heartbeat(&self) {
+        self.ends()[0].heartbeat.fetch_add(1, Relaxed);
+    }
+
+    /// Check whether the other process is alive, watching its heartbeat for
+    /// up to `timeout`.
+    ///
+    /// Death is detected by checking whether the pid of the other process
+    /// still exists, so a recycled pid can make a dead process look
+    /// unresponsive instead.
+    #[must_use]
+    #[inline]
+    pub fn peer_status(&self, timeout: Duration) -> PeerStatus {
+        let theirs = self.ends()[1];
+        if theirs.pid.load(Relaxed) == NOT_ATTACHED {
+            return PeerStatus::NotAttached;
+        }
+        let deadline = Instant::now().checked_add(timeout);
+        let beat = theirs.heartbeat.load(Relaxed);
+        loop {
+            if self.peer_gone() {
+                return PeerStatus::Gone;
+            }
+            if theirs.generation.load(Relaxed) != self.generation
+                || theirs.heartbeat.load(Relaxed) != beat
+            {
+                return PeerStatus::Alive;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return PeerStatus::Unresponsive;
+            }
+            yield_now();
+        }
+    }
+
+    /// True if the other process detached, or was attached and no longer
+    /// exists.
+    fn peer_gone(&self) -> bool {
+        match self.ends()[1].pid.load(Relaxed) {
+            NOT_ATTACHED => false,
+            DETACHED => true,
+            pid => libc::pid_t::try_from(pid).is_ok_and(|pid| {
+                // SAFETY:
+                // Signal 0 only checks whether the process exists.
+                let result = unsafe { libc::kill(pid, 0) };
+                result != 0 && io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH)
+            }),
+        }
+    }
+
+    /// The mapped segment.
+    fn segment(&self) -> &Segment {
+        // SAFETY:
+        // Mapped until drop, and all zero (as created by `ftruncate`) is a
+        // valid `Segment`.
+        unsafe { self.segment.as_ref() }
+    }
+
+    /// Both sides.
+    fn sides(&self) -> &[Side; 2] {
+        &self.segment().sides
+    }
+
+    /// The side of this process and the side of the other process.
+    fn ends(&self) -> [&Side; 2] {
+        let sides = self.sides();
+        [&sides[self.side], &sides[self.side ^ 1]]
+    }
+}
+impl Drop for ShmRendezvous {
+    fn drop(&mut self) {
+        self.ends()[0].pid.store(DETACHED, Relaxed);
+        unmap(self.segment);
+        if self.side == 0 {
+            // SAFETY:
+            // `name` is a valid C string.
+            unsafe { libc::shm_unlink(self.name.as_ptr()) };
+        }
+    }
+}
+
+/// Convert a segment name.
+fn c_name(name: &str) -> io::Result<CString> {
+    CString::new(name).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))
+}
+
+/// Map the segment of `fd`, and close `fd`.
+fn map(fd: OwnedFd) -> io::Result<NonNull<Segment>> {
+    // SAFETY:
+    // `fd` is open, the mapping is checked below.
+    let address = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            size_of::<Segment>(),
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd.as_raw_fd(),
+            0,
+        )
+    };
+    let error = io::Error::last_os_error();
+    // Not needed once mapped.
+    drop(fd);
+    if address == libc::MAP_FAILED {
+        return Err(error);
+    }
+    NonNull::new(address.cast()).ok_or(error)
+}
+
+/// Unmap a segment mapped by [`map`].
+fn unmap(segment: NonNull<Segment>) {
+    // SAFETY:
+    // Mapped in `map` with this size, and not used after this.
+    unsafe { libc::munmap(segment.as_ptr().cast(), size_of::<Segment>()) };
+}
+
+/// Turn a libc return value into a result.
+fn cvt(result: libc::c_int) -> io::Result<()> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Pid of this process.
+fn current_pid() -> usize {
+    // SAFETY:
+    // `getpid` has no preconditions.
+    usize::try_from(unsafe { libc::getpid() }).unwrap_or(DETACHED)
+}
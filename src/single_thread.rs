@@ -0,0 +1,56 @@
+//! Contains [`SingleThread`]
+
+use core::mem::swap;
+
+use crate::{SwapPoint, SyncPoint};
+
+/// Stand-in for a [`RendezvousData`](crate::RendezvousData) when there is no
+/// other thread, for example in a `--threads 1` debug mode.
+///
+/// [`SyncPoint::sync`] does nothing and [`SwapPoint::swap`] alternates
+/// between two local buffers, so pipeline code written against the traits
+/// runs unchanged.
+/// # Example: Select at runtime
+/// ```rust
+/// use rendezvous_swap::{RendezvousData, SingleThread, SwapPoint};
+///
+/// let threads = 1;
+/// let mut point: Box<dyn SwapPoint<Data = i32>> = if threads == 1 {
+///     Box::new(SingleThread::new(0, 0))
+/// } else {
+///     let (mine, theirs) = RendezvousData::new(0, 0);
+///     // hand `theirs` to another thread
+///     # drop(theirs);
+///     Box::new(mine)
+/// };
+/// *point.swap() = 3;
+/// *point.swap() = 5;
+/// assert_eq!(*point.swap(), 3);
+/// ```
+#[non_exhaustive]
+pub struct SingleThread<T> {
+    /// The buffer last returned by `swap`, and the other buffer
+    data: (T, T),
+}
+impl<T> SingleThread<T> {
+    /// Create a [`SingleThread`] from the initial values of the two buffers.
+    #[must_use]
+    #[inline]
+    pub const fn new(data1: T, data2: T) -> Self {
+        Self {
+            data: (data1, data2),
+        }
+    }
+}
+impl<T> SyncPoint for SingleThread<T> {
+    #[inline]
+    fn sync(&mut self) {}
+}
+impl<T> SwapPoint for SingleThread<T> {
+    type Data = T;
+    #[inline]
+    fn swap(&mut self) -> &mut T {
+        swap(&mut self.data.0, &mut self.data.1);
+        &mut self.data.0
+    }
+}
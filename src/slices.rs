@@ -0,0 +1,137 @@
+//! Contains [`RendezvousSlices`]
+
+use alloc::sync::Arc;
+
+use crate::core_cell::{slice_mut, SlotCell};
+use crate::{Disconnected, Rendezvous, RendezvousData, SwapPoint, SyncPoint};
+
+/// Synchronise execution and swap slices of a length chosen at runtime
+/// between threads, created by [`RendezvousSlices::new`].
+///
+/// Works like [`RendezvousData`], but [`RendezvousSlices::swap`] returns
+/// `&mut [T]` pointing straight into the shared allocation, which holds both
+/// buffers back to back, instead of a fixed size array or a `Vec` behind
+/// another pointer.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::RendezvousSlices;
+/// use std::thread;
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = RendezvousSlices::new(1024, |_| 0_u8);
+/// let handle = thread::spawn(move || {
+///     their_rendezvous.swap().fill(7);
+///     their_rendezvous.swap();
+/// });
+/// my_rendezvous.swap();
+/// assert!(my_rendezvous.swap().iter().all(|&byte| byte == 7));
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct RendezvousSlices<T: Send> {
+    /// Syncs the swaps
+    rendezvous: Rendezvous,
+    /// Index of the buffer this thread currently owns
+    buffer: usize,
+    /// Both buffers, buffer `i` is elements `i * len..(i + 1) * len`
+    slots: Arc<[SlotCell<T>]>,
+}
+impl<T: Send> RendezvousSlices<T> {
+    /// Create a linked pair swapping slices of `len` elements, element `i`
+    /// of both buffers is `init(i)`.
+    ///
+    /// # Panics
+    /// If the two buffers together would hold more than `isize::MAX` bytes.
+    #[must_use]
+    #[inline]
+    pub fn new<F: FnMut(usize) -> T>(len: usize, mut init: F) -> (Self, Self) {
+        let total = len.checked_mul(2).expect("slices too long");
+        let slots: Arc<[SlotCell<T>]> = (0..total)
+            .map(|index| SlotCell::new(init(index % len)))
+            .collect();
+        let (first, second) = Rendezvous::new();
+        (
+            Self {
+                rendezvous: first,
+                buffer: 0,
+                slots: Arc::clone(&slots),
+            },
+            Self {
+                rendezvous: second,
+                buffer: 1,
+                slots,
+            },
+        )
+    }
+
+    /// Swap data with other thread and get a mutable reference to the
+    /// slice, see [`RendezvousData::swap`].
+    #[inline]
+    pub fn swap(&mut self) -> &mut [T] {
+        self.rendezvous.wait();
+        self.buffer ^= 1;
+        self.current_mut()
+    }
+
+    /// [`RendezvousSlices::swap`] that returns instead of spinning forever
+    /// once the other handle is dropped, see [`Rendezvous::wait_checked`].
+    ///
+    /// # Errors
+    /// If the other handle has been dropped, see [`Rendezvous::wait_checked`].
+    #[inline]
+    pub fn swap_checked(&mut self) -> Result<&mut [T], Disconnected> {
+        self.rendezvous.wait_checked()?;
+        self.buffer ^= 1;
+        Ok(self.current_mut())
+    }
+
+    /// The slice returned by the last swap, without swapping.
+    #[inline]
+    pub fn current_mut(&mut self) -> &mut [T] {
+        let len = self.len();
+        let cells = &self.slots[self.buffer * len..(self.buffer + 1) * len];
+        // SAFETY:
+        // The other thread handed this buffer over at the last swap, and
+        // only accesses it again after this thread arrives at the next one.
+        unsafe { slice_mut(cells) }
+    }
+
+    /// Number of elements of each slice.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.slots.len() / 2
+    }
+
+    /// True if the slices have no elements.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+impl<T: Send> SyncPoint for RendezvousSlices<T> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.swap();
+    }
+}
+impl<T: Send> SwapPoint for RendezvousSlices<T> {
+    type Data = [T];
+    #[inline]
+    fn swap(&mut self) -> &mut [T] {
+        RendezvousSlices::swap(self)
+    }
+}
+
+impl<T: Send> RendezvousData<T> {
+    /// Create a linked pair swapping slices of `len` elements, see
+    /// [`RendezvousSlices::new`].
+    #[must_use]
+    #[inline]
+    pub fn new_slices<F: FnMut(usize) -> T>(
+        len: usize,
+        init: F,
+    ) -> (RendezvousSlices<T>, RendezvousSlices<T>) {
+        RendezvousSlices::new(len, init)
+    }
+}
@@ -0,0 +1,118 @@
+//! Contains [`SplitRendezvousData`]
+
+use alloc::sync::Arc;
+
+use crate::core_cell::SlotCell;
+use crate::padded::Padded;
+use crate::{Rendezvous, SyncPoint};
+
+/// Buffers of a [`SplitRendezvousData`] pair, two per side.
+struct SplitShared<T: Send> {
+    /// `buffers[side][generation & 1]` is written by `side` in that
+    /// generation and read by the other side in the next one, the
+    /// rendezvous keeps the threads at most one generation apart
+    buffers: [[Padded<SlotCell<T>>; 2]; 2],
+}
+
+/// Synchronise execution and exchange data between threads, with the
+/// incoming data staying readable while the outgoing data is written.
+///
+/// Within a generation each thread gets a shared reference to the data the
+/// other thread wrote in the previous generation, and a mutable reference to
+/// the buffer it writes for the next one. Four buffers are used internally,
+/// so nothing has to be copied to keep the input around while producing the
+/// output. Buffers are never cleared, the outgoing buffer contains what this
+/// thread wrote to it two generations ago.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::SplitRendezvousData;
+/// use std::thread;
+///
+/// let (mut my_rendezvous, mut their_rendezvous) =
+///     SplitRendezvousData::new([0, 0], [0, 0]);
+/// let handle = thread::spawn(move || {
+///     for _ in 0..10 {
+///         let (incoming, outgoing) = their_rendezvous.swap();
+///         *outgoing = *incoming + 1;
+///     }
+/// });
+/// let mut last = 0;
+/// for _ in 0..10 {
+///     let (incoming, outgoing) = my_rendezvous.swap();
+///     last = *incoming;
+///     *outgoing = *incoming + 1;
+/// }
+/// assert_eq!(last, 9);
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct SplitRendezvousData<T: Send> {
+    /// Synchronizes the generations
+    rendezvous: Rendezvous,
+    /// Thread local generation
+    generation: usize,
+    /// Index of this side in the buffers
+    side: usize,
+    /// Shared buffers
+    shared: Arc<SplitShared<T>>,
+}
+impl<T: Send> SplitRendezvousData<T> {
+    /// Create a linked pair of [`SplitRendezvousData`]
+    /// Arguments are the two buffers of each handle, the first buffer is
+    /// written in the first generation and the second is what the other
+    /// handle reads in the first generation.
+    #[must_use]
+    #[inline]
+    pub fn new(first: [T; 2], second: [T; 2]) -> (Self, Self) {
+        let shared = Arc::new(SplitShared {
+            buffers: [first, second].map(|side| side.map(|data| Padded::new(SlotCell::new(data)))),
+        });
+        let (first, second) = Rendezvous::new();
+        (
+            Self {
+                rendezvous: first,
+                generation: 0,
+                side: 0,
+                shared: Arc::clone(&shared),
+            },
+            Self {
+                rendezvous: second,
+                generation: 0,
+                side: 1,
+                shared,
+            },
+        )
+    }
+
+    /// Synchronize with the other thread, then get the data it wrote in the
+    /// generation that just ended and the buffer to write for the next one.
+    #[inline]
+    pub fn swap(&mut self) -> (&T, &mut T) {
+        self.rendezvous.wait();
+        self.generation = self.generation.wrapping_add(1);
+        self.split()
+    }
+
+    /// Get the incoming and outgoing data of the current generation, without
+    /// synchronizing.
+    #[must_use]
+    #[inline]
+    pub fn split(&mut self) -> (&T, &mut T) {
+        let parity = self.generation & 1;
+        let incoming = &self.shared.buffers[self.side ^ 1][parity ^ 1];
+        let outgoing = &self.shared.buffers[self.side][parity];
+        // SAFETY:
+        // The other thread wrote `incoming` in the previous generation and
+        // only writes its other buffer in this one, so only this thread
+        // accesses it. `outgoing` was last read by the other thread in the
+        // previous generation. Both references borrow `self`, so they end
+        // before the next generation.
+        unsafe { (&*incoming.get_mut(), outgoing.get_mut()) }
+    }
+}
+impl<T: Send> SyncPoint for SplitRendezvousData<T> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.swap();
+    }
+}
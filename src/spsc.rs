@@ -0,0 +1,358 @@
+//! Bounded single producer, single consumer channel, see [`channel`]
+//!
+//! Where the pairs of this crate move in lockstep, the sender of a channel
+//! runs ahead of the receiver by up to `capacity` values, and only waits
+//! once the channel is full. It uses the same technique as the pairs: each
+//! side only writes its own padded position counter, which the other side
+//! reads, so only [`Acquire`] loads and [`Release`] stores are needed, and
+//! no read-modify-write atomics.
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::fmt;
+use core::hint::spin_loop;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Acquire, Ordering::Release};
+
+#[cfg(feature = "std")]
+use crate::contention::{Contention, ContentionStats};
+use crate::core_cell::SlotCell;
+use crate::padded::Padded;
+use crate::Disconnected;
+
+/// State shared by a [`Sender`] and a [`Receiver`].
+///
+/// Positions count modulo `2 * capacity`, so a full ring (positions
+/// `capacity` apart) can be told apart from an empty one (equal positions)
+/// for any capacity. Position `p` uses slot `p % capacity`.
+struct Shared<T: Send> {
+    /// Position of the next value to receive, written by the receiver
+    head: Padded<AtomicUsize>,
+    /// Position of the next value to send, written by the sender
+    tail: Padded<AtomicUsize>,
+    /// Set when the sender is dropped
+    sender_gone: AtomicBool,
+    /// Set when the receiver is dropped
+    receiver_gone: AtomicBool,
+    /// Slots from `head` to `tail` hold values, the others are uninitialized
+    slots: Box<[SlotCell<MaybeUninit<T>>]>,
+}
+impl<T: Send> Shared<T> {
+    /// Number of slots.
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Position after `position`.
+    fn next(&self, position: usize) -> usize {
+        let next = position + 1;
+        if next == 2 * self.capacity() {
+            0
+        } else {
+            next
+        }
+    }
+
+    /// Number of values between `head` and `tail`.
+    fn len(&self, head: usize, tail: usize) -> usize {
+        if tail >= head {
+            tail - head
+        } else {
+            tail + 2 * self.capacity() - head
+        }
+    }
+
+    /// Slot of `position`.
+    fn slot(&self, position: usize) -> &SlotCell<MaybeUninit<T>> {
+        &self.slots[position % self.capacity()]
+    }
+}
+impl<T: Send> Drop for Shared<T> {
+    /// Drop the values that were sent but not received.
+    fn drop(&mut self) {
+        let (mut head, tail) = (*self.head.get_mut(), *self.tail.get_mut());
+        while head != tail {
+            // SAFETY:
+            // Both sides are gone, and the slots from `head` to `tail` hold
+            // values, which are dropped once.
+            unsafe { self.slot(head).get_mut().assume_init_drop() };
+            head = self.next(head);
+        }
+    }
+}
+
+/// Create a channel holding up to `capacity` values that have been sent but
+/// not received.
+///
+/// # Panics
+/// If `capacity` is zero, or larger than `usize::MAX / 2`.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::spsc;
+/// use std::thread;
+///
+/// let (mut sender, mut receiver) = spsc::channel(16);
+/// let handle = thread::spawn(move || {
+///     for i in 0..100 {
+///         sender.send(i).unwrap(); // waits only while 16 values are queued
+///     }
+/// });
+/// let mut sum = 0;
+/// while let Ok(value) = receiver.recv() {
+///     sum += value;
+/// }
+/// assert_eq!(sum, 4950);
+/// # handle.join().unwrap();
+/// ```
+#[must_use]
+#[inline]
+pub fn channel<T: Send>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(
+        capacity > 0 && capacity <= usize::MAX / 2,
+        "invalid channel capacity {capacity}"
+    );
+    let shared = Arc::new(Shared {
+        head: Padded::new(AtomicUsize::new(0)),
+        tail: Padded::new(AtomicUsize::new(0)),
+        sender_gone: AtomicBool::new(false),
+        receiver_gone: AtomicBool::new(false),
+        slots: (0..capacity)
+            .map(|_| SlotCell::new(MaybeUninit::uninit()))
+            .collect(),
+    });
+    (
+        Sender {
+            tail: 0,
+            head: 0,
+            #[cfg(feature = "std")]
+            contention: Contention::new(),
+            shared: Arc::clone(&shared),
+        },
+        Receiver {
+            head: 0,
+            tail: 0,
+            #[cfg(feature = "std")]
+            contention: Contention::new(),
+            shared,
+        },
+    )
+}
+
+/// Sending half of a [`channel`].
+///
+/// Dropping it disconnects the channel, the receiver still gets the values
+/// sent before.
+#[non_exhaustive]
+pub struct Sender<T: Send> {
+    /// Position of the next value to send
+    tail: usize,
+    /// Last seen position of the receiver, values before it were received
+    head: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// Positions and slots
+    shared: Arc<Shared<T>>,
+}
+impl<T: Send> Sender<T> {
+    /// Send `value` if the channel is not full, without waiting.
+    ///
+    /// # Errors
+    /// If the channel is full or the receiver was dropped, with `value`.
+    #[inline]
+    pub fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        let shared = &*self.shared;
+        if shared.receiver_gone.load(Acquire) {
+            return Err(TrySendError::Disconnected(value));
+        }
+        if shared.len(self.head, self.tail) == shared.capacity() {
+            self.head = shared.head.load(Acquire);
+            if shared.len(self.head, self.tail) == shared.capacity() {
+                return Err(TrySendError::Full(value));
+            }
+        }
+        // SAFETY:
+        // The slot is outside of `head..tail`, so the receiver has taken its
+        // value out (observed with acquire ordering through `head`), and
+        // only reads it again after this thread publishes `tail` past it.
+        unsafe { shared.slot(self.tail).get_mut().write(value) };
+        self.tail = shared.next(self.tail);
+        shared.tail.store(self.tail, Release);
+        Ok(())
+    }
+
+    /// Send `value`, waiting while the channel is full.
+    ///
+    /// # Errors
+    /// If the receiver was dropped, with `value`.
+    #[inline]
+    pub fn send(&mut self, mut value: T) -> Result<(), SendError<T>> {
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        loop {
+            match self.try_send(value) {
+                Ok(()) => break,
+                Err(TrySendError::Disconnected(value)) => return Err(SendError(value)),
+                Err(TrySendError::Full(returned)) => value = returned,
+            }
+            spin_loop();
+            #[cfg(feature = "std")]
+            if self.contention.spin(&mut spins) {
+                crate::os::std::thread::yield_now();
+            }
+        }
+        #[cfg(feature = "std")]
+        self.contention.finish(spins);
+        Ok(())
+    }
+
+    /// Maximum number of values sent but not received.
+    #[must_use]
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    /// Statistics on how often [`Sender::send`] gave up spinning and yielded
+    /// to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+}
+impl<T: Send> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.sender_gone.store(true, Release);
+    }
+}
+
+/// Receiving half of a [`channel`].
+#[non_exhaustive]
+pub struct Receiver<T: Send> {
+    /// Position of the next value to receive
+    head: usize,
+    /// Last seen position of the sender, values before it were sent
+    tail: usize,
+    /// Detects when spinning is counterproductive
+    #[cfg(feature = "std")]
+    contention: Contention,
+    /// Positions and slots
+    shared: Arc<Shared<T>>,
+}
+impl<T: Send> Receiver<T> {
+    /// Receive the oldest value, if any, without waiting.
+    #[inline]
+    pub fn try_recv(&mut self) -> Option<T> {
+        let shared = &*self.shared;
+        if self.head == self.tail {
+            self.tail = shared.tail.load(Acquire);
+            if self.head == self.tail {
+                return None;
+            }
+        }
+        // SAFETY:
+        // The slot is inside of `head..tail`, so the sender has written it
+        // (observed with acquire ordering through `tail`), and only writes
+        // it again after this thread publishes `head` past it. The value is
+        // read once, the slot counts as uninitialized from here on.
+        let value = unsafe { shared.slot(self.head).get_mut().assume_init_read() };
+        self.head = shared.next(self.head);
+        shared.head.store(self.head, Release);
+        Some(value)
+    }
+
+    /// Receive the oldest value, waiting while the channel is empty.
+    ///
+    /// # Errors
+    /// If the channel is empty and the sender was dropped.
+    #[inline]
+    pub fn recv(&mut self) -> Result<T, Disconnected> {
+        #[cfg(feature = "std")]
+        let mut spins = 0;
+        let value = loop {
+            if let Some(value) = self.try_recv() {
+                break value;
+            }
+            if self.shared.sender_gone.load(Acquire) {
+                // Values sent before the sender was dropped are visible now.
+                return self.try_recv().ok_or(Disconnected);
+            }
+            spin_loop();
+            #[cfg(feature = "std")]
+            if self.contention.spin(&mut spins) {
+                crate::os::std::thread::yield_now();
+            }
+        };
+        #[cfg(feature = "std")]
+        self.contention.finish(spins);
+        Ok(value)
+    }
+
+    /// Maximum number of values sent but not received.
+    #[must_use]
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    /// Statistics on how often [`Receiver::recv`] gave up spinning and
+    /// yielded to the OS scheduler.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[inline]
+    pub fn contention_stats(&self) -> ContentionStats {
+        self.contention.stats()
+    }
+}
+impl<T: Send> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_gone.store(true, Release);
+    }
+}
+
+/// The receiver of a [`channel`] was dropped, [`Sender::send`] returns the
+/// value that was not sent.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SendError<T>(pub T);
+impl<T> fmt::Display for SendError<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sending on a channel whose receiver was dropped")
+    }
+}
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> crate::os::std::error::Error for SendError<T> {}
+
+/// [`Sender::try_send`] did not send, with the value.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TrySendError<T> {
+    /// The channel holds `capacity` values
+    Full(T),
+    /// The receiver was dropped
+    Disconnected(T),
+}
+impl<T> TrySendError<T> {
+    /// The value that was not sent.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(value) | Self::Disconnected(value) => value,
+        }
+    }
+}
+impl<T> fmt::Display for TrySendError<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Full(_) => f.write_str("sending on a full channel"),
+            Self::Disconnected(_) => f.write_str("sending on a channel whose receiver was dropped"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> crate::os::std::error::Error for TrySendError<T> {}
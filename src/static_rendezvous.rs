@@ -0,0 +1,110 @@
+//! Contains [`StaticRendezvous`] and [`StaticRendezvousData`]
+
+use core::sync::atomic::{AtomicBool, Ordering::Relaxed};
+
+use crate::borrowed::{RendezvousDataRef, RendezvousDataStorage, RendezvousRef};
+use crate::sync::const_unless_loom;
+
+/// Shared state of a [`RendezvousRef`] pair that can be placed in a
+/// `static`, for bare-metal targets without an allocator.
+///
+/// The handles are taken out once with [`StaticRendezvous::split`].
+/// # Example
+/// ```rust
+/// use rendezvous_swap::StaticRendezvous;
+/// use std::thread;
+///
+/// static RENDEZVOUS: StaticRendezvous = StaticRendezvous::new();
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = RENDEZVOUS.split().unwrap();
+/// assert!(RENDEZVOUS.split().is_none());
+/// let handle = thread::spawn(move || their_rendezvous.wait());
+/// my_rendezvous.wait();
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct StaticRendezvous {
+    /// Counters
+    storage: RendezvousDataStorage<()>,
+    /// Set once the handles have been taken out
+    split: AtomicBool,
+}
+impl StaticRendezvous {
+    const_unless_loom! {
+        /// Shared state of a pair that has not been split yet.
+        #[must_use]
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                storage: RendezvousDataStorage::new((), ()),
+                split: AtomicBool::new(false),
+            }
+        }
+    }
+
+    /// Take out the two handles of the pair, `None` if they have been taken
+    /// out before.
+    #[must_use]
+    #[inline]
+    pub fn split(&self) -> Option<(RendezvousRef<'_>, RendezvousRef<'_>)> {
+        // Only the handles taken out here access the storage, so there is
+        // nothing to synchronize with.
+        (!self.split.swap(true, Relaxed)).then(|| RendezvousRef::from_fresh(&self.storage))
+    }
+}
+impl Default for StaticRendezvous {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shared state of a [`RendezvousDataRef`] pair that can be placed in a
+/// `static`, for bare-metal targets without an allocator, see
+/// [`StaticRendezvous`].
+/// # Example
+/// ```rust
+/// use rendezvous_swap::StaticRendezvousData;
+/// use std::thread;
+///
+/// static BUFFERS: StaticRendezvousData<[u8; 64]> = StaticRendezvousData::new([0; 64], [0; 64]);
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = BUFFERS.split().unwrap();
+/// let handle = thread::spawn(move || {
+///     their_rendezvous.swap()[0] = 1;
+///     their_rendezvous.swap();
+/// });
+/// my_rendezvous.swap();
+/// assert_eq!(my_rendezvous.swap()[0], 1);
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct StaticRendezvousData<T: Send> {
+    /// Counters and buffers
+    storage: RendezvousDataStorage<T>,
+    /// Set once the handles have been taken out
+    split: AtomicBool,
+}
+impl<T: Send> StaticRendezvousData<T> {
+    const_unless_loom! {
+        /// Shared state of a pair that has not been split yet, the first handle
+        /// starts out with `data1` and the second with `data2`.
+        #[must_use]
+        #[inline]
+        pub fn new(data1: T, data2: T) -> Self {
+            Self {
+                storage: RendezvousDataStorage::new(data1, data2),
+                split: AtomicBool::new(false),
+            }
+        }
+    }
+
+    /// Take out the two handles of the pair, `None` if they have been taken
+    /// out before.
+    #[must_use]
+    #[inline]
+    pub fn split(&self) -> Option<(RendezvousDataRef<'_, T>, RendezvousDataRef<'_, T>)> {
+        // See `StaticRendezvous::split`.
+        (!self.split.swap(true, Relaxed)).then(|| RendezvousDataRef::from_fresh(&self.storage))
+    }
+}
@@ -0,0 +1,62 @@
+//! Contains [`Op`], the operations checked by the `strict` feature
+
+use core::fmt;
+
+/// Operation a handle performs at a sync point.
+///
+/// With the `strict` feature each thread publishes the operation along with
+/// its generation, and both threads check that they agree once the sync
+/// point completes, see [`Counter::check`](crate::counter::Counter::check).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "strict"), allow(dead_code))]
+pub(crate) enum Op {
+    /// `Rendezvous::wait`
+    Wait,
+    /// `RendezvousData::swap`
+    Swap,
+    /// One of the waits of `Paused::reinit`
+    Reinit,
+    /// The wait before the leader accesses both buffers in
+    /// `RendezvousData::swap_with_both`
+    SwapWithBoth,
+    /// `RendezvousDataN::checked_swap`
+    CheckedSwap,
+}
+impl Op {
+    /// Encode as a nonzero integer, zero is "nothing published yet".
+    #[cfg(feature = "strict")]
+    pub(crate) const fn encode(self) -> usize {
+        match self {
+            Self::Wait => 1,
+            Self::Swap => 2,
+            Self::Reinit => 3,
+            Self::SwapWithBoth => 4,
+            Self::CheckedSwap => 5,
+        }
+    }
+
+    /// Inverse of [`Op::encode`].
+    #[cfg(feature = "strict")]
+    pub(crate) const fn decode(value: usize) -> Option<Self> {
+        match value {
+            1 => Some(Self::Wait),
+            2 => Some(Self::Swap),
+            3 => Some(Self::Reinit),
+            4 => Some(Self::SwapWithBoth),
+            5 => Some(Self::CheckedSwap),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for Op {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Wait => "wait",
+            Self::Swap => "swap",
+            Self::Reinit => "reinit",
+            Self::SwapWithBoth => "swap_with_both",
+            Self::CheckedSwap => "checked_swap",
+        })
+    }
+}
@@ -0,0 +1,122 @@
+//! Contains [`Hooked`]
+
+use crate::{RendezvousDataN, SwapPoint, SyncPoint};
+
+/// A [`RendezvousDataN`] that calls `on_publish` with the outgoing data
+/// right before handing it over, and `on_receive` with the incoming data
+/// right after receiving it, created by [`RendezvousDataN::with_hooks`].
+///
+/// The swap is only correct on cache coherent processors. On heterogeneous
+/// systems without coherence (for example a DSP and an MCU core sharing
+/// memory), `on_publish` is where the sender cleans (writes back) the cache
+/// lines of the buffer, and `on_receive` where the receiver invalidates
+/// them. The hooks of each side are set on its own handle.
+///
+/// The data handed over when the handle is dropped does not pass through
+/// `on_publish`.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::RendezvousData;
+/// use std::thread;
+///
+/// fn clean(data: &[u8; 64]) {
+///     // write back the cache lines of `data`
+/// #   let _ = data;
+/// }
+/// fn invalidate(data: &[u8; 64]) {
+///     // discard stale cache lines of `data`
+/// #   let _ = data;
+/// }
+///
+/// let (my_rendezvous, their_rendezvous) = RendezvousData::new([0; 64], [0; 64]);
+/// let mut my_rendezvous = my_rendezvous.with_hooks(clean, invalidate);
+/// let handle = thread::spawn(move || {
+///     let mut their_rendezvous = their_rendezvous.with_hooks(clean, invalidate);
+///     their_rendezvous.swap()[0] = 1;
+///     their_rendezvous.swap();
+/// });
+/// my_rendezvous.swap();
+/// assert_eq!(my_rendezvous.swap()[0], 1);
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct Hooked<T: Send, const SLOTS: usize, P, R> {
+    /// The handle
+    inner: RendezvousDataN<T, SLOTS>,
+    /// Called with the outgoing data before each swap
+    on_publish: P,
+    /// Called with the incoming data after each swap
+    on_receive: R,
+}
+impl<T, const SLOTS: usize, P, R> Hooked<T, SLOTS, P, R>
+where
+    T: Send,
+    P: FnMut(&T),
+    R: FnMut(&T),
+{
+    /// Swap data with the other thread, see [`RendezvousDataN::swap`],
+    /// calling the hooks around it.
+    #[inline]
+    pub fn swap(&mut self) -> &mut T {
+        (self.on_publish)(self.inner.current_mut());
+        let data = self.inner.swap();
+        (self.on_receive)(data);
+        data
+    }
+
+    /// The handle, to read its state and statistics.
+    #[must_use]
+    #[inline]
+    pub const fn get_ref(&self) -> &RendezvousDataN<T, SLOTS> {
+        &self.inner
+    }
+
+    /// Remove the hooks.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> RendezvousDataN<T, SLOTS> {
+        self.inner
+    }
+}
+impl<T, const SLOTS: usize, P, R> SyncPoint for Hooked<T, SLOTS, P, R>
+where
+    T: Send,
+    P: FnMut(&T),
+    R: FnMut(&T),
+{
+    #[inline]
+    fn sync(&mut self) {
+        let _ = Hooked::swap(self);
+    }
+}
+impl<T, const SLOTS: usize, P, R> SwapPoint for Hooked<T, SLOTS, P, R>
+where
+    T: Send,
+    P: FnMut(&T),
+    R: FnMut(&T),
+{
+    type Data = T;
+    #[inline]
+    fn swap(&mut self) -> &mut T {
+        Hooked::swap(self)
+    }
+}
+
+impl<T: Send, const SLOTS: usize> RendezvousDataN<T, SLOTS> {
+    /// Call `on_publish` with the outgoing data before every swap, and
+    /// `on_receive` with the incoming data after it, for cache maintenance
+    /// on platforms that are not cache coherent, see [`Hooked`].
+    #[must_use]
+    #[inline]
+    pub fn with_hooks<P, R>(self, on_publish: P, on_receive: R) -> Hooked<T, SLOTS, P, R>
+    where
+        P: FnMut(&T),
+        R: FnMut(&T),
+    {
+        Hooked {
+            inner: self,
+            on_publish,
+            on_receive,
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Atomics, cells and spin hints of the pair protocol
+//!
+//! Built with `--cfg loom`, these are the model checked versions of
+//! [loom](https://docs.rs/loom), so that `tests/loom.rs` can explore every
+//! interleaving and every ordering the memory model allows for two threads
+//! syncing and swapping. Otherwise they are the ones of `core`.
+//!
+//! Only the generation counters, the fences ordering them, the slots and the
+//! spin loops waiting on the counters go through here. Everything else
+//! (statistics, tags, flags of the optional features) keeps using `core`,
+//! and is not model checked.
+
+#[cfg(not(loom))]
+pub(crate) use core::cell::UnsafeCell;
+#[cfg(not(loom))]
+pub(crate) use core::hint::spin_loop;
+#[cfg(all(not(loom), feature = "explicit-fences"))]
+pub(crate) use core::sync::atomic::fence;
+#[cfg(not(loom))]
+pub(crate) use core::sync::atomic::AtomicUsize;
+
+#[cfg(loom)]
+pub(crate) use loom::cell::UnsafeCell;
+#[cfg(loom)]
+pub(crate) use loom::hint::spin_loop;
+#[cfg(all(loom, feature = "explicit-fences"))]
+pub(crate) use loom::sync::atomic::fence;
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::AtomicUsize;
+
+/// Define a function that is a `const fn`, except when built with
+/// `--cfg loom`, whose atomics and cells cannot be created in constants.
+macro_rules! const_unless_loom {
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $body:block) => {
+        $(#[$attr])*
+        #[cfg(not(loom))]
+        $vis const fn $name($($arg: $ty),*) -> $ret $body
+
+        $(#[$attr])*
+        #[cfg(loom)]
+        $vis fn $name($($arg: $ty),*) -> $ret $body
+    };
+}
+pub(crate) use const_unless_loom;
@@ -0,0 +1,127 @@
+//! Contains [`SyncPoint`] and [`SwapPoint`]
+
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+
+#[cfg(feature = "std")]
+use crate::os::std::sync::Barrier;
+#[cfg(feature = "alloc")]
+use crate::{Rendezvous, RendezvousDataN, SubRendezvous};
+
+/// A point where a thread synchronizes execution with other threads.
+///
+/// Allows pipeline stages to be written generically over the barrier type.
+/// Implemented by the barrier types of this crate, by the
+/// [`FanOutPublisher`](crate::FanOutPublisher) and
+/// [`FanOutReader`](crate::FanOutReader) of a broadcast, and with `std` by
+/// `&Barrier` and `Arc<Barrier>` of
+/// [`std::sync::Barrier`](https://doc.rust-lang.org/std/sync/struct.Barrier.html),
+/// so the same stage runs in a pair or in a larger group of threads.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{Rendezvous, SyncPoint};
+/// use std::thread;
+///
+/// fn stage(mut sync: impl SyncPoint, ticks: usize) {
+///     for _ in 0..ticks {
+///         sync.sync();
+///     }
+/// }
+/// let (my_rendezvous, their_rendezvous) = Rendezvous::new();
+/// let handle = thread::spawn(move || stage(their_rendezvous, 4));
+/// stage(my_rendezvous, 4);
+/// # handle.join().unwrap();
+/// ```
+/// # Example: Same stage on a `std` barrier
+/// ```rust
+/// use rendezvous_swap::SyncPoint;
+/// use std::sync::{Arc, Barrier};
+/// use std::thread;
+///
+/// fn stage(mut sync: impl SyncPoint, ticks: usize) {
+///     for _ in 0..ticks {
+///         sync.sync();
+///     }
+/// }
+/// # #[cfg(feature = "std")]
+/// # {
+/// let barrier = Arc::new(Barrier::new(3));
+/// let handles: Vec<_> = (0..2)
+///     .map(|_| {
+///         let barrier = Arc::clone(&barrier);
+///         thread::spawn(move || stage(barrier, 4))
+///     })
+///     .collect();
+/// stage(&*barrier, 4);
+/// # handles.into_iter().for_each(|handle| handle.join().unwrap());
+/// # }
+/// ```
+pub trait SyncPoint {
+    /// Synchronize execution with the other thread(s).
+    ///
+    /// For types that swap data, the data is swapped and ignored.
+    fn sync(&mut self);
+}
+
+/// A [`SyncPoint`] that also swaps data.
+pub trait SwapPoint: SyncPoint {
+    /// The data that is swapped, possibly a slice.
+    type Data: ?Sized;
+
+    /// Synchronize execution and get a mutable reference to the swapped data.
+    fn swap(&mut self) -> &mut Self::Data;
+
+    /// Synchronize execution, then call `f` with the swapped data and return
+    /// its result, see [`RendezvousDataN::swap_with`](crate::RendezvousDataN::swap_with).
+    #[inline]
+    fn swap_with<R, F: FnOnce(&mut Self::Data) -> R>(&mut self, f: F) -> R
+    where
+        Self: Sized,
+    {
+        f(self.swap())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl SyncPoint for Rendezvous {
+    #[inline]
+    fn sync(&mut self) {
+        self.wait();
+    }
+}
+#[cfg(feature = "alloc")]
+impl SyncPoint for SubRendezvous<'_> {
+    #[inline]
+    fn sync(&mut self) {
+        self.wait();
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Send, const SLOTS: usize> SyncPoint for RendezvousDataN<T, SLOTS> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = RendezvousDataN::swap(self);
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Send, const SLOTS: usize> SwapPoint for RendezvousDataN<T, SLOTS> {
+    type Data = T;
+    #[inline]
+    fn swap(&mut self) -> &mut T {
+        RendezvousDataN::swap(self)
+    }
+}
+#[cfg(feature = "std")]
+impl SyncPoint for &Barrier {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.wait();
+    }
+}
+#[cfg(feature = "std")]
+impl SyncPoint for Arc<Barrier> {
+    #[inline]
+    fn sync(&mut self) {
+        let _ = self.wait();
+    }
+}
@@ -0,0 +1,91 @@
+//! Contains [`Tagged`]
+
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use crate::{SwapPoint, SyncPoint};
+
+/// A handle marked with a phase tag type, so that handles of different
+/// pipelines or phases are distinct types.
+///
+/// The tag is any type, usually an empty struct per pipeline, and only
+/// exists at compile time: a `Tagged` has the size and speed of the handle,
+/// and derefs to it, so all of its methods are available. Passing the audio
+/// pair where the network pair is expected is a type error.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{RendezvousData, Tagged};
+/// use std::thread;
+///
+/// struct Audio;
+///
+/// fn mix(mut pair: Tagged<RendezvousData<[f32; 4]>, Audio>) {
+///     pair.swap()[0] = 0.5;
+///     pair.swap();
+/// }
+///
+/// let (mut my_rendezvous, their_rendezvous) =
+///     Tagged::<_, Audio>::pair(RendezvousData::new([0.0; 4], [0.0; 4]));
+/// let handle = thread::spawn(move || mix(their_rendezvous));
+/// my_rendezvous.swap();
+/// assert_eq!(my_rendezvous.swap()[0], 0.5);
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct Tagged<H, Tag> {
+    /// The handle
+    inner: H,
+    /// The tag, `fn() -> Tag` so that it does not affect auto traits
+    tag: PhantomData<fn() -> Tag>,
+}
+impl<H, Tag> Tagged<H, Tag> {
+    /// Tag `inner`.
+    #[must_use]
+    #[inline]
+    pub const fn new(inner: H) -> Self {
+        Self {
+            inner,
+            tag: PhantomData,
+        }
+    }
+
+    /// Tag both handles of a pair, as returned by its constructor.
+    #[must_use]
+    #[inline]
+    pub fn pair((first, second): (H, H)) -> (Self, Self) {
+        (Self::new(first), Self::new(second))
+    }
+
+    /// Remove the tag.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> H {
+        self.inner
+    }
+}
+impl<H, Tag> Deref for Tagged<H, Tag> {
+    type Target = H;
+    #[inline]
+    fn deref(&self) -> &H {
+        &self.inner
+    }
+}
+impl<H, Tag> DerefMut for Tagged<H, Tag> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut H {
+        &mut self.inner
+    }
+}
+impl<H: SyncPoint, Tag> SyncPoint for Tagged<H, Tag> {
+    #[inline]
+    fn sync(&mut self) {
+        self.inner.sync();
+    }
+}
+impl<H: SwapPoint, Tag> SwapPoint for Tagged<H, Tag> {
+    type Data = H::Data;
+    #[inline]
+    fn swap(&mut self) -> &mut H::Data {
+        self.inner.swap()
+    }
+}
@@ -0,0 +1,41 @@
+//! Hooks to delay the calling thread's counter stores, for testing
+//!
+//! Lets tests check that the other thread's timeout and probe paths behave,
+//! and that nothing depends on the store at the sync point happening quickly.
+//! Hooks are per thread, so only the side that sets them is delayed.
+
+use core::cell::Cell;
+use core::time::Duration;
+
+crate::os::std::thread_local! {
+    /// Delay before the current thread publishes a new generation
+    static STORE_DELAY: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+/// Sleep for `delay` every time the calling thread is about to publish that
+/// it arrived at a sync point. `None` removes the delay.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{test_hooks, Rendezvous};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+/// let handle = thread::spawn(move || {
+///     test_hooks::delay_stores(Some(Duration::from_millis(10)));
+///     their_rendezvous.wait();
+/// });
+/// my_rendezvous.wait();
+/// # handle.join().unwrap();
+/// ```
+#[inline]
+pub fn delay_stores(delay: Option<Duration>) {
+    STORE_DELAY.with(|cell| cell.set(delay));
+}
+
+/// Called before a new generation is published.
+pub(crate) fn before_store() {
+    if let Some(delay) = STORE_DELAY.with(Cell::get) {
+        crate::os::std::thread::sleep(delay);
+    }
+}
@@ -0,0 +1,179 @@
+//! Reusable test harnesses for components built on this crate
+//!
+//! Each driver runs the two handles of a pair on two threads through a
+//! scenario and panics if the pair misbehaves, so applications can check
+//! their own [`SyncPoint`] and [`SwapPoint`] implementations (or wrappers
+//! around the types of this crate) against the same scenarios the crate
+//! tests itself with.
+
+use crate::os::std::thread;
+use crate::os::std::time::Instant;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+use core::time::Duration;
+
+use crate::{ProbeResult, Rendezvous, SwapPoint, SyncPoint};
+
+/// Run `iterations` syncs on both handles, on two threads, checking that
+/// neither thread ever gets past a sync point before the other arrived.
+/// Returns the handles.
+///
+/// # Panics
+/// If a thread leaves a sync point before the other thread arrived.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{test_kit, Rendezvous};
+///
+/// let (first, second) = Rendezvous::new();
+/// test_kit::exercise_pair(first, second, 1000);
+/// ```
+#[inline]
+pub fn exercise_pair<A, B>(first: A, second: B, iterations: usize) -> (A, B)
+where
+    A: SyncPoint + Send + 'static,
+    B: SyncPoint + Send + 'static,
+{
+    exercise_with_delay(first, second, iterations, Duration::ZERO)
+}
+
+/// [`exercise_pair`], where the second thread sleeps for `delay` before
+/// every sync, to check that nothing depends on the other thread arriving
+/// quickly. Also checks that the first thread actually waited.
+///
+/// # Panics
+/// If a thread leaves a sync point before the other thread arrived.
+#[inline]
+pub fn exercise_slow_partner<A, B>(
+    first: A,
+    second: B,
+    iterations: usize,
+    delay: Duration,
+) -> (A, B)
+where
+    A: SyncPoint + Send + 'static,
+    B: SyncPoint + Send + 'static,
+{
+    let start = Instant::now();
+    let handles = exercise_with_delay(first, second, iterations, delay);
+    let expected = delay.saturating_mul(u32::try_from(iterations).unwrap_or(u32::MAX));
+    assert!(
+        start.elapsed() >= expected,
+        "first thread did not wait for its slow partner"
+    );
+    handles
+}
+
+/// Swap `iterations` times on both handles, on two threads, checking that
+/// every swap returns the value the other thread wrote before its previous
+/// swap, as with [`RendezvousData`](crate::RendezvousData). Returns the
+/// handles.
+///
+/// # Panics
+/// If a swap returns the wrong value.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{test_kit, RendezvousData};
+///
+/// let (first, second) = RendezvousData::new(0, 0);
+/// test_kit::exercise_swap_loop(first, second, 1000);
+/// ```
+#[inline]
+pub fn exercise_swap_loop<A, B>(first: A, second: B, iterations: u64) -> (A, B)
+where
+    A: SwapPoint<Data = u64> + Send + 'static,
+    B: SwapPoint<Data = u64> + Send + 'static,
+{
+    /// Swap loop of one side, the values written by `side` are odd or even.
+    fn swap_loop<S: SwapPoint<Data = u64>>(mut handle: S, side: u64, iterations: u64) -> S {
+        for i in 0..iterations {
+            let data = handle.swap();
+            if i > 0 {
+                let expected = (i - 1) * 2 + (1 - side);
+                assert_eq!(*data, expected, "swap {i} returned the wrong data");
+            }
+            *data = i * 2 + side;
+        }
+        handle
+    }
+    let other = thread::spawn(move || swap_loop(second, 1, iterations));
+    let first = swap_loop(first, 0, iterations);
+    (first, join(other))
+}
+
+/// Check that `probe` reports a partner that stopped syncing as
+/// [`ProbeResult::Unresponsive`] within `timeout`, and one that is waiting
+/// as [`ProbeResult::Waiting`].
+///
+/// # Panics
+/// If a probe returns the wrong result.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{test_kit, Rendezvous};
+/// use std::time::Duration;
+///
+/// let (first, second) = Rendezvous::new();
+/// test_kit::exercise_disconnect(first, second, Duration::from_millis(10));
+/// ```
+#[inline]
+pub fn exercise_disconnect(mut first: Rendezvous, mut second: Rendezvous, timeout: Duration) {
+    let other = thread::spawn(move || {
+        second.wait();
+        // dies without answering probes
+        drop(second);
+    });
+    first.wait();
+    join(other);
+    assert_eq!(first.probe(timeout), ProbeResult::Unresponsive);
+
+    let (mut first, mut second) = Rendezvous::new();
+    let other = thread::spawn(move || second.wait());
+    while first.probe(timeout) != ProbeResult::Waiting {}
+    first.wait();
+    join(other);
+}
+
+/// Shared part of [`exercise_pair`] and [`exercise_slow_partner`].
+fn exercise_with_delay<A, B>(first: A, second: B, iterations: usize, delay: Duration) -> (A, B)
+where
+    A: SyncPoint + Send + 'static,
+    B: SyncPoint + Send + 'static,
+{
+    /// Arrive `iterations` times, checking the arrivals of the other side.
+    fn arrive<S: SyncPoint>(
+        mut handle: S,
+        mine: &AtomicUsize,
+        theirs: &AtomicUsize,
+        iterations: usize,
+        delay: Duration,
+    ) -> S {
+        for i in 1..=iterations {
+            if !delay.is_zero() {
+                thread::sleep(delay);
+            }
+            mine.store(i, Relaxed);
+            handle.sync();
+            let arrived = theirs.load(Relaxed);
+            assert!(
+                arrived >= i,
+                "left sync point {i} before the other thread arrived (it is at {arrived})"
+            );
+        }
+        handle
+    }
+    let arrivals = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+    let other_arrivals = Arc::clone(&arrivals);
+    let other = thread::spawn(move || {
+        let [mine, theirs] = &*other_arrivals;
+        arrive(second, mine, theirs, iterations, delay)
+    });
+    let [theirs, mine] = &*arrivals;
+    let first = arrive(first, mine, theirs, iterations, Duration::ZERO);
+    (first, join(other))
+}
+
+/// Join `handle`, resuming a panic from the other thread on this one.
+fn join<T>(handle: thread::JoinHandle<T>) -> T {
+    handle
+        .join()
+        .unwrap_or_else(|panic| crate::os::std::panic::resume_unwind(panic))
+}
@@ -0,0 +1,249 @@
+//! Recording the generation timeline of a pair, for offline analysis of
+//! phase alignment and jitter between the two threads.
+//!
+//! Each thread wraps its handle in a [`Traced`], which records when the
+//! thread arrived at and left every sync point into a ring allocated up
+//! front, so recording never allocates. After the run, the [`Trace`]s of
+//! both threads are exported together with [`write_csv`] or
+//! [`write_chrome_trace`] (for `chrome://tracing` or
+//! [Perfetto](https://ui.perfetto.dev)).
+//! # Example
+//! ```rust
+//! use rendezvous_swap::trace::{write_csv, Traced};
+//! use rendezvous_swap::Rendezvous;
+//! use std::thread;
+//!
+//! let (my_rendezvous, their_rendezvous) = Rendezvous::new();
+//! let handle = thread::spawn(move || {
+//!     let mut traced = Traced::new(their_rendezvous, 1024);
+//!     for _ in 0..10 {
+//!         traced.wait();
+//!     }
+//!     traced.trace()
+//! });
+//! let mut traced = Traced::new(my_rendezvous, 1024);
+//! for _ in 0..10 {
+//!     traced.wait();
+//! }
+//! let mut csv = Vec::new();
+//! write_csv(&mut csv, &traced.trace(), &handle.join().unwrap()).unwrap();
+//! assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 21);
+//! ```
+
+use crate::os::std::io::{self, Write};
+use crate::os::std::time::Instant;
+use alloc::vec::Vec;
+
+use crate::{SwapPoint, SyncPoint};
+
+/// One sync point passed by one thread.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// Number of syncs of the thread up to and including this one
+    pub generation: u64,
+    /// When the thread arrived at the sync point
+    pub arrived: Instant,
+    /// When the thread left the sync point
+    pub departed: Instant,
+}
+
+/// The events recorded by a [`Traced`], oldest first.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    /// The recorded events, oldest first
+    pub events: Vec<TraceEvent>,
+}
+
+/// A [`SyncPoint`] that records the time of every sync into a ring of the
+/// most recent events.
+#[non_exhaustive]
+pub struct Traced<S> {
+    /// The traced handle
+    inner: S,
+    /// The recorded events
+    ring: Ring,
+}
+
+/// Ring of the most recent events of a [`Traced`].
+struct Ring {
+    /// The events, the oldest is at `generation % capacity` once full
+    events: Vec<TraceEvent>,
+    /// Number of events the ring holds at most
+    capacity: usize,
+    /// Number of syncs so far
+    generation: u64,
+}
+impl Ring {
+    /// Index of the oldest event once the ring is full.
+    fn oldest(&self) -> usize {
+        usize::try_from(self.generation % self.capacity as u64).unwrap_or(0)
+    }
+
+    /// Record a sync that started at `arrived` and just completed.
+    fn record(&mut self, arrived: Instant) {
+        let event = TraceEvent {
+            generation: self.generation.wrapping_add(1),
+            arrived,
+            departed: Instant::now(),
+        };
+        if self.events.len() < self.capacity {
+            self.events.push(event);
+        } else {
+            let oldest = self.oldest();
+            if let Some(slot) = self.events.get_mut(oldest) {
+                *slot = event;
+            }
+        }
+        self.generation = event.generation;
+    }
+}
+
+impl<S: SyncPoint> Traced<S> {
+    /// Trace `inner`, keeping the last `capacity` events.
+    ///
+    /// # Panics
+    /// If `capacity` is zero.
+    #[must_use]
+    #[inline]
+    pub fn new(inner: S, capacity: usize) -> Self {
+        assert!(capacity > 0, "trace needs room for at least one event");
+        Self {
+            inner,
+            ring: Ring {
+                events: Vec::with_capacity(capacity),
+                capacity,
+                generation: 0,
+            },
+        }
+    }
+
+    /// Synchronize execution with the other thread, and record it.
+    #[inline]
+    pub fn wait(&mut self) {
+        let arrived = Instant::now();
+        self.inner.sync();
+        self.ring.record(arrived);
+    }
+
+    /// Number of syncs so far, recorded or not.
+    #[must_use]
+    #[inline]
+    pub const fn generation(&self) -> u64 {
+        self.ring.generation
+    }
+
+    /// Copy out the recorded events, oldest first.
+    #[must_use]
+    #[inline]
+    pub fn trace(&self) -> Trace {
+        let ring = &self.ring;
+        let split = if ring.events.len() < ring.capacity {
+            0
+        } else {
+            ring.oldest()
+        };
+        let (newest, oldest) = ring.events.split_at(split);
+        Trace {
+            events: oldest.iter().chain(newest).copied().collect(),
+        }
+    }
+
+    /// Stop tracing and take the handle back.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+impl<S: SwapPoint> Traced<S> {
+    /// Swap data with the other thread, and record it.
+    #[inline]
+    pub fn swap(&mut self) -> &mut S::Data {
+        let arrived = Instant::now();
+        let data = self.inner.swap();
+        self.ring.record(arrived);
+        data
+    }
+}
+impl<S: SyncPoint> SyncPoint for Traced<S> {
+    #[inline]
+    fn sync(&mut self) {
+        self.wait();
+    }
+}
+impl<S: SwapPoint> SwapPoint for Traced<S> {
+    type Data = S::Data;
+    #[inline]
+    fn swap(&mut self) -> &mut S::Data {
+        Traced::swap(self)
+    }
+}
+
+/// Earliest time in either trace, which becomes time zero of an export.
+fn epoch(first: &Trace, second: &Trace) -> Option<Instant> {
+    first
+        .events
+        .iter()
+        .chain(&second.events)
+        .map(|event| event.arrived)
+        .min()
+}
+
+/// Write the events of both threads as CSV, one row per event with the
+/// columns `thread,generation,arrived_ns,departed_ns`. Times are relative to
+/// the earliest event.
+///
+/// # Errors
+/// If writing fails.
+#[inline]
+pub fn write_csv(out: &mut impl Write, first: &Trace, second: &Trace) -> io::Result<()> {
+    writeln!(out, "thread,generation,arrived_ns,departed_ns")?;
+    let Some(epoch) = epoch(first, second) else {
+        return Ok(());
+    };
+    for (thread, trace) in [first, second].into_iter().enumerate() {
+        for event in &trace.events {
+            writeln!(
+                out,
+                "{thread},{},{},{}",
+                event.generation,
+                event.arrived.duration_since(epoch).as_nanos(),
+                event.departed.duration_since(epoch).as_nanos(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the events of both threads in the Chrome trace event format, each
+/// wait as a complete event on the track of its thread, with the generation
+/// as an argument.
+///
+/// # Errors
+/// If writing fails.
+#[inline]
+pub fn write_chrome_trace(out: &mut impl Write, first: &Trace, second: &Trace) -> io::Result<()> {
+    write!(out, "[")?;
+    if let Some(epoch) = epoch(first, second) {
+        let mut separator = "";
+        for (thread, trace) in [first, second].into_iter().enumerate() {
+            for event in &trace.events {
+                let start = event.arrived.duration_since(epoch);
+                let duration = event.departed.duration_since(event.arrived);
+                write!(
+                    out,
+                    "{separator}\n{{\"name\":\"wait\",\"ph\":\"X\",\"pid\":0,\"tid\":{thread},\"ts\":{}.{:03},\"dur\":{}.{:03},\"args\":{{\"generation\":{}}}}}",
+                    start.as_micros(),
+                    start.subsec_nanos() % 1000,
+                    duration.as_micros(),
+                    duration.subsec_nanos() % 1000,
+                    event.generation,
+                )?;
+                separator = ",";
+            }
+        }
+    }
+    writeln!(out, "\n]")
+}
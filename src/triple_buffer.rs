@@ -0,0 +1,148 @@
+//! Contains [`TripleBuffer`], [`TripleBufferWriter`] and
+//! [`TripleBufferReader`]
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU8, Ordering::AcqRel, Ordering::Relaxed};
+
+use crate::core_cell::SlotCell;
+use crate::padded::Padded;
+
+/// Bits of `TripleBuffer::back` holding the index of the back buffer.
+const INDEX: u8 = 0b011;
+
+/// Bit of `TripleBuffer::back` set when the back buffer was published after
+/// the reader last took a buffer.
+const FRESH: u8 = 0b100;
+
+/// Shared state of a writer and a reader that always gets the most recently
+/// published buffer, created by [`TripleBuffer::new`].
+///
+/// Of the three buffers, the writer owns one, the reader owns one, and the
+/// third (the back buffer) is owned by neither. Publishing exchanges the
+/// buffer of the writer with the back buffer, and the reader exchanges its
+/// buffer with the back buffer when a newer one was published. Both
+/// exchanges are a single atomic swap of the padded back buffer index, so
+/// neither side ever waits for the other: the writer can publish any number
+/// of times between two reads, and the reader skips to the latest one.
+/// Unlike the pairs of this crate, which only use loads and stores, each
+/// publication and each read of a new buffer costs one read-modify-write
+/// atomic.
+///
+/// Buffers are reused, so the buffer the writer gets back after publishing
+/// holds stale data.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::TripleBuffer;
+/// use std::thread;
+///
+/// let (mut writer, mut reader) = TripleBuffer::new(0, 0, 0);
+/// let handle = thread::spawn(move || {
+///     for frame in 1..=100 {
+///         *writer.current() = frame;
+///         writer.publish(); // never waits for the reader
+///     }
+/// });
+/// let mut last = 0;
+/// while last < 100 {
+///     let frame = *reader.latest();
+///     assert!(frame >= last);
+///     last = frame;
+/// }
+/// # handle.join().unwrap();
+/// ```
+#[non_exhaustive]
+pub struct TripleBuffer<T: Send> {
+    /// Index of the back buffer, and [`FRESH`]
+    back: Padded<AtomicU8>,
+    /// Buffer `i` is owned by whichever side holds index `i`
+    buffers: [Padded<SlotCell<T>>; 3],
+}
+impl<T: Send> TripleBuffer<T> {
+    /// Create a linked writer and reader, the writer starts out with
+    /// `data1`, the reader with `data2` and `data3` is the back buffer.
+    ///
+    /// Until the first publication, [`TripleBufferReader::latest`] returns
+    /// `data2`.
+    #[must_use]
+    #[inline]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(data1: T, data2: T, data3: T) -> (TripleBufferWriter<T>, TripleBufferReader<T>) {
+        let shared = Arc::new(Self {
+            back: Padded::new(AtomicU8::new(2)),
+            buffers: [
+                Padded::new(SlotCell::new(data1)),
+                Padded::new(SlotCell::new(data2)),
+                Padded::new(SlotCell::new(data3)),
+            ],
+        });
+        (
+            TripleBufferWriter {
+                index: 0,
+                shared: Arc::clone(&shared),
+            },
+            TripleBufferReader { index: 1, shared },
+        )
+    }
+}
+
+/// Writing half of a [`TripleBuffer`].
+#[non_exhaustive]
+pub struct TripleBufferWriter<T: Send> {
+    /// Buffer owned by the writer
+    index: u8,
+    /// Shared index and buffers
+    shared: Arc<TripleBuffer<T>>,
+}
+impl<T: Send> TripleBufferWriter<T> {
+    /// The buffer to fill before the next [`TripleBufferWriter::publish`].
+    #[inline]
+    pub fn current(&mut self) -> &mut T {
+        // SAFETY:
+        // The writer owns this buffer, it got it from the back buffer index
+        // with acquire ordering, after the reader last released it.
+        unsafe { self.shared.buffers[usize::from(self.index)].get_mut() }
+    }
+
+    /// Make the current buffer the latest one for the reader, without
+    /// waiting, and get the buffer to fill next.
+    #[inline]
+    pub fn publish(&mut self) -> &mut T {
+        let previous = self.shared.back.swap(self.index | FRESH, AcqRel);
+        self.index = previous & INDEX;
+        self.current()
+    }
+}
+
+/// Reading half of a [`TripleBuffer`].
+#[non_exhaustive]
+pub struct TripleBufferReader<T: Send> {
+    /// Buffer owned by the reader
+    index: u8,
+    /// Shared index and buffers
+    shared: Arc<TripleBuffer<T>>,
+}
+impl<T: Send> TripleBufferReader<T> {
+    /// True if a buffer was published since the last
+    /// [`TripleBufferReader::latest`].
+    #[must_use]
+    #[inline]
+    pub fn has_new(&self) -> bool {
+        self.shared.back.load(Relaxed) & FRESH != 0
+    }
+
+    /// The most recently published buffer, or the one returned last time if
+    /// nothing was published since, without waiting.
+    #[inline]
+    pub fn latest(&mut self) -> &mut T {
+        if self.has_new() {
+            // Only the writer sets `FRESH`, so the back buffer stays fresh
+            // until this swap.
+            let previous = self.shared.back.swap(self.index, AcqRel);
+            self.index = previous & INDEX;
+        }
+        // SAFETY:
+        // The reader owns this buffer, it got it from the back buffer index
+        // with acquire ordering, after the writer published it.
+        unsafe { self.shared.buffers[usize::from(self.index)].get_mut() }
+    }
+}
@@ -0,0 +1,148 @@
+//! Contains [`WaitStats`]
+
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64 as AtomicStat;
+#[cfg(not(target_has_atomic = "64"))]
+use core::sync::atomic::AtomicUsize as AtomicStat;
+use core::sync::atomic::Ordering::Relaxed;
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+/// What the waits of one thread at the sync points of its pair cost,
+/// recorded with the `stats` feature, see `wait_stats` and
+/// `peer_wait_stats` on the handles.
+///
+/// The thread that spins and waits less is the bottleneck: when one side
+/// has almost every wait in `immediate_waits`, it is the one the other side
+/// is waiting for. On targets without 64 bit atomics the totals wrap at
+/// `usize::MAX`.
+/// # Example: Find the bottleneck
+/// ```rust
+/// use rendezvous_swap::RendezvousData;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (mut render, mut physics) = RendezvousData::new(0, 0);
+/// let handle = thread::spawn(move || {
+///     for _ in 0..10 {
+///         thread::sleep(Duration::from_millis(1)); // slow step
+///         physics.swap();
+///     }
+///     physics.wait_stats()
+/// });
+/// for _ in 0..10 {
+///     render.swap();
+/// }
+/// let physics = handle.join().unwrap();
+/// let render = render.wait_stats();
+/// assert_eq!(render.waits, 10);
+/// assert!(render.spins > physics.spins);
+/// #[cfg(feature = "std")]
+/// assert!(render.wait_time > physics.wait_time);
+/// ```
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WaitStats {
+    /// Number of completed waits.
+    pub waits: u64,
+    /// Number of waits where the other thread had already arrived, so that
+    /// the thread did not spin at all.
+    pub immediate_waits: u64,
+    /// Total number of times the counter of the other thread was checked
+    /// without it having arrived.
+    pub spins: u64,
+    /// Most spins of a single wait.
+    pub max_spins: u64,
+    /// Total time spent waiting for the other thread.
+    #[cfg(feature = "std")]
+    pub wait_time: Duration,
+    /// Longest single wait.
+    #[cfg(feature = "std")]
+    pub max_wait_time: Duration,
+}
+
+/// The [`WaitStats`] of the owning thread of a counter, written only by that
+/// thread.
+pub(crate) struct WaitRecord {
+    /// See [`WaitStats::waits`]
+    waits: AtomicStat,
+    /// See [`WaitStats::immediate_waits`]
+    immediate_waits: AtomicStat,
+    /// See [`WaitStats::spins`]
+    spins: AtomicStat,
+    /// See [`WaitStats::max_spins`]
+    max_spins: AtomicStat,
+    /// [`WaitStats::wait_time`] in nanoseconds
+    #[cfg(feature = "std")]
+    wait_nanos: AtomicStat,
+    /// [`WaitStats::max_wait_time`] in nanoseconds
+    #[cfg(feature = "std")]
+    max_wait_nanos: AtomicStat,
+}
+impl WaitRecord {
+    /// Nothing recorded yet.
+    pub(crate) const fn new() -> Self {
+        Self {
+            waits: AtomicStat::new(0),
+            immediate_waits: AtomicStat::new(0),
+            spins: AtomicStat::new(0),
+            max_spins: AtomicStat::new(0),
+            #[cfg(feature = "std")]
+            wait_nanos: AtomicStat::new(0),
+            #[cfg(feature = "std")]
+            max_wait_nanos: AtomicStat::new(0),
+        }
+    }
+
+    /// Record a wait that spun `spins` times and took `waited`. Only called
+    /// by the owning thread, so no read-modify-write atomics are needed.
+    #[inline]
+    pub(crate) fn record(&self, spins: u32, #[cfg(feature = "std")] waited: Duration) {
+        add(&self.waits, 1);
+        if spins == 0 {
+            add(&self.immediate_waits, 1);
+        }
+        add(&self.spins, spins.into());
+        max(&self.max_spins, spins.into());
+        #[cfg(feature = "std")]
+        {
+            let nanos = u64::try_from(waited.as_nanos()).unwrap_or(u64::MAX);
+            add(&self.wait_nanos, nanos);
+            max(&self.max_wait_nanos, nanos);
+        }
+    }
+
+    /// Snapshot of the statistics.
+    pub(crate) fn stats(&self) -> WaitStats {
+        WaitStats {
+            waits: get(&self.waits),
+            immediate_waits: get(&self.immediate_waits),
+            spins: get(&self.spins),
+            max_spins: get(&self.max_spins),
+            #[cfg(feature = "std")]
+            wait_time: Duration::from_nanos(get(&self.wait_nanos)),
+            #[cfg(feature = "std")]
+            max_wait_time: Duration::from_nanos(get(&self.max_wait_nanos)),
+        }
+    }
+}
+
+/// Value of `stat`.
+fn get(stat: &AtomicStat) -> u64 {
+    #[allow(clippy::useless_conversion)]
+    u64::try_from(stat.load(Relaxed)).unwrap_or(u64::MAX)
+}
+
+/// Add `value` to `stat`, which only the calling thread writes.
+fn add(stat: &AtomicStat, value: u64) {
+    #[allow(clippy::cast_possible_truncation)]
+    stat.store(stat.load(Relaxed).wrapping_add(value as _), Relaxed);
+}
+
+/// Raise `stat` to `value`, which only the calling thread writes.
+fn max(stat: &AtomicStat, value: u64) {
+    if get(stat) < value {
+        #[allow(clippy::cast_possible_truncation)]
+        stat.store(value as _, Relaxed);
+    }
+}
@@ -0,0 +1,118 @@
+//! Contains [`WaitStrategy`], [`Spin`], [`SpinBackoff`] and [`YieldAfter`]
+
+use core::hint::spin_loop;
+
+/// What a thread does while the other thread has not arrived yet, set for a
+/// pair with `new_with_strategy` (for example
+/// [`Rendezvous::new_with_strategy`](crate::Rendezvous::new_with_strategy)).
+///
+/// Without a strategy, waits spin and fall back to yielding (or parking)
+/// once spinning stops paying off, see
+/// [`ContentionStats`](crate::ContentionStats). A strategy replaces that
+/// heuristic entirely, for tuning the waits to a known machine: spinning is
+/// best on a dedicated core, and terrible when there are more threads than
+/// cores. Waits that give up ([`Rendezvous::wait_checked`](crate::Rendezvous::wait_checked))
+/// use the strategy as well, timed waits keep yielding so they can notice
+/// the deadline.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::{Rendezvous, WaitStrategy};
+/// use std::thread;
+///
+/// /// Always yield to the OS scheduler, for an oversubscribed machine.
+/// #[derive(Clone)]
+/// struct AlwaysYield;
+/// impl WaitStrategy for AlwaysYield {
+///     fn pause(&self, _spins: u32) {
+///         thread::yield_now();
+///     }
+/// }
+///
+/// let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new_with_strategy(AlwaysYield);
+/// let handle = thread::spawn(move || their_rendezvous.wait());
+/// my_rendezvous.wait();
+/// # handle.join().unwrap();
+/// ```
+pub trait WaitStrategy: Send + Sync {
+    /// Called after the `spins`:th check (counting from 1) of a wait found
+    /// that the other thread has not arrived, before checking again.
+    fn pause(&self, spins: u32);
+}
+
+/// [`WaitStrategy`] that only spins, for threads on dedicated cores.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Spin;
+impl WaitStrategy for Spin {
+    #[inline]
+    fn pause(&self, _spins: u32) {
+        spin_loop();
+    }
+}
+
+/// [`WaitStrategy`] that spins with exponential backoff: pause instructions
+/// double with every check, up to `max_pauses` per check, which leaves the
+/// cache line of the counter (and a hyperthreading sibling) alone during
+/// long waits.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpinBackoff {
+    /// Maximum number of pause instructions between two checks
+    pub max_pauses: u32,
+}
+impl SpinBackoff {
+    /// Backoff up to `max_pauses` pause instructions between two checks.
+    #[must_use]
+    #[inline]
+    pub const fn new(max_pauses: u32) -> Self {
+        Self { max_pauses }
+    }
+}
+impl Default for SpinBackoff {
+    /// Up to 64 pause instructions between two checks.
+    #[inline]
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+impl WaitStrategy for SpinBackoff {
+    #[inline]
+    fn pause(&self, spins: u32) {
+        let pauses = 1_u32
+            .checked_shl(spins.saturating_sub(1))
+            .map_or(self.max_pauses, |pauses| pauses.min(self.max_pauses));
+        for _ in 0..pauses.max(1) {
+            spin_loop();
+        }
+    }
+}
+
+/// [`WaitStrategy`] that spins for `spins` checks, and then yields to the OS
+/// scheduler after every check, for machines with more threads than cores.
+#[cfg(feature = "std")]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct YieldAfter {
+    /// Number of checks to spin for before yielding
+    pub spins: u32,
+}
+#[cfg(feature = "std")]
+impl YieldAfter {
+    /// Spin for `spins` checks before yielding.
+    #[must_use]
+    #[inline]
+    pub const fn new(spins: u32) -> Self {
+        Self { spins }
+    }
+}
+#[cfg(feature = "std")]
+impl WaitStrategy for YieldAfter {
+    #[inline]
+    fn pause(&self, spins: u32) {
+        if spins <= self.spins {
+            spin_loop();
+        } else {
+            crate::os::yield_now();
+        }
+    }
+}
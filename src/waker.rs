@@ -0,0 +1,86 @@
+//! Contains [`RendezvousCompleter`], and wakers that arrive at a sync point, see
+//! [`Rendezvous::waker`](crate::Rendezvous::waker)
+
+use alloc::sync::Arc;
+use alloc::task::Wake;
+use core::marker::PhantomData;
+use core::task::Waker;
+
+use crate::counter::CounterPair;
+use crate::strict::Op;
+
+/// Arrival of one side at one generation, performed by waking.
+struct Arrival {
+    /// Counters of the pair
+    counters: Arc<CounterPair>,
+    /// True if the first handle of the pair arrives
+    first: bool,
+    /// Generation the side is at before arriving
+    generation: usize,
+}
+impl Wake for Arrival {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // Only handles that do nothing but wait have wakers, so any other
+        // generation this might tag is also a wait.
+        let counter = &self.counters[usize::from(!self.first)];
+        counter.tag(self.generation.wrapping_add(1), Op::Wait);
+        counter.advance_from(self.generation);
+    }
+}
+
+/// Waker that advances the counter of the `first` (or second) handle in
+/// `counters` from `generation` when woken.
+pub(crate) fn arrival_waker(counters: Arc<CounterPair>, first: bool, generation: usize) -> Waker {
+    Waker::from(Arc::new(Arrival {
+        counters,
+        first,
+        generation,
+    }))
+}
+
+/// Marks the arrival of one side at one sync point, from wherever it is
+/// handed to, created by [`RendezvousDataN::arrive_later`](crate::RendezvousDataN::arrive_later).
+///
+/// Completing (or dropping) it arrives on behalf of the owner of the handle,
+/// so the other thread can continue. Can be sent to another thread, or moved
+/// into a callback (signal handler, audio callback, GUI event).
+///
+/// It borrows the handle like the [`PendingSwap`](crate::PendingSwap) it
+/// came with, so the handle cannot be used again before the completer has
+/// arrived, even if the [`PendingSwap`](crate::PendingSwap) is dropped.
+#[non_exhaustive]
+pub struct RendezvousCompleter<'handle> {
+    /// Arrives when woken
+    waker: Option<Waker>,
+    /// Borrow of the handle
+    handle: PhantomData<&'handle mut ()>,
+}
+impl RendezvousCompleter<'_> {
+    /// Completer that arrives by waking `waker`.
+    pub(crate) const fn new(waker: Waker) -> Self {
+        Self {
+            waker: Some(waker),
+            handle: PhantomData,
+        }
+    }
+
+    /// Arrive at the sync point on behalf of the owner of the handle.
+    #[inline]
+    pub fn complete(mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+impl Drop for RendezvousCompleter<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
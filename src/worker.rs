@@ -0,0 +1,127 @@
+//! Contains [`ComputeWorker`], a request/response façade over a pair of
+//! [`RendezvousData`]
+
+use crate::os::std::thread::{self, JoinHandle};
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering::Relaxed};
+
+use crate::RendezvousData;
+
+/// A worker thread that computes a response for every request, using the
+/// "ping-pong compute server" pattern.
+///
+/// The caller fills in [`ComputeWorker::request`] and calls
+/// [`ComputeWorker::call`], which hands the request to the worker, waits for
+/// the worker closure to run and returns its response. Requests and
+/// responses are swapped with a [`RendezvousData`] each, so buffers are
+/// reused and nothing is allocated per call: the request buffer returned
+/// after a call holds an old request, and the worker closure gets the
+/// response buffer it filled two calls ago.
+///
+/// Dropping the [`ComputeWorker`] stops the worker thread and joins it.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::worker::ComputeWorker;
+///
+/// let mut worker = ComputeWorker::spawn(|request: &mut Vec<u32>, response: &mut u32| {
+///     *response = request.iter().sum();
+/// });
+/// for n in 1..4 {
+///     let request = worker.request();
+///     request.clear();
+///     request.extend(0..n);
+///     let sum = *worker.call();
+///     assert_eq!(sum, (0..n).sum());
+/// }
+/// ```
+#[non_exhaustive]
+pub struct ComputeWorker<Req: Send, Resp: Send> {
+    /// Requests to the worker
+    requests: RendezvousData<Req>,
+    /// Responses from the worker
+    responses: RendezvousData<Resp>,
+    /// Set before the last request, to stop the worker
+    stop: Arc<AtomicBool>,
+    /// The worker thread, joined on drop
+    thread: Option<JoinHandle<()>>,
+}
+impl<Req, Resp> ComputeWorker<Req, Resp>
+where
+    Req: Send + Default + 'static,
+    Resp: Send + Default + 'static,
+{
+    /// Spawn a worker thread running `f` for every request, with default
+    /// initialized buffers.
+    #[must_use]
+    #[inline]
+    pub fn spawn<F>(f: F) -> Self
+    where
+        F: FnMut(&mut Req, &mut Resp) + Send + 'static,
+    {
+        Self::spawn_with(
+            (Req::default(), Req::default()),
+            (Resp::default(), Resp::default()),
+            f,
+        )
+    }
+}
+impl<Req, Resp> ComputeWorker<Req, Resp>
+where
+    Req: Send + 'static,
+    Resp: Send + 'static,
+{
+    /// Spawn a worker thread running `f` for every request, with the given
+    /// initial buffers.
+    #[must_use]
+    #[inline]
+    pub fn spawn_with<F>(requests: (Req, Req), responses: (Resp, Resp), mut f: F) -> Self
+    where
+        F: FnMut(&mut Req, &mut Resp) + Send + 'static,
+    {
+        let (requests, mut worker_requests) = RendezvousData::new(requests.0, requests.1);
+        let (responses, mut worker_responses) = RendezvousData::new(responses.0, responses.1);
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker_stop = Arc::clone(&stop);
+        let thread = thread::spawn(move || loop {
+            let request = worker_requests.swap();
+            // The swap synchronized with the store in `drop`.
+            if worker_stop.load(Relaxed) {
+                break;
+            }
+            f(request, worker_responses.current_mut());
+            worker_responses.swap();
+        });
+        Self {
+            requests,
+            responses,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Get a mutable reference to the request buffer, to be filled in before
+    /// [`ComputeWorker::call`].
+    #[must_use]
+    #[inline]
+    pub fn request(&mut self) -> &mut Req {
+        self.requests.current_mut()
+    }
+
+    /// Send the request to the worker and wait for the response.
+    #[inline]
+    pub fn call(&mut self) -> &mut Resp {
+        self.requests.swap();
+        self.responses.swap()
+    }
+}
+impl<Req: Send, Resp: Send> Drop for ComputeWorker<Req, Resp> {
+    #[inline]
+    fn drop(&mut self) {
+        self.stop.store(true, Relaxed);
+        self.requests.swap();
+        if let Some(thread) = self.thread.take() {
+            // A panic in the worker closure has already been reported.
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Contains [`Zeroable`]
+
+use core::mem::MaybeUninit;
+use core::num::Wrapping;
+use core::sync::atomic::{
+    AtomicBool, AtomicI16, AtomicI32, AtomicI8, AtomicIsize, AtomicU16, AtomicU32, AtomicU8,
+    AtomicUsize,
+};
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicI64, AtomicU64};
+
+/// Types for which all zero bytes is a valid value, so buffers of them can
+/// be created zeroed by the allocator, see
+/// [`RendezvousDataN::new_zeroed`](crate::RendezvousDataN::new_zeroed).
+///
+/// Implemented for the integer, float and atomic types, `bool`, `char`,
+/// and arrays and [`Wrapping`] of them.
+///
+/// # Safety
+/// A value whose bytes are all zero must be a valid value of the type.
+/// # Example
+/// ```rust
+/// use rendezvous_swap::Zeroable;
+///
+/// struct Particle {
+///     position: [f32; 3],
+///     velocity: [f32; 3],
+/// }
+/// // SAFETY: all fields are zeroable
+/// unsafe impl Zeroable for Particle {}
+/// ```
+pub unsafe trait Zeroable {}
+
+/// Implement [`Zeroable`] for types that are valid when zeroed.
+macro_rules! zeroable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            // SAFETY:
+            // Zero is a valid value of the type.
+            unsafe impl Zeroable for $ty {}
+        )*
+    };
+}
+zeroable!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    AtomicBool,
+    AtomicU8,
+    AtomicU16,
+    AtomicU32,
+    AtomicUsize,
+    AtomicI8,
+    AtomicI16,
+    AtomicI32,
+    AtomicIsize,
+);
+#[cfg(target_has_atomic = "64")]
+zeroable!(AtomicU64, AtomicI64);
+
+// SAFETY:
+// An array of zeroable elements is zeroable.
+unsafe impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
+// SAFETY:
+// `Wrapping` has the layout of `T`.
+unsafe impl<T: Zeroable> Zeroable for Wrapping<T> {}
+// SAFETY:
+// Any bytes are a valid `MaybeUninit`.
+unsafe impl<T> Zeroable for MaybeUninit<T> {}
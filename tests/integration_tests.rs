@@ -1,3 +1,7 @@
+// Apart from the pairs on borrowed storage, everything tested here needs
+// `alloc`.
+#![cfg(feature = "alloc")]
+
 #[test]
 fn test_rendezvous() {
     use rendezvous_swap::Rendezvous;
@@ -60,3 +64,3250 @@ fn test_rendezvous_data_repeat() {
 
     handle.join().unwrap();
 }
+
+// the closure gets the received data, generically through `SwapPoint` too
+#[test]
+fn test_swap_with() {
+    use rendezvous_swap::{RendezvousData, SwapPoint};
+    use std::thread;
+
+    fn increment(point: &mut impl SwapPoint<Data = usize>) -> usize {
+        point.swap_with(|data| {
+            *data += 1;
+            *data
+        })
+    }
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 10);
+    let handle = thread::spawn(move || (0..4).map(|_| increment(&mut their_rendezvous)).sum());
+    let mine: usize = (0..4)
+        .map(|_| my_rendezvous.swap_with(|data| core::mem::replace(data, 0)))
+        .sum();
+    // one thread increments what the other resets
+    assert_eq!(
+        (mine, handle.join().unwrap()),
+        (10 + 1 + 1 + 1, 1 + 1 + 1 + 1)
+    );
+}
+
+// more threads than cores makes spinning counterproductive, which should be
+// visible in the stats
+#[cfg(feature = "std")]
+#[test]
+fn test_contention_stats() {
+    const ITERATIONS: usize = 200;
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let handle = thread::spawn(move || {
+        for _ in 0..ITERATIONS {
+            // partner arrives late, so the other side spins through its budget
+            thread::sleep(std::time::Duration::from_millis(1));
+            their_rendezvous.wait();
+        }
+    });
+    for _ in 0..ITERATIONS {
+        my_rendezvous.wait();
+    }
+    let stats = my_rendezvous.contention_stats();
+    assert_eq!(stats.waits, ITERATIONS as u64);
+    assert!(stats.contended_waits > 0);
+    assert!(stats.yields > 0);
+    assert!(stats.is_degraded());
+
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "parking")]
+#[test]
+fn test_parking() {
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let handle = thread::spawn(move || {
+        for _ in 0..3 {
+            thread::sleep(Duration::from_millis(30));
+            their_rendezvous.wait();
+        }
+    });
+    for _ in 0..3 {
+        my_rendezvous.wait();
+    }
+    handle.join().unwrap();
+    let stats = my_rendezvous.contention_stats();
+    assert_eq!(stats.contended_waits, 3);
+    // parked until woken, instead of yielding thousands of times
+    assert!(stats.yields < 100, "{stats:?}");
+}
+
+// Runs with either counter implementation, compare `cargo test test_litmus`
+// with `cargo test test_litmus --features explicit-fences`.
+#[test]
+fn test_litmus() {
+    use rendezvous_swap::Rendezvous;
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    use std::sync::Arc;
+    use std::thread;
+
+    const ITERATIONS: usize = 20_000;
+
+    // Each thread stores `i` to its variable before sync `2i - 1`, and reads
+    // the other variable between syncs `2i - 1` and `2i`. The read must see
+    // exactly `i`: not less (message passing, store buffering: the store is
+    // before the sync) and not more (load buffering: the next store is after
+    // sync `2i`, which needs this thread to arrive).
+    fn run(mine: &AtomicUsize, theirs: &AtomicUsize, rendezvous: &mut Rendezvous) -> usize {
+        let mut violations = 0;
+        for i in 1..=ITERATIONS {
+            mine.store(i, Relaxed);
+            rendezvous.wait();
+            if theirs.load(Relaxed) != i {
+                violations += 1;
+            }
+            rendezvous.wait();
+        }
+        violations
+    }
+
+    let x = Arc::new(AtomicUsize::new(0));
+    let y = Arc::new(AtomicUsize::new(0));
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let handle = {
+        let (x, y) = (Arc::clone(&x), Arc::clone(&y));
+        thread::spawn(move || run(&y, &x, &mut their_rendezvous))
+    };
+    let violations = run(&x, &y, &mut my_rendezvous);
+    assert_eq!((violations, handle.join().unwrap()), (0, 0));
+}
+
+// the fast side must be exactly `RATIO` waits ahead at every sync
+#[test]
+fn test_rendezvous_ratio() {
+    const ITERATIONS: usize = 1000;
+    const RATIO: usize = 4;
+    use core::num::NonZeroUsize;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use rendezvous_swap::Rendezvous;
+    use std::sync::Arc;
+    use std::thread;
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let (mut fast, mut slow) = Rendezvous::new_ratio(NonZeroUsize::new(RATIO).unwrap());
+    let fast_count = Arc::clone(&count);
+    let handle = thread::spawn(move || {
+        for _ in 0..ITERATIONS * RATIO {
+            fast_count.fetch_add(1, Ordering::Relaxed);
+            fast.wait();
+        }
+    });
+    for i in 1..=ITERATIONS {
+        slow.wait();
+        let count = count.load(Ordering::Relaxed);
+        assert!((i * RATIO..=(i + 1) * RATIO).contains(&count));
+    }
+
+    handle.join().unwrap();
+}
+
+// a slow side catches up several generations in each call to `wait_n`
+#[test]
+fn test_rendezvous_wait_n() {
+    const ITERATIONS: usize = 100;
+    const RATIO: usize = 4;
+    const CATCH_UP: usize = 3;
+    use core::num::NonZeroUsize;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use rendezvous_swap::Rendezvous;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let (mut fast, mut slow) = Rendezvous::new_ratio(NonZeroUsize::new(RATIO).unwrap());
+    let fast_count = Arc::clone(&count);
+    let handle = thread::spawn(move || {
+        for _ in 0..ITERATIONS * CATCH_UP {
+            fast_count.fetch_add(RATIO, Ordering::Relaxed);
+            fast.wait_n(RATIO);
+        }
+    });
+    for i in 1..=ITERATIONS {
+        if i % 10 == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        slow.wait_n(CATCH_UP);
+        let synced = i * CATCH_UP;
+        let count = count.load(Ordering::Relaxed);
+        assert!((synced * RATIO..=(synced + 1) * RATIO).contains(&count));
+    }
+
+    handle.join().unwrap();
+}
+
+// sub-barrier phases are aligned within each generation
+#[test]
+fn test_sub_rendezvous() {
+    const ITERATIONS: usize = 100;
+    const PHASES: usize = 3;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use rendezvous_swap::Rendezvous;
+    use std::sync::Arc;
+    use std::thread;
+
+    let phase = Arc::new(AtomicUsize::new(0));
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let their_phase = Arc::clone(&phase);
+    let handle = thread::spawn(move || {
+        for _ in 0..ITERATIONS {
+            let mut sub = their_rendezvous.sub();
+            for _ in 0..PHASES {
+                their_phase.fetch_add(1, Ordering::Relaxed);
+                sub.wait();
+                sub.wait();
+            }
+            their_rendezvous.wait();
+        }
+    });
+    for i in 0..ITERATIONS {
+        let mut sub = my_rendezvous.sub();
+        for j in 1..=PHASES {
+            sub.wait();
+            assert_eq!(phase.load(Ordering::Relaxed), i * PHASES + j);
+            sub.wait();
+        }
+        my_rendezvous.wait();
+    }
+
+    handle.join().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "sub-barrier misuse")]
+fn test_sub_rendezvous_misuse() {
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    thread::spawn(move || {
+        their_rendezvous.wait();
+    });
+    my_rendezvous.sub().wait();
+}
+
+#[cfg(feature = "bench")]
+#[test]
+fn test_measure_pair() {
+    let report = rendezvous_swap::bench::measure_pair_with(100);
+    assert_eq!(report.samples, 100);
+    assert!(report.wait.p50 <= report.wait.p99);
+    assert!(report.swap.p50 <= report.swap.p99);
+}
+
+#[cfg(feature = "bench")]
+#[test]
+fn test_time_pair() {
+    use rendezvous_swap::bench::time_pair;
+    use rendezvous_swap::{FailoverRendezvous, RendezvousData};
+
+    let swaps = time_pair(
+        1000,
+        || RendezvousData::new([0_u64; 8], [0; 8]),
+        |rendezvous| rendezvous.swap()[0] += 1,
+    );
+    assert!(swaps > std::time::Duration::ZERO);
+    let _ = time_pair(1000, FailoverRendezvous::new, FailoverRendezvous::wait);
+}
+
+#[test]
+fn test_observer() {
+    const ITERATIONS: usize = 200;
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let observer = my_rendezvous.observer();
+    let start = observer.observe();
+    let handle = thread::spawn(move || {
+        for _ in 0..ITERATIONS {
+            their_rendezvous.wait();
+        }
+    });
+    for _ in 0..ITERATIONS {
+        my_rendezvous.wait();
+        // the observer only ever sees completed generations
+        assert!(observer.observe().generations_since(&start) <= ITERATIONS);
+    }
+    handle.join().unwrap();
+    assert_eq!(observer.observe().generations_since(&start), ITERATIONS);
+}
+
+// the same generic code runs with or without a second thread
+#[test]
+fn test_single_thread() {
+    use rendezvous_swap::{RendezvousData, SingleThread, SwapPoint};
+    use std::thread;
+
+    fn produce(point: &mut impl SwapPoint<Data = usize>, ticks: usize) -> usize {
+        let mut last = 0;
+        for i in 1..=ticks {
+            let data = point.swap();
+            last = *data;
+            *data = i;
+        }
+        last
+    }
+    let mut single = SingleThread::new(0, 0);
+    assert_eq!(produce(&mut single, 10), 8);
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || produce(&mut their_rendezvous, 10));
+    assert_eq!(produce(&mut my_rendezvous, 10), 9);
+    assert_eq!(handle.join().unwrap(), 9);
+}
+
+// one side is replaced by a new thread while paused
+#[test]
+fn test_pause_resume() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (my_rendezvous, their_rendezvous) = RendezvousData::new(0, 0);
+    let worker = thread::spawn(move || {
+        let mut paused = their_rendezvous.pause();
+        *paused.get_mut() = 1;
+        paused
+    });
+    let mut paused = my_rendezvous.pause();
+    *paused.get_mut() = 7;
+    let their_paused = worker.join().unwrap();
+
+    let worker = thread::spawn(move || {
+        let mut their_rendezvous = their_paused.resume();
+        assert_eq!(*their_rendezvous.swap(), 1);
+    });
+    let mut my_rendezvous = paused.resume();
+    assert_eq!(*my_rendezvous.swap(), 7);
+    worker.join().unwrap();
+}
+
+// the first handle reinitializes before the second, each sees both buffers
+#[test]
+fn test_paused_reinit() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (my_rendezvous, their_rendezvous) = RendezvousData::new(vec![1], vec![2]);
+    let handle = thread::spawn(move || {
+        let mut paused = their_rendezvous.pause();
+        // SAFETY: both threads call `reinit` before resuming
+        unsafe {
+            paused.reinit(|mine, theirs| {
+                assert_eq!(theirs, &[2, 3]);
+                mine.push(4);
+            });
+        }
+        paused.resume().swap().clone()
+    });
+    let mut paused = my_rendezvous.pause();
+    // SAFETY: both threads call `reinit` before resuming
+    unsafe {
+        paused.reinit(|mine, theirs| {
+            assert_eq!(theirs, &[1]);
+            mine.push(3);
+        });
+    }
+    // resuming swaps once more
+    assert_eq!(paused.resume().swap(), &[2, 3]);
+    assert_eq!(handle.join().unwrap(), &[1, 4]);
+}
+
+#[test]
+fn test_counter_ptrs() {
+    use core::sync::atomic::Ordering;
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let (mine, theirs) = my_rendezvous.counter_ptrs();
+    assert_eq!(their_rendezvous.counter_ptrs(), (theirs, mine));
+    let handle = thread::spawn(move || {
+        their_rendezvous.wait();
+        // dropping it would arrive at the next sync point
+        their_rendezvous
+    });
+    my_rendezvous.wait();
+    let _their_rendezvous = handle.join().unwrap();
+    // SAFETY: a handle is still alive, and the counters are only read
+    unsafe {
+        assert_eq!((*mine).load(Ordering::Acquire), 1);
+        assert_eq!((*theirs).load(Ordering::Acquire), 1);
+    }
+}
+
+#[cfg(all(feature = "notify", unix))]
+#[test]
+fn test_notify_bridge() {
+    const ITERATIONS: usize = 100;
+    use rendezvous_swap::notify::bridge;
+    use rendezvous_swap::{RendezvousData, SwapPoint};
+    use std::thread;
+
+    let (mut event_loop_side, compute_side) = RendezvousData::new(0, 0);
+    let (mut compute_side, notifier) = bridge(compute_side).unwrap();
+    assert!(!notifier.is_signaled());
+    let handle = thread::spawn(move || {
+        for i in 1..=ITERATIONS {
+            *compute_side.swap() = i;
+        }
+    });
+    for i in 1..=ITERATIONS {
+        while !notifier.is_signaled() {
+            thread::yield_now();
+        }
+        notifier.clear();
+        assert_eq!(*event_loop_side.swap(), i - 1);
+    }
+    handle.join().unwrap();
+    assert!(!notifier.is_signaled());
+}
+
+#[cfg(all(feature = "mio", unix))]
+#[test]
+fn test_notify_mio() {
+    use mio::{Events, Interest, Poll, Token};
+    use rendezvous_swap::notify::bridge;
+    use rendezvous_swap::{Rendezvous, SyncPoint};
+    use std::thread;
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(4);
+    let (mut event_loop_side, compute_side) = Rendezvous::new();
+    let (mut compute_side, mut notifier) = bridge(compute_side).unwrap();
+    poll.registry()
+        .register(&mut notifier, Token(7), Interest::READABLE)
+        .unwrap();
+    let handle = thread::spawn(move || compute_side.sync());
+    poll.poll(&mut events, None).unwrap();
+    assert_eq!(events.iter().next().unwrap().token(), Token(7));
+    notifier.clear();
+    event_loop_side.wait();
+    handle.join().unwrap();
+}
+
+#[cfg(all(feature = "tokio", unix))]
+#[test]
+fn test_tokio_rendezvous() {
+    const ITERATIONS: usize = 100;
+    use rendezvous_swap::notify::{bridge, TokioRendezvous};
+    use rendezvous_swap::{RendezvousData, SwapPoint};
+    use std::thread;
+
+    let (async_side, compute_side) = RendezvousData::new(0, 0);
+    let (mut compute_side, notifier) = bridge(compute_side).unwrap();
+    let handle = thread::spawn(move || {
+        for i in 1..=ITERATIONS {
+            *compute_side.swap() = i;
+        }
+    });
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+    runtime.block_on(async move {
+        let mut async_side = TokioRendezvous::new(async_side, notifier).unwrap();
+        for i in 1..=ITERATIONS {
+            assert_eq!(*async_side.swap().await.unwrap(), i - 1);
+        }
+    });
+    handle.join().unwrap();
+}
+
+// commands are handled while waiting for the other thread
+#[cfg(feature = "std")]
+#[test]
+fn test_select2() {
+    use rendezvous_swap::select::{select2, Selected};
+    use rendezvous_swap::Rendezvous;
+    use std::sync::mpsc::channel;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let (sender, receiver) = channel();
+    let handle = thread::spawn(move || {
+        for i in 0..10 {
+            sender.send(i).unwrap();
+        }
+        their_rendezvous.wait();
+    });
+    let mut received = Vec::new();
+    while let Selected::Message(i) = select2(&mut my_rendezvous, &receiver) {
+        received.push(i);
+    }
+    // all messages were sent before the other thread arrived
+    assert_eq!(received, (0..10).collect::<Vec<_>>());
+    handle.join().unwrap();
+}
+
+// every worker is served once per round, in whatever order they arrive, and
+// the handles of workers that have not arrived are left alone
+#[cfg(feature = "std")]
+#[test]
+fn test_select() {
+    use rendezvous_swap::select::select;
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    const WORKERS: usize = 3;
+    const ROUNDS: usize = 200;
+    let (mut coordinator, workers): (Vec<_>, Vec<_>) =
+        (0..WORKERS).map(|_| Rendezvous::new()).unzip();
+    let handles: Vec<_> = workers
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut worker)| {
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    for _ in 0..index * 100 {
+                        std::hint::spin_loop();
+                    }
+                    worker.wait();
+                }
+            })
+        })
+        .collect();
+    for round in 1..=ROUNDS {
+        // served workers are removed, they may already be waiting for the
+        // next round
+        let mut pending: Vec<_> = coordinator.iter_mut().collect();
+        while !pending.is_empty() {
+            let index = select(&mut pending);
+            assert_eq!(pending.remove(index).generation(), round);
+            assert!(pending
+                .iter()
+                .all(|handle| handle.generation() == round - 1));
+        }
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_probe() {
+    use rendezvous_swap::{ProbeResult, Rendezvous};
+    use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let (sender, receiver) = channel();
+    let handle = thread::spawn(move || {
+        // busy, but answering probes
+        while receiver.try_recv().is_err() {
+            their_rendezvous.answer_probes();
+            thread::yield_now();
+        }
+        their_rendezvous.wait();
+        // dead
+        thread::sleep(Duration::from_millis(100));
+        drop(their_rendezvous);
+    });
+    let timeout = Duration::from_secs(10);
+    assert_eq!(my_rendezvous.probe(timeout), ProbeResult::Alive);
+    sender.send(()).unwrap();
+    while my_rendezvous.probe(timeout) != ProbeResult::Waiting {}
+    my_rendezvous.wait();
+    assert_eq!(
+        my_rendezvous.probe(Duration::from_millis(1)),
+        ProbeResult::Unresponsive
+    );
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "count-atomics")]
+#[test]
+fn test_count_atomics() {
+    use rendezvous_swap::{atomic_counts, RendezvousData};
+    use std::sync::atomic::Ordering;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        for _ in 0..100 {
+            their_rendezvous.swap();
+        }
+    });
+    let (_, theirs) = my_rendezvous.counter_ptrs();
+    for generation in 1..=100 {
+        // SAFETY: the pointer is valid while `my_rendezvous` is alive
+        while unsafe { &*theirs }.load(Ordering::Acquire) != generation {}
+        let _ = atomic_counts::take();
+        my_rendezvous.swap();
+        let counts = atomic_counts::take();
+        assert_eq!(counts.stores, 1);
+        assert_eq!(counts.loads, 1);
+        assert_eq!(counts.read_modify_writes, 0);
+    }
+    handle.join().unwrap();
+}
+
+// a bursty producer runs ahead by up to `pool size - 2` buffers, and the
+// consumer sees every buffer in order
+#[test]
+fn test_batched() {
+    use rendezvous_swap::BatchProducer;
+    use std::thread;
+
+    const POOL: usize = 5;
+    let (mut producer, mut consumer) = BatchProducer::new(vec![0; POOL]);
+    let handle = thread::spawn(move || {
+        for i in 1..=1000 {
+            *producer.current() = i;
+            producer.publish();
+            assert!(producer.queued() <= POOL - 2);
+        }
+        producer
+    });
+    for i in 1..=1000 {
+        assert_eq!(*consumer.receive(), i);
+    }
+    let mut producer = handle.join().unwrap();
+    assert_eq!(consumer.try_receive(), None);
+
+    // burst without the consumer
+    for i in 1..=(POOL - 2) {
+        *producer.current() = i;
+        producer.publish();
+    }
+    assert_eq!(consumer.queued(), POOL - 2);
+    for i in 1..=(POOL - 2) {
+        assert_eq!(consumer.try_receive().copied(), Some(i));
+    }
+}
+
+#[test]
+fn test_new_zeroed_large() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    const LEN: usize = 1 << 16;
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::<[u32; LEN]>::new_zeroed();
+    let handle = thread::spawn(move || {
+        let data = their_rendezvous.swap();
+        assert!(data.iter().all(|&x| x == 0));
+        data[LEN - 1] = 7;
+        their_rendezvous.swap();
+    });
+    assert!(my_rendezvous.swap().iter().all(|&x| x == 0));
+    assert_eq!(my_rendezvous.swap()[LEN - 1], 7);
+    handle.join().unwrap();
+}
+
+// the address of pinned data stays the same across swaps
+#[test]
+fn test_pinned_rendezvous_data() {
+    use core::marker::PhantomPinned;
+    use rendezvous_swap::PinnedRendezvousData;
+    use std::thread;
+
+    struct NoMove(PhantomPinned);
+    let (mut my_rendezvous, mut their_rendezvous) =
+        PinnedRendezvousData::new(NoMove(PhantomPinned), NoMove(PhantomPinned));
+    let handle = thread::spawn(move || {
+        let mut addresses = Vec::new();
+        for _ in 0..4 {
+            addresses.push(&*their_rendezvous.swap_pinned() as *const NoMove as usize);
+        }
+        addresses
+    });
+    let mut addresses = Vec::new();
+    for _ in 0..4 {
+        addresses.push(&*my_rendezvous.swap_pinned() as *const NoMove as usize);
+    }
+    let theirs = handle.join().unwrap();
+    assert_eq!(addresses[0], theirs[1]);
+    assert_eq!(addresses[1], theirs[0]);
+    assert_eq!(addresses[0], addresses[2]);
+    assert_eq!(addresses[1], addresses[3]);
+}
+
+/// Executable model of the generation protocol.
+///
+/// Each thread owns a counter that only it writes. To wait, a thread at
+/// generation `g` stores `g + 1` to its counter and then spins until the
+/// other counter is not `g`, after which it is at generation `g + 1`.
+/// All interleavings of two threads doing `WAITS` waits each are enumerated,
+/// and the implementation is checked against the reachable states.
+mod model {
+    use std::collections::{BTreeSet, HashSet};
+
+    const WAITS: usize = 4;
+
+    /// Program counter of one thread in the model.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+    enum Pc {
+        /// About to store `generation + 1`
+        Store,
+        /// Spinning on the other counter
+        Spin,
+        /// Done with all waits
+        Done,
+    }
+
+    /// One thread: program counter, local generation and published counter.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+    struct Thread {
+        pc: Pc,
+        generation: usize,
+        counter: usize,
+    }
+
+    type State = [Thread; 2];
+
+    /// Advance thread `i`, or `None` if it is blocked or done.
+    /// Also returns the (generation, other counter) pair observed when a
+    /// wait completes.
+    fn step(state: State, i: usize) -> Option<(State, Option<(usize, usize)>)> {
+        let mut next = state;
+        let other = state[1 - i].counter;
+        let me = &mut next[i];
+        match me.pc {
+            Pc::Store => {
+                me.counter = me.generation + 1;
+                me.pc = Pc::Spin;
+                Some((next, None))
+            },
+            Pc::Spin if other != me.generation => {
+                me.generation += 1;
+                me.pc = if me.generation == WAITS {
+                    Pc::Done
+                } else {
+                    Pc::Store
+                };
+                let observed = (me.generation, other);
+                Some((next, Some(observed)))
+            },
+            Pc::Spin | Pc::Done => None,
+        }
+    }
+
+    /// Explore every interleaving, checking the invariants, and return the
+    /// set of (generation, other counter) pairs a thread can observe when a
+    /// wait returns.
+    fn explore() -> BTreeSet<(usize, usize)> {
+        let start = Thread {
+            pc: Pc::Store,
+            generation: 0,
+            counter: 0,
+        };
+        let mut seen = HashSet::new();
+        let mut stack = vec![[start; 2]];
+        let mut observations = BTreeSet::new();
+        while let Some(state) = stack.pop() {
+            if !seen.insert(state) {
+                continue;
+            }
+            // barrier: the threads are never more than one generation apart
+            assert!(state[0].generation.abs_diff(state[1].generation) <= 1);
+            let mut progress = false;
+            for i in 0..2 {
+                if let Some((next, observed)) = step(state, i) {
+                    progress = true;
+                    if let Some((generation, other)) = observed {
+                        // a wait only returns once the other thread arrived
+                        assert!(other >= generation);
+                        observations.insert((generation, other));
+                    }
+                    stack.push(next);
+                }
+            }
+            // no deadlock: the only state without progress is the end
+            if !progress {
+                assert!(state.iter().all(|t| t.pc == Pc::Done), "{state:?}");
+            }
+        }
+        observations
+    }
+
+    #[test]
+    fn model_invariants() {
+        let observations = explore();
+        for generation in 1..=WAITS {
+            assert!(observations.contains(&(generation, generation)));
+        }
+    }
+
+    // every state observed by the implementation is reachable in the model
+    #[test]
+    fn implementation_refines_model() {
+        use rendezvous_swap::Rendezvous;
+        use std::sync::atomic::Ordering;
+        use std::thread;
+
+        // returns the handle, dropping it would arrive at the next sync point
+        fn run(mut rendezvous: Rendezvous) -> (Vec<(usize, usize)>, Rendezvous) {
+            let (_, theirs) = rendezvous.counter_ptrs();
+            let observed = (1..=WAITS)
+                .map(|generation| {
+                    rendezvous.wait();
+                    // SAFETY: the pointer is valid while `rendezvous` is alive
+                    (generation, unsafe { &*theirs }.load(Ordering::Acquire))
+                })
+                .collect();
+            (observed, rendezvous)
+        }
+        let observations = explore();
+        for _ in 0..200 {
+            let (my_rendezvous, their_rendezvous) = Rendezvous::new();
+            let handle = thread::spawn(move || run(their_rendezvous));
+            let (mut observed, _my_rendezvous) = run(my_rendezvous);
+            observed.extend(handle.join().unwrap().0);
+            for observation in observed {
+                assert!(observations.contains(&observation), "{observation:?}");
+            }
+        }
+    }
+}
+
+// with 4 slots the data of the first thread arrives 3 swaps later, and the
+// data of the second thread at the next swap
+#[test]
+fn test_rendezvous_data_n() {
+    use rendezvous_swap::RendezvousDataN;
+    use std::thread;
+
+    const SLOTS: usize = 4;
+    let (mut first, mut second) = RendezvousDataN::new_n([0; SLOTS]);
+    let handle = thread::spawn(move || {
+        for i in 1..100 {
+            let data = second.swap();
+            if i > SLOTS - 1 {
+                assert_eq!(*data, i - (SLOTS - 1));
+            }
+            *data = 1000 + i;
+        }
+    });
+    for i in 1..100 {
+        let data = first.swap();
+        if i > 1 {
+            assert_eq!(*data, 1000 + i - 1);
+        }
+        *data = i;
+    }
+    handle.join().unwrap();
+}
+
+// a slow partner switches the pair to the channel, and a fast one back
+#[cfg(feature = "std")]
+#[test]
+fn test_failover_rendezvous() {
+    use rendezvous_swap::FailoverRendezvous;
+    use std::thread;
+    use std::time::Duration;
+
+    const SLOW: usize = 3 * 64;
+    const FAST: usize = 100 * 64;
+    let (mut my_rendezvous, mut their_rendezvous) = FailoverRendezvous::new();
+    let handle = thread::spawn(move || {
+        for _ in 0..SLOW {
+            thread::sleep(Duration::from_micros(500));
+            their_rendezvous.wait();
+        }
+        for _ in 0..FAST {
+            their_rendezvous.wait();
+        }
+        their_rendezvous.is_blocking()
+    });
+    let mut blocked = false;
+    for _ in 0..SLOW {
+        my_rendezvous.wait();
+        blocked |= my_rendezvous.is_blocking();
+    }
+    assert!(blocked);
+    for _ in 0..FAST {
+        my_rendezvous.wait();
+    }
+    assert!(!my_rendezvous.is_blocking());
+    assert!(!handle.join().unwrap());
+}
+
+// a partner that is slow to publish its arrival is unresponsive to probes,
+// and swaps still see the right data
+#[cfg(feature = "test-hooks")]
+#[test]
+fn test_delayed_stores() {
+    use rendezvous_swap::{test_hooks, ProbeResult, RendezvousData};
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        test_hooks::delay_stores(Some(Duration::from_millis(50)));
+        for i in 1..=4 {
+            *their_rendezvous.swap() = -i;
+        }
+        their_rendezvous.swap();
+    });
+    assert_eq!(
+        my_rendezvous.probe(Duration::from_millis(1)),
+        ProbeResult::Unresponsive
+    );
+    for i in 1..=4 {
+        *my_rendezvous.swap() = i;
+    }
+    assert_eq!(*my_rendezvous.swap(), -4);
+    handle.join().unwrap();
+}
+
+// the worker thread is replaced every few swaps without desyncing the pair
+#[test]
+fn test_into_parts() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (mut my_rendezvous, their_rendezvous) = RendezvousData::new(0, 0);
+    let mut parts = their_rendezvous.into_parts();
+    for respawn in 0..10 {
+        let worker = thread::spawn(move || {
+            let mut their_rendezvous = RendezvousData::from_parts(parts);
+            for i in 0..3 {
+                *their_rendezvous.swap() = respawn * 3 + i;
+            }
+            their_rendezvous.into_parts()
+        });
+        for i in 0..3 {
+            let data = my_rendezvous.swap();
+            if respawn * 3 + i > 0 {
+                assert_eq!(*data, respawn * 3 + i - 1);
+            }
+        }
+        parts = worker.join().unwrap();
+        assert_eq!(parts.generation(), (respawn + 1) * 3);
+    }
+}
+
+// a handle stashed in a `void*` resumes at its generation on another thread,
+// and the shared state is freed once it is recovered and dropped
+#[test]
+fn test_into_raw() {
+    use core::ffi::c_void;
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::sync::Arc;
+    use std::thread;
+
+    let (mut my_rendezvous, their_rendezvous) = RendezvousData::new(0, 0);
+    let raw = their_rendezvous.into_raw() as usize;
+    let worker = thread::spawn(move || {
+        // SAFETY: from `into_raw` of a handle of this type, recovered once
+        let mut their_rendezvous = unsafe { RendezvousData::<i32>::from_raw(raw as *mut c_void) };
+        *their_rendezvous.swap() = 1;
+        their_rendezvous.into_raw() as usize
+    });
+    my_rendezvous.swap();
+    let raw = worker.join().unwrap();
+    let worker = thread::spawn(move || {
+        // SAFETY: as above
+        let mut their_rendezvous = unsafe { RendezvousData::<i32>::from_raw(raw as *mut c_void) };
+        assert_eq!(their_rendezvous.generation(), 1);
+        *their_rendezvous.swap() = 2;
+    });
+    assert_eq!(*my_rendezvous.swap(), 1);
+    worker.join().unwrap();
+
+    let data = Arc::new(());
+    let (my_rendezvous, their_rendezvous) =
+        RendezvousData::new(Arc::clone(&data), Arc::clone(&data));
+    drop(my_rendezvous);
+    // SAFETY: from `into_raw` of a handle of this type, recovered once
+    drop(unsafe { RendezvousData::<Arc<()>>::from_raw(their_rendezvous.into_raw()) });
+    assert_eq!(Arc::strong_count(&data), 1);
+
+    let (mut my_rendezvous, their_rendezvous) = Rendezvous::new();
+    let raw = their_rendezvous.into_raw() as usize;
+    let worker = thread::spawn(move || {
+        // SAFETY: from `Rendezvous::into_raw`, recovered once
+        let mut their_rendezvous = unsafe { Rendezvous::from_raw(raw as *mut c_void) };
+        their_rendezvous.wait();
+        their_rendezvous.generation()
+    });
+    my_rendezvous.wait();
+    assert_eq!(worker.join().unwrap(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_compute_worker() {
+    use rendezvous_swap::worker::ComputeWorker;
+
+    let mut worker = ComputeWorker::spawn(|request: &mut u64, response: &mut u64| {
+        *response = *request * 2;
+    });
+    for i in 0..1000 {
+        *worker.request() = i;
+        assert_eq!(*worker.call(), i * 2);
+    }
+}
+
+// one thread reinitializes while the other keeps swapping
+#[cfg(feature = "strict")]
+#[test]
+#[should_panic(expected = "this thread called reinit, the other thread called swap")]
+fn test_strict_mode() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    thread::spawn(move || loop {
+        their_rendezvous.swap();
+    });
+    let mut paused = my_rendezvous.pause();
+    // SAFETY: not upheld on purpose, the strict check panics after the first
+    // sync, before the closure runs
+    unsafe { paused.reinit(|_, _| {}) };
+}
+
+// the other thread swaps once less than this one and drops its handle, the
+// extra swap panics instead of waiting forever
+#[cfg(any(debug_assertions, feature = "checked"))]
+#[test]
+#[should_panic(
+    expected = "this thread arrived at generation 5, but the other handle was dropped after arriving at generation 4"
+)]
+fn test_unbalanced_swaps() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    thread::spawn(move || {
+        for _ in 0..3 {
+            their_rendezvous.swap();
+        }
+    });
+    // the dropped handle arrives once more
+    for _ in 0..5 {
+        my_rendezvous.swap();
+    }
+}
+
+// the lagging side catches up once, then the pair is back in lockstep
+#[test]
+fn test_resync() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        for i in 1..=10 {
+            *their_rendezvous.swap() = i;
+        }
+    });
+    assert_eq!(*my_rendezvous.swap(), 0);
+    let mut stale = None;
+    while my_rendezvous.resync(|data| stale = Some(*data)) == 0 {}
+    assert_eq!(stale, Some(1));
+    for i in 3..=10 {
+        assert_eq!(*my_rendezvous.swap(), i - 1);
+    }
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "test-kit")]
+#[test]
+fn test_kit_scenarios() {
+    use rendezvous_swap::{test_kit, FailoverRendezvous, Rendezvous, RendezvousData};
+    use std::time::Duration;
+
+    let (first, second) = Rendezvous::new();
+    let (first, second) = test_kit::exercise_pair(first, second, 1000);
+    test_kit::exercise_slow_partner(first, second, 10, Duration::from_millis(1));
+    let (first, second) = FailoverRendezvous::new();
+    test_kit::exercise_pair(first, second, 1000);
+    let (first, second) = RendezvousData::new(0, 0);
+    test_kit::exercise_swap_loop(first, second, 1000);
+    let (first, second) = Rendezvous::new();
+    test_kit::exercise_disconnect(first, second, Duration::from_millis(10));
+}
+
+// only the swaps marked urgent by the other thread are reported as urgent
+#[test]
+fn test_urgent() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        for i in 1..100 {
+            if i % 7 == 0 {
+                their_rendezvous.mark_urgent();
+            }
+            *their_rendezvous.swap() = i;
+        }
+    });
+    for i in 1..100 {
+        my_rendezvous.swap();
+        assert_eq!(my_rendezvous.is_urgent(), i % 7 == 0);
+    }
+    handle.join().unwrap();
+
+    // marking the next swap does not hide the mark of the last one
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        for _ in 0..2 {
+            their_rendezvous.mark_urgent();
+            their_rendezvous.swap();
+        }
+    });
+    my_rendezvous.swap();
+    thread::sleep(Duration::from_millis(20));
+    assert!(my_rendezvous.is_urgent());
+    my_rendezvous.swap();
+    handle.join().unwrap();
+}
+
+// a custom double buffer on the raw handshake, publishing early and doing
+// other work before completing
+#[test]
+fn test_generation_gate() {
+    use rendezvous_swap::raw::GenerationGate;
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    use std::sync::Arc;
+    use std::thread;
+
+    let (mut my_gate, mut their_gate) = GenerationGate::new();
+    let slots = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+    let their_slots = Arc::clone(&slots);
+    let handle = thread::spawn(move || {
+        for i in 1..1000 {
+            their_slots[i % 2].store(i, Relaxed);
+            their_gate.arrive();
+            while !their_gate.try_complete() {}
+        }
+    });
+    for i in 1..1000 {
+        my_gate.arrive();
+        my_gate.complete();
+        assert_eq!(slots[i % 2].load(Relaxed), i);
+        assert_eq!(my_gate.generation(), i);
+    }
+    handle.join().unwrap();
+}
+
+// whatever the topology, checking must not disturb the pair
+#[cfg(feature = "std")]
+#[test]
+fn test_check_smt() {
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let handle = thread::spawn(move || {
+        for _ in 0..100 {
+            their_rendezvous.check_smt();
+            their_rendezvous.wait();
+        }
+    });
+    for _ in 0..100 {
+        let siblings = my_rendezvous.check_smt();
+        assert_eq!(siblings, my_rendezvous.is_smt_sibling());
+        my_rendezvous.wait();
+    }
+    handle.join().unwrap();
+}
+
+// waking arrives once, later wakes of a stale waker do nothing
+#[test]
+fn test_waker() {
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let waker = my_rendezvous.waker();
+    waker.wake_by_ref();
+    waker.wake_by_ref();
+    their_rendezvous.wait(); // does not need this thread
+    my_rendezvous.wait();
+    waker.wake_by_ref(); // stale
+    let handle = thread::spawn(move || {
+        their_rendezvous.wait();
+        their_rendezvous
+    });
+    my_rendezvous.wait();
+    let mut their_rendezvous = handle.join().unwrap();
+    let (mine, theirs) = my_rendezvous.counter_ptrs();
+    // SAFETY: the pointers are valid while the handles are alive
+    unsafe {
+        use std::sync::atomic::Ordering::Acquire;
+        assert_eq!((*mine).load(Acquire), 2);
+        assert_eq!((*theirs).load(Acquire), 2);
+    }
+    my_rendezvous.waker().wake();
+    their_rendezvous.wait();
+}
+
+// a callback on another thread arrives for this side every generation
+#[test]
+fn test_rendezvous_completer() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        for i in 1..100 {
+            *their_rendezvous.swap() = i;
+        }
+    });
+    for i in 1..100 {
+        thread::scope(|scope| {
+            let (completer, pending) = my_rendezvous.arrive_later();
+            scope.spawn(move || drop(completer)); // dropping also completes
+            let data = pending.finish();
+            if i > 1 {
+                assert_eq!(*data, i - 1);
+            }
+        });
+    }
+    handle.join().unwrap();
+}
+
+// the handle stays borrowed until the completer arrived, and the next swap
+// completes the swap it arrived at
+#[test]
+fn test_pending_swap_dropped() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(1, 2);
+    let handle = thread::spawn(move || (*their_rendezvous.swap(), *their_rendezvous.swap()));
+    thread::scope(|scope| {
+        let (completer, pending) = my_rendezvous.arrive_later();
+        drop(pending);
+        scope.spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            completer.complete();
+        });
+    });
+    let data = my_rendezvous.swap();
+    assert_eq!(*data, 2);
+    *data = 3;
+    my_rendezvous.swap();
+    assert_eq!(handle.join().unwrap(), (1, 3));
+}
+
+#[test]
+fn test_split_rendezvous_data() {
+    use rendezvous_swap::SplitRendezvousData;
+    use std::thread;
+
+    const ITERATIONS: u64 = 10_000;
+    let (mut my_rendezvous, mut their_rendezvous) =
+        SplitRendezvousData::new([vec![0_u64; 64], vec![0; 64]], [vec![0; 64], vec![0; 64]]);
+    let handle = thread::spawn(move || {
+        for i in 1..ITERATIONS {
+            let (incoming, outgoing) = their_rendezvous.swap();
+            assert!(incoming.iter().all(|&x| x == i - 1));
+            outgoing.iter_mut().for_each(|x| *x = i);
+        }
+    });
+    for i in 1..ITERATIONS {
+        let (incoming, outgoing) = my_rendezvous.swap();
+        assert!(incoming.iter().all(|&x| x == i - 1));
+        outgoing.copy_from_slice(incoming);
+        outgoing.iter_mut().for_each(|x| *x += 1);
+    }
+    handle.join().unwrap();
+}
+
+// the reader sees every buffer the writer filled, one swap later
+#[test]
+fn test_read_only() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    const ITERATIONS: u64 = 10_000;
+    let (mut writer, reader) = RendezvousData::new(vec![0_u64; 64], vec![0; 64]);
+    let mut reader = reader.read_only();
+    let handle = thread::spawn(move || {
+        for i in 0..ITERATIONS {
+            assert!(reader.read().iter().all(|&x| x == i), "torn buffer");
+        }
+        reader.generation()
+    });
+    for i in 1..=ITERATIONS {
+        let data = writer.swap();
+        if i > 2 {
+            assert!(data.iter().all(|&x| x == i - 2));
+        }
+        data.fill(i);
+    }
+    assert_eq!(handle.join().unwrap(), ITERATIONS as usize);
+}
+
+#[test]
+fn test_compat_tick() {
+    use rendezvous_swap::{compat, Rendezvous, RendezvousData};
+    use std::thread;
+
+    fn run_loop(ticks: usize, mut on_tick: impl FnMut()) {
+        for _ in 0..ticks {
+            on_tick();
+        }
+    }
+    let (my_rendezvous, their_rendezvous) = Rendezvous::new();
+    let handle = thread::spawn(move || run_loop(100, compat::tick(their_rendezvous)));
+    run_loop(100, compat::tick(my_rendezvous));
+    handle.join().unwrap();
+
+    let (my_rendezvous, their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        let mut next = 0;
+        run_loop(
+            101,
+            compat::tick_swap(their_rendezvous, |data: &mut i32| {
+                next += 1;
+                *data = next;
+            }),
+        );
+    });
+    let mut received = Vec::new();
+    run_loop(
+        101,
+        compat::tick_swap(my_rendezvous, |data: &mut i32| received.push(*data)),
+    );
+    handle.join().unwrap();
+    assert_eq!(received, (0..=100).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_handle_size() {
+    use rendezvous_swap::{Rendezvous, RendezvousData, RendezvousDataN, MAX_HANDLE_SIZE};
+
+    assert!(size_of::<Rendezvous>() <= MAX_HANDLE_SIZE);
+    assert!(size_of::<RendezvousData<u8>>() <= MAX_HANDLE_SIZE);
+    assert!(size_of::<RendezvousData<[u64; 1024]>>() <= MAX_HANDLE_SIZE);
+    assert!(size_of::<RendezvousDataN<[u64; 1024], 8>>() <= MAX_HANDLE_SIZE);
+}
+
+#[test]
+fn test_try_into_inner() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![0], vec![0]);
+    let mut my_rendezvous = match my_rendezvous.try_into_inner() {
+        Ok(_) => panic!("other handle is alive"),
+        Err(handle) => handle,
+    };
+    let handle = thread::spawn(move || {
+        for i in 1..=100 {
+            their_rendezvous.swap().push(i);
+        }
+    });
+    for _ in 1..=100 {
+        my_rendezvous.swap().clear();
+    }
+    handle.join().unwrap();
+    let (mine, theirs) = my_rendezvous.try_into_inner().ok().unwrap();
+    assert_eq!(mine, []);
+    assert_eq!(theirs, [100]);
+}
+
+#[test]
+fn test_pair_id() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+
+    let (a, b) = Rendezvous::new();
+    let (c, d) = Rendezvous::new();
+    assert_eq!(a.pair_id(), b.pair_id());
+    assert_ne!(a.pair_id(), c.pair_id());
+    assert!(Rendezvous::same_pair(&a, &b) && Rendezvous::same_pair(&d, &c));
+    assert!(!Rendezvous::same_pair(&a, &a) && !Rendezvous::same_pair(&a, &d));
+
+    let (a, b) = RendezvousData::new(0, 0);
+    let (c, d) = RendezvousData::<u8>::new_zeroed();
+    assert_eq!(a.pair_id(), b.pair_id());
+    assert_eq!(c.pair_id(), d.pair_id());
+    assert!(a.pair_id() < c.pair_id());
+    assert!(RendezvousData::same_pair(&b, &a) && RendezvousData::same_pair(&c, &d));
+    assert!(!RendezvousData::same_pair(&a, &a));
+    assert!(!a.pair_id().to_string().is_empty());
+}
+
+#[cfg(feature = "migration-stats")]
+#[test]
+fn test_migration_stats() {
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let handle = thread::spawn(move || {
+        for _ in 0..1000 {
+            their_rendezvous.wait();
+        }
+        their_rendezvous
+    });
+    for _ in 0..1000 {
+        my_rendezvous.wait();
+    }
+    let their_rendezvous = handle.join().unwrap();
+    assert_eq!(
+        my_rendezvous.peer_migrations(),
+        their_rendezvous.migrations()
+    );
+    assert_eq!(
+        my_rendezvous.migrations(),
+        their_rendezvous.peer_migrations()
+    );
+    // 15 samples per thread
+    assert!(my_rendezvous.migrations() < 1000 / 64);
+}
+
+// the thread waiting for a slow partner spins and waits, the slow one does not
+#[cfg(feature = "stats")]
+#[test]
+fn test_wait_stats() {
+    use rendezvous_swap::{RendezvousData, WaitStats};
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    assert_eq!(my_rendezvous.wait_stats(), WaitStats::default());
+    let handle = thread::spawn(move || {
+        for _ in 0..20 {
+            thread::sleep(Duration::from_millis(1));
+            their_rendezvous.swap();
+        }
+        their_rendezvous
+    });
+    for _ in 0..20 {
+        my_rendezvous.swap();
+    }
+    let their_rendezvous = handle.join().unwrap();
+    let (mine, theirs) = (my_rendezvous.wait_stats(), their_rendezvous.wait_stats());
+    assert_eq!(mine, their_rendezvous.peer_wait_stats());
+    assert_eq!(theirs, my_rendezvous.peer_wait_stats());
+    assert_eq!((mine.waits, theirs.waits), (20, 20));
+    assert!(mine.spins > theirs.spins);
+    assert!(mine.max_spins <= mine.spins);
+    assert!(theirs.immediate_waits > mine.immediate_waits);
+    #[cfg(feature = "std")]
+    {
+        assert!(mine.wait_time >= Duration::from_millis(10));
+        assert!(mine.max_wait_time <= mine.wait_time);
+    }
+}
+
+#[test]
+fn test_with_arena() {
+    use rendezvous_swap::{Arena, Rendezvous, WithArena};
+    use std::thread;
+
+    let mut arena = Arena::new();
+    let small = arena.alloc(1_u8);
+    assert_eq!(*small, 1);
+    let words = arena.alloc_slice(&[u64::MAX; 1000]);
+    assert_eq!(words.as_ptr().align_offset(align_of::<u64>()), 0);
+    assert!(words.iter().all(|&x| x == u64::MAX));
+    let capacity = arena.capacity();
+    assert!(capacity >= 8000);
+    arena.reset();
+    assert!(arena.capacity() <= capacity);
+    assert_eq!(*arena.alloc(()), ());
+
+    let (my_rendezvous, their_rendezvous) = Rendezvous::new();
+    let handle = thread::spawn(move || {
+        let mut their_rendezvous = WithArena::new(their_rendezvous);
+        for i in 0..100 {
+            let arena = their_rendezvous.sync();
+            let values = arena.alloc_slice(&[i; 100]);
+            assert!(values.iter().all(|&x| x == i));
+        }
+    });
+    let mut my_rendezvous = WithArena::new(my_rendezvous);
+    for _ in 0..100 {
+        my_rendezvous.sync();
+        // steady state: the arena keeps a single chunk
+        my_rendezvous.arena().alloc([0_u32; 16]);
+        assert!(my_rendezvous.arena().capacity() <= 1 << 10);
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_cooperative() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+
+    // Two "fibers" on one thread, each yield runs the other one to its next
+    // sync point.
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    for _ in 0..100 {
+        my_rendezvous.wait_cooperative(|| their_rendezvous.wait_cooperative(|| {}));
+    }
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    for i in 1..=100 {
+        let mut yields = 0;
+        let data = my_rendezvous.swap_cooperative(|| {
+            yields += 1;
+            *their_rendezvous.swap_cooperative(|| unreachable!()) = i;
+        });
+        assert_eq!(yields, 1);
+        *data = 0;
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_last_error() {
+    use rendezvous_swap::{ErrorKind, ProbeResult, Rendezvous};
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    assert_eq!(my_rendezvous.last_error(), None);
+    let handle = thread::spawn(move || {
+        their_rendezvous.wait();
+        thread::sleep(Duration::from_millis(200));
+        their_rendezvous.wait();
+        their_rendezvous.last_error()
+    });
+    my_rendezvous.wait();
+    assert_eq!(
+        my_rendezvous.probe(Duration::from_millis(1)),
+        ProbeResult::Unresponsive
+    );
+    let error = my_rendezvous.last_error().unwrap();
+    assert_eq!(error.kind, ErrorKind::Unresponsive);
+    assert_eq!(error.generation, 1);
+    assert!(!error.by_peer);
+    my_rendezvous.wait();
+    let error = handle.join().unwrap().unwrap();
+    assert_eq!(error.kind, ErrorKind::Unresponsive);
+    assert!(error.by_peer);
+}
+
+#[test]
+fn test_for_generations() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![], vec![]);
+    let handle = thread::spawn(move || {
+        let mut i = 0;
+        their_rendezvous.for_generations(100, |data| {
+            data.clear();
+            data.push(i);
+            i += 1;
+        });
+    });
+    let mut received = Vec::new();
+    let last = my_rendezvous.for_generations(100, |data| received.append(data));
+    received.append(last);
+    handle.join().unwrap();
+    assert_eq!(received, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_drop_drains() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        for i in 1..=10 {
+            *their_rendezvous.swap() = i;
+        }
+    });
+    for i in 1..=10 {
+        assert_eq!(*my_rendezvous.swap(), i - 1);
+    }
+    // the final value is handed over when the other handle is dropped
+    assert_eq!(*my_rendezvous.swap(), 10);
+    handle.join().unwrap();
+
+    // the other thread is still there after `into_parts`
+    let (my_rendezvous, mut their_rendezvous) = RendezvousData::new(1, 2);
+    let parts = my_rendezvous.into_parts();
+    let handle = thread::spawn(move || *their_rendezvous.swap());
+    assert_eq!(*RendezvousData::from_parts(parts).swap(), 2);
+    assert_eq!(handle.join().unwrap(), 1);
+}
+
+#[cfg(feature = "bench")]
+#[test]
+fn test_ping_pong_meter() {
+    use rendezvous_swap::bench::PingPongMeter;
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+    use std::time::Duration;
+
+    let (my_rendezvous, mut their_rendezvous) = RendezvousData::new([0_u64; 8], [0; 8]);
+    let handle = thread::spawn(move || {
+        for i in 0..200 {
+            their_rendezvous.swap()[0] = i;
+        }
+    });
+    let mut meter = PingPongMeter::with_threshold(my_rendezvous, Duration::MAX);
+    for _ in 0..100 {
+        meter.swap();
+    }
+    assert_eq!(meter.stats().remote_lines, 0);
+    let mut meter = PingPongMeter::new(meter.into_inner());
+    for _ in 0..100 {
+        meter.swap();
+    }
+    handle.join().unwrap();
+    let stats = meter.stats();
+    assert_eq!(stats.swaps, 100);
+    assert!(stats.remote_lines <= stats.swaps);
+}
+
+#[cfg(all(feature = "rt", unix))]
+#[test]
+fn test_rt_spawn_pair() {
+    use rendezvous_swap::rt::{spawn_pair, Priority, PriorityError};
+    use rendezvous_swap::RendezvousData;
+
+    let (mut first, mut second) = RendezvousData::new(1, 2);
+    let threads = spawn_pair(
+        Priority::normal(),
+        move || *first.swap(),
+        move || *second.swap(),
+    )
+    .unwrap();
+    assert_eq!(threads.join().unwrap(), (2, 1));
+
+    // Out of range for every policy, so neither closure may run.
+    let (mut first, mut second) = RendezvousData::new(1, 2);
+    let spawned = spawn_pair(
+        Priority::fifo(1000),
+        move || -> i32 { *first.swap() },
+        move || -> i32 { *second.swap() },
+    );
+    assert!(matches!(spawned, Err(PriorityError::Rejected(_))));
+}
+
+#[cfg(all(feature = "shm", target_os = "linux"))]
+#[test]
+fn test_shm() {
+    use rendezvous_swap::shm::{PeerStatus, ShmRendezvous};
+    use std::io::ErrorKind;
+    use std::thread;
+    use std::time::Duration;
+
+    let name = format!("/rendezvous_swap_test_{}", std::process::id());
+    let mut creator = ShmRendezvous::create(&name).unwrap();
+    assert_eq!(
+        ShmRendezvous::create(&name).err().unwrap().kind(),
+        ErrorKind::AlreadyExists
+    );
+    assert_eq!(creator.peer_status(Duration::ZERO), PeerStatus::NotAttached);
+    let mut opener = ShmRendezvous::open(&name).unwrap();
+    assert!(ShmRendezvous::open(&name).is_err());
+    assert_eq!(
+        creator.peer_status(Duration::from_millis(10)),
+        PeerStatus::Unresponsive
+    );
+    let handle = thread::spawn(move || {
+        for _ in 0..1000 {
+            opener.wait().unwrap();
+        }
+    });
+    for _ in 0..1000 {
+        creator.wait().unwrap();
+    }
+    handle.join().unwrap();
+    assert_eq!(creator.peer_status(Duration::ZERO), PeerStatus::Gone);
+    assert_eq!(creator.wait().unwrap_err().kind(), ErrorKind::BrokenPipe);
+    drop(creator);
+    assert!(ShmRendezvous::open(&name).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_spin_budget() {
+    use rendezvous_swap::{RendezvousData, SpinBudget};
+    use std::thread;
+    use std::time::Duration;
+
+    let budget = SpinBudget::new(Duration::from_micros(20), Duration::ZERO);
+    assert_eq!(budget.sleep, Duration::from_micros(1));
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    assert_eq!(my_rendezvous.spin_budget(), None);
+    my_rendezvous.set_spin_budget(Some(SpinBudget::new(
+        Duration::from_micros(20),
+        Duration::from_millis(1),
+    )));
+    assert_eq!(their_rendezvous.spin_budget(), None);
+    let handle = thread::spawn(move || {
+        for i in 1..=10 {
+            thread::sleep(Duration::from_millis(2));
+            *their_rendezvous.swap() = i;
+        }
+        their_rendezvous.swap();
+    });
+    let mut sum = 0;
+    for _ in 0..=10 {
+        sum += *my_rendezvous.swap();
+    }
+    assert_eq!(sum, 55);
+    assert!(my_rendezvous.contention_stats().yields >= 10);
+    my_rendezvous.set_spin_budget(None);
+    assert_eq!(my_rendezvous.spin_budget(), None);
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_swap_with_both() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0_u64, 0);
+    let handle = thread::spawn(move || {
+        for i in 1..=100 {
+            *their_rendezvous.swap() = i;
+            // the leader runs the closure, never the second handle
+            // SAFETY: both threads call `swap_with_both` here
+            let received = *unsafe { their_rendezvous.swap_with_both(|_, _| unreachable!()) };
+            assert_eq!(received, 3 * i);
+        }
+    });
+    for i in 1..=100 {
+        *my_rendezvous.swap() = 2 * i;
+        // SAFETY: both threads call `swap_with_both` here
+        let received = *unsafe {
+            my_rendezvous.swap_with_both(|outgoing, incoming| {
+                assert_eq!((*outgoing, *incoming), (2 * i, i));
+                *outgoing += *incoming;
+                *incoming = 0;
+            })
+        };
+        assert_eq!(received, 0);
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_dirty_rendezvous_data() {
+    use rendezvous_swap::DirtyRendezvousData;
+    use std::thread;
+
+    const LEN: usize = 64;
+    let (mut my_rendezvous, mut their_rendezvous) = DirtyRendezvousData::new(&[0_usize; LEN]);
+    let handle = thread::spawn(move || {
+        for i in 1..=200 {
+            // small and large changes, overlapping with the other thread's
+            let range = if i % 4 == 0 {
+                0..LEN
+            } else {
+                i % LEN..(i % LEN + 4).min(LEN)
+            };
+            their_rendezvous.get_mut(range).fill(i);
+            let replica = their_rendezvous.swap().to_vec();
+            assert_eq!(their_rendezvous.dirty(), 0..0);
+            assert_eq!(replica[i % LEN], i * 1000);
+            their_rendezvous.swap();
+        }
+        their_rendezvous.exchanges()
+    });
+    let mut expected = vec![0; LEN];
+    for i in 1..=200 {
+        let range = if i % 4 == 0 {
+            0..LEN
+        } else {
+            i % LEN..(i % LEN + 4).min(LEN)
+        };
+        expected[range.clone()].fill(i);
+        my_rendezvous.get_mut(i % LEN..i % LEN + 1).fill(i * 1000);
+        expected[i % LEN] = i * 1000;
+        assert_eq!(my_rendezvous.swap(), &expected[..]);
+        // nothing changed on either side
+        assert_eq!(my_rendezvous.swap(), &expected[..]);
+    }
+    assert!(my_rendezvous.exchanges() > 0);
+    assert_eq!(my_rendezvous.exchanges(), handle.join().unwrap());
+}
+
+#[cfg(feature = "checksum")]
+#[test]
+fn test_checked_swap() {
+    use rendezvous_swap::{ErrorKind, RendezvousData};
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    use std::thread;
+
+    /// Reads back different bytes the third time, like a corrupted buffer.
+    #[derive(Debug)]
+    struct Flaky {
+        reads: AtomicUsize,
+        bytes: [u8; 4],
+    }
+    impl AsRef<[u8]> for Flaky {
+        fn as_ref(&self) -> &[u8] {
+            if self.reads.fetch_add(1, Relaxed) == 2 {
+                &self.bytes[..3]
+            } else {
+                &self.bytes
+            }
+        }
+    }
+    let flaky = || Flaky {
+        reads: AtomicUsize::new(0),
+        bytes: [1, 2, 3, 4],
+    };
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(flaky(), flaky());
+    let handle = thread::spawn(move || {
+        their_rendezvous.checked_swap().unwrap();
+        let failed = their_rendezvous.checked_swap().is_err();
+        their_rendezvous.checked_swap().unwrap();
+        failed
+    });
+    // each buffer is read when sent and received, and corrupted when sent
+    // back
+    my_rendezvous.checked_swap().unwrap();
+    let error = my_rendezvous.checked_swap().unwrap_err();
+    assert_eq!(error.generation, 2);
+    assert_ne!(error.expected, error.actual);
+    assert_eq!(
+        my_rendezvous.last_error().unwrap().kind,
+        ErrorKind::ChecksumMismatch
+    );
+    my_rendezvous.checked_swap().unwrap();
+    assert!(handle.join().unwrap());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_scoped() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::thread;
+
+    thread::scope(|scope| {
+        let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::scoped(scope);
+        scope.spawn(move || {
+            for _ in 0..100 {
+                their_rendezvous.wait().unwrap();
+            }
+        });
+        for _ in 0..101 {
+            my_rendezvous.wait().unwrap();
+        }
+        assert!(my_rendezvous.wait().is_err());
+        assert_eq!(
+            my_rendezvous.wait().unwrap_err().to_string(),
+            "the other handle of the pair was dropped"
+        );
+    });
+    // the thread stops early, the other thread is not left spinning
+    let mut sum = 0;
+    thread::scope(|scope| {
+        let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::scoped(scope, 0, 0);
+        scope.spawn(move || {
+            for i in 1..=10 {
+                *their_rendezvous.swap().unwrap() = i;
+            }
+        });
+        while let Ok(data) = my_rendezvous.swap() {
+            sum += *data;
+        }
+    });
+    assert_eq!(sum, 55);
+}
+
+// the other thread is woken by the last arrival of a dropped handle before it
+// is marked as gone, and must still see it as gone at its next wait
+#[cfg(feature = "std")]
+#[test]
+fn test_scoped_drop_race() {
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+
+    for _ in 0..1000 {
+        let waits = thread::scope(|scope| {
+            let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::scoped(scope);
+            scope.spawn(move || their_rendezvous.wait().unwrap());
+            (0..).take_while(|_| my_rendezvous.wait().is_ok()).count()
+        });
+        assert_eq!(waits, 2);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_migrate() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let old_worker = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(1));
+        *their_rendezvous.swap() = 1;
+        their_rendezvous
+    });
+    my_rendezvous.swap();
+    let mut their_rendezvous = old_worker.join().unwrap();
+    let new_worker = thread::spawn(move || {
+        let mut placed = false;
+        let received = *their_rendezvous.migrate(|data| {
+            *data += 1;
+            placed = true;
+        });
+        assert!(placed);
+        assert_eq!(their_rendezvous.contention_stats().yields, 0);
+        received
+    });
+    assert_eq!(*my_rendezvous.swap(), 2);
+    assert_eq!(new_worker.join().unwrap(), 0);
+}
+
+#[test]
+fn test_fan_out() {
+    use rendezvous_swap::FanOutPublisher;
+    use std::thread;
+
+    let (mut publisher, readers) = FanOutPublisher::new(vec![0; 16], vec![0; 16], 4);
+    assert_eq!(publisher.readers(), 4);
+    let mut readers = readers.into_iter();
+    let dropped = readers.next().unwrap();
+    let handles: Vec<_> = readers
+        .map(|mut reader| {
+            thread::spawn(move || {
+                for i in 1..=1000 {
+                    let data = reader.read();
+                    assert!(data.iter().all(|&x| x == i), "torn publication {i}");
+                }
+                reader.generation()
+            })
+        })
+        .collect();
+    drop(dropped);
+    assert_eq!(publisher.readers(), 3);
+    let mut data = publisher.current();
+    for i in 1..=1000 {
+        data.fill(i);
+        data = publisher.publish();
+    }
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 1000);
+    }
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn test_trace() {
+    use rendezvous_swap::trace::{write_chrome_trace, write_csv, Traced};
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (my_rendezvous, their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        let mut traced = Traced::new(their_rendezvous, 4);
+        for i in 1..=10 {
+            *traced.swap() = i;
+        }
+        traced.trace()
+    });
+    let mut traced = Traced::new(my_rendezvous, 4);
+    for _ in 0..10 {
+        traced.swap();
+    }
+    assert_eq!(traced.generation(), 10);
+    let mine = traced.trace();
+    let theirs = handle.join().unwrap();
+    let generations: Vec<_> = mine.events.iter().map(|event| event.generation).collect();
+    assert_eq!(generations, [7, 8, 9, 10]);
+    assert!(theirs
+        .events
+        .iter()
+        .all(|event| event.arrived <= event.departed));
+
+    let mut csv = Vec::new();
+    write_csv(&mut csv, &mine, &theirs).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().count(), 9);
+    assert!(csv.lines().nth(5).unwrap().starts_with("1,7,"));
+
+    let mut json = Vec::new();
+    write_chrome_trace(&mut json, &mine, &theirs).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.starts_with('[') && json.trim_end().ends_with(']'));
+    assert_eq!(json.matches("\"ph\":\"X\"").count(), 8);
+}
+
+#[test]
+fn test_tagged() {
+    use rendezvous_swap::{RendezvousData, SwapPoint, Tagged};
+    use std::thread;
+
+    struct Phase1;
+
+    fn stage(mut pair: Tagged<RendezvousData<u32>, Phase1>) -> u32 {
+        *SwapPoint::swap(&mut pair) = 1;
+        *pair.swap()
+    }
+
+    let (mut my_rendezvous, their_rendezvous) =
+        Tagged::<_, Phase1>::pair(RendezvousData::new(0, 0));
+    assert_eq!(
+        size_of_val(&my_rendezvous),
+        size_of::<RendezvousData<u32>>()
+    );
+    let handle = thread::spawn(move || stage(their_rendezvous));
+    *my_rendezvous.swap() = 2;
+    assert_eq!(*my_rendezvous.swap(), 1);
+    assert_eq!(handle.join().unwrap(), 2);
+    let _untagged: RendezvousData<u32> = my_rendezvous.into_inner();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_preemption_threshold() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    assert_eq!(my_rendezvous.preemption_threshold(), None);
+    my_rendezvous.set_preemption_threshold(Some(Duration::from_micros(100)));
+    assert_eq!(
+        my_rendezvous.preemption_threshold(),
+        Some(Duration::from_micros(100))
+    );
+    let handle = thread::spawn(move || {
+        for i in 1..=3 {
+            thread::sleep(Duration::from_millis(5));
+            *their_rendezvous.swap() = i;
+        }
+        their_rendezvous.swap();
+    });
+    let mut sum = 0;
+    for _ in 0..=3 {
+        sum += *my_rendezvous.swap();
+    }
+    assert_eq!(sum, 6);
+    let stats = my_rendezvous.contention_stats();
+    assert!(stats.suspected_preemptions >= 3);
+    assert!(!stats.is_degraded());
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_clock() {
+    use rendezvous_swap::{Clock, ProbeResult, RendezvousData};
+    use std::cell::Cell;
+    use std::thread;
+    use std::time::Duration;
+
+    struct Manual(Cell<Duration>);
+    impl Clock for Manual {
+        fn now(&self) -> Duration {
+            let now = self.0.get() + Duration::from_millis(1);
+            self.0.set(now);
+            now
+        }
+    }
+
+    let clock = Manual(Cell::new(Duration::ZERO));
+    let start = clock.now();
+    clock.sleep(Duration::from_millis(5));
+    assert!(clock.now() - start >= Duration::from_millis(5));
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    assert_eq!(
+        my_rendezvous.probe_with(Duration::from_millis(3), &clock),
+        ProbeResult::Unresponsive
+    );
+    let handle = thread::spawn(move || their_rendezvous.swap().to_owned());
+    while my_rendezvous.probe_with(Duration::from_millis(3), &clock) != ProbeResult::Waiting {}
+    my_rendezvous.swap();
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_tags() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    assert_eq!(my_rendezvous.peer_tag(), 0);
+    let handle = thread::spawn(move || {
+        for i in 1..=100_u64 {
+            their_rendezvous.set_tag(i);
+            *their_rendezvous.swap() = i;
+            assert_eq!(their_rendezvous.peer_tag(), u64::MAX - i);
+        }
+    });
+    for i in 1..=100_u64 {
+        my_rendezvous.set_tag(u64::MAX - i);
+        my_rendezvous.swap();
+        assert_eq!(my_rendezvous.peer_tag(), i);
+    }
+    handle.join().unwrap();
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let handle = thread::spawn(move || {
+        their_rendezvous.set_tag(7);
+        their_rendezvous.wait();
+    });
+    my_rendezvous.wait();
+    assert_eq!(my_rendezvous.peer_tag(), 7);
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_barrier2() {
+    use rendezvous_swap::compat::Barrier2;
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    use std::sync::Arc;
+    use std::thread;
+
+    const ITERATIONS: usize = 1000;
+    let barrier = Arc::new(Barrier2::new());
+    let counter = Arc::new(AtomicUsize::new(0));
+    let run = |barrier: Arc<Barrier2>, counter: Arc<AtomicUsize>| {
+        move || {
+            let mut leaders = 0;
+            for i in 0..ITERATIONS {
+                counter.fetch_add(1, Relaxed);
+                barrier.wait();
+                assert_eq!(counter.load(Relaxed), 2 * (i + 1));
+                leaders += usize::from(barrier.wait().is_leader());
+            }
+            leaders
+        }
+    };
+    let handle = thread::spawn(run(Arc::clone(&barrier), Arc::clone(&counter)));
+    let leaders = run(barrier, counter)() + handle.join().unwrap();
+    assert_eq!(leaders, ITERATIONS);
+}
+
+// the same generic stage runs on every kind of sync point
+#[cfg(feature = "std")]
+#[test]
+fn test_sync_point_generic() {
+    use rendezvous_swap::{
+        FanOutPublisher, Rendezvous, RendezvousData, RendezvousGroup, SwapPoint, SyncPoint,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    const TICKS: usize = 100;
+
+    // every thread publishes its tick before each sync, and afterwards finds
+    // all threads at that tick or the next
+    fn lockstep(points: Vec<Box<dyn SyncPoint + Send>>) {
+        let ticks: Arc<Vec<AtomicUsize>> =
+            Arc::new(points.iter().map(|_| AtomicUsize::new(0)).collect());
+        let handles: Vec<_> = points
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut point)| {
+                let ticks = Arc::clone(&ticks);
+                thread::spawn(move || {
+                    for tick in 1..=TICKS {
+                        ticks[index].store(tick, Relaxed);
+                        point.sync();
+                        let mut others = ticks.iter().map(|other| other.load(Relaxed));
+                        assert!(others.all(|other| other == tick || other == tick + 1));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .for_each(|handle| handle.join().unwrap());
+    }
+
+    let (my_rendezvous, their_rendezvous) = Rendezvous::new();
+    lockstep(vec![Box::new(my_rendezvous), Box::new(their_rendezvous)]);
+    let (my_rendezvous, their_rendezvous) = RendezvousData::new(0, 0);
+    lockstep(vec![Box::new(my_rendezvous), Box::new(their_rendezvous)]);
+    let group = RendezvousGroup::new(3);
+    lockstep(
+        group
+            .into_iter()
+            .map(|member| Box::new(member) as _)
+            .collect(),
+    );
+    let barrier = Arc::new(Barrier::new(3));
+    lockstep(
+        (0..3)
+            .map(|_| Box::new(Arc::clone(&barrier)) as _)
+            .collect(),
+    );
+
+    let (mut publisher, readers) = FanOutPublisher::new(0, 0, 2);
+    let handles: Vec<_> = readers
+        .into_iter()
+        .map(|mut reader| {
+            thread::spawn(move || {
+                for _ in 0..TICKS {
+                    reader.sync();
+                }
+                reader.generation()
+            })
+        })
+        .collect();
+    for tick in 1..=TICKS {
+        *SwapPoint::swap(&mut publisher) = tick;
+    }
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), TICKS);
+    }
+}
+
+#[test]
+fn test_rendezvous_group() {
+    use rendezvous_swap::RendezvousGroup;
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    use std::sync::Arc;
+    use std::thread;
+
+    const THREADS: usize = 5;
+    const ITERATIONS: usize = 500;
+    let slots: Arc<Vec<AtomicUsize>> =
+        Arc::new((0..THREADS).map(|_| AtomicUsize::new(0)).collect());
+    let group = RendezvousGroup::new(THREADS);
+    assert_eq!(group[0].len(), THREADS);
+    let handles: Vec<_> = group
+        .into_iter()
+        .map(|mut member| {
+            let slots = Arc::clone(&slots);
+            thread::spawn(move || {
+                let index = member.index();
+                for i in 1..=ITERATIONS {
+                    slots[index].store(i, Relaxed);
+                    member.wait();
+                    assert!(slots.iter().all(|slot| slot.load(Relaxed) == i));
+                    member.wait();
+                }
+                member.generation()
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 2 * ITERATIONS);
+    }
+
+    let mut single = RendezvousGroup::new(1);
+    single[0].wait();
+    assert_eq!(single[0].generation(), 1);
+}
+
+// every buffer visits the stages in order, each stage adding its index, and
+// is never accessed by two stages at once
+#[test]
+fn test_rendezvous_ring() {
+    use rendezvous_swap::RendezvousRing;
+    use std::thread;
+
+    const STAGES: usize = 4;
+    const ITERATIONS: usize = 1000;
+    let ring = RendezvousRing::new(STAGES, || vec![0; 64]);
+    assert_eq!(ring[0].len(), STAGES);
+    let handles: Vec<_> = ring
+        .into_iter()
+        .map(|mut stage| {
+            thread::spawn(move || {
+                let index = stage.index();
+                for generation in 1..=ITERATIONS {
+                    let data = stage.advance();
+                    // filled by the first stage `index` generations ago,
+                    // or coming back from the last stage
+                    let age = if index == 0 { STAGES } else { index };
+                    let expected = if generation > age {
+                        (generation - age) * 1000 + (0..age).sum::<usize>()
+                    } else {
+                        0
+                    };
+                    assert!(data.iter().all(|&x| x == expected), "torn buffer");
+                    if index == 0 {
+                        data.fill(generation * 1000);
+                    } else if generation > index {
+                        data.iter_mut().for_each(|x| *x += index);
+                    }
+                }
+                stage.generation()
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), ITERATIONS);
+    }
+
+    let mut single = RendezvousRing::new(1, || 3);
+    assert_eq!(*single[0].advance(), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_experiment() {
+    use core::cell::Cell;
+    use rendezvous_swap::{Clock, Experiment, RendezvousGroup};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Advances by a millisecond every time it is read.
+    struct Ticks(Cell<u64>);
+    impl Clock for Ticks {
+        fn now(&self) -> Duration {
+            self.0.set(self.0.get() + 1);
+            Duration::from_millis(self.0.get())
+        }
+    }
+
+    const WINDOW: u64 = 8;
+    let mut first = RendezvousGroup::new(2);
+    let (their_first, first) = (first.pop().unwrap(), first.pop().unwrap());
+    let mut second = RendezvousGroup::new(2);
+    let (their_second, second) = (second.pop().unwrap(), second.pop().unwrap());
+    let handle = thread::spawn(move || {
+        let mut experiment = Experiment::new(their_first, their_second, WINDOW);
+        for _ in 0..5 * WINDOW + 3 {
+            experiment.sync();
+        }
+        experiment.into_inner()
+    });
+    let mut experiment = Experiment::with_clock(first, second, WINDOW, Ticks(Cell::new(0)));
+    for i in 0..5 * WINDOW + 3 {
+        assert_eq!(experiment.is_first(), (i / WINDOW).is_multiple_of(2));
+        experiment.sync();
+    }
+    let report = experiment.report();
+    // Three windows of the first pair and two of the second completed.
+    assert_eq!(report.first.syncs, 3 * WINDOW);
+    assert_eq!(report.second.syncs, 2 * WINDOW);
+    assert_eq!(report.first.time, Duration::from_millis(3));
+    assert_eq!(report.second.time, Duration::from_millis(2));
+    assert_eq!(report.first.mean(), report.second.mean());
+    assert!(!report.first_is_faster());
+
+    let (first, second) = experiment.into_inner();
+    let (their_first, their_second) = handle.join().unwrap();
+    assert_eq!(first.generation(), their_first.generation());
+    assert_eq!(first.generation(), (3 * WINDOW) as usize);
+    assert_eq!(second.generation(), their_second.generation());
+    assert_eq!(second.generation(), (2 * WINDOW + 3) as usize);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_disconnect() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::thread;
+    use std::time::Duration;
+
+    // dropped between syncs: the pending wait completes, then fails
+    let (mut my_rendezvous, their_rendezvous) = Rendezvous::new();
+    drop(their_rendezvous);
+    assert_eq!(my_rendezvous.wait_checked(), Ok(()));
+    assert!(my_rendezvous.wait_checked().is_err());
+    assert!(my_rendezvous.wait_checked().is_err());
+
+    // panicking while the other thread is already waiting: no incomplete data
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        let data = their_rendezvous.swap();
+        thread::sleep(Duration::from_millis(20));
+        *data = 1;
+        panic!("incomplete");
+    });
+    assert_eq!(my_rendezvous.swap_checked().copied(), Ok(0));
+    assert!(my_rendezvous.swap_checked().is_err());
+    assert!(my_rendezvous.swap_checked().is_err());
+    assert!(handle.join().is_err());
+
+    // handles taken apart do not leave the pair
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let parts = my_rendezvous.into_parts();
+    let handle = thread::spawn(move || their_rendezvous.wait_checked());
+    my_rendezvous = Rendezvous::from_parts(parts);
+    assert_eq!(my_rendezvous.wait_checked(), Ok(()));
+    assert_eq!(handle.join().unwrap(), Ok(()));
+}
+
+#[test]
+fn test_swap_hooks() {
+    use rendezvous_swap::RendezvousData;
+    use std::cell::RefCell;
+    use std::thread;
+
+    let log = RefCell::new(Vec::new());
+    let (my_rendezvous, their_rendezvous) = RendezvousData::new(10, 20);
+    let mut my_rendezvous = my_rendezvous.with_hooks(
+        |data: &i32| log.borrow_mut().push(("publish", *data)),
+        |data: &i32| log.borrow_mut().push(("receive", *data)),
+    );
+    let handle = thread::spawn(move || {
+        let mut their_rendezvous = their_rendezvous.with_hooks(|_: &i32| {}, |_: &i32| {});
+        *their_rendezvous.swap() = 21;
+        their_rendezvous.swap();
+    });
+    *my_rendezvous.swap() = 11;
+    my_rendezvous.swap();
+    handle.join().unwrap();
+    let _ = my_rendezvous.into_inner();
+    assert_eq!(
+        log.into_inner(),
+        [
+            ("publish", 10),
+            ("receive", 20),
+            ("publish", 11),
+            ("receive", 21)
+        ]
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_timeout() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let start = Instant::now();
+    assert!(my_rendezvous
+        .wait_timeout(Duration::from_millis(20))
+        .is_err());
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    // the retry completes the same sync, and the generations stay in step
+    let handle = thread::spawn(move || {
+        their_rendezvous.wait();
+        their_rendezvous.wait();
+    });
+    while my_rendezvous
+        .wait_timeout(Duration::from_millis(1))
+        .is_err()
+    {}
+    my_rendezvous.wait_timeout(Duration::from_secs(10)).unwrap();
+    handle.join().unwrap();
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let (sender, receiver) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        receiver.recv().unwrap();
+        *their_rendezvous.swap() = 2;
+        *their_rendezvous.swap()
+    });
+    assert!(my_rendezvous.swap_timeout(Duration::ZERO).is_err());
+    sender.send(()).unwrap();
+    *my_rendezvous.swap() = 1;
+    assert_eq!(*my_rendezvous.swap(), 2);
+    assert_eq!(handle.join().unwrap(), 1);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Waker};
+    use std::thread;
+
+    const ITERATIONS: usize = 1000;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let (mut async_side, mut compute_side) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        for i in 1..=ITERATIONS {
+            let data = compute_side.swap();
+            if i > 1 {
+                assert_eq!(*data, i - 1);
+            }
+            *data = i * 2;
+        }
+        compute_side.swap();
+    });
+    runtime.block_on(async {
+        for i in 1..=ITERATIONS {
+            let data = async_side.swap_async().await;
+            if i > 1 {
+                assert_eq!(*data, (i - 1) * 2);
+            }
+            *data = i;
+        }
+        assert_eq!(*async_side.swap_async().await, ITERATIONS * 2);
+    });
+    handle.join().unwrap();
+
+    // dropping a polled future leaves the wait pending, a later wait completes it
+    let (mut async_side, mut compute_side) = Rendezvous::new();
+    {
+        let mut future = pin!(async_side.wait_async());
+        let mut context = Context::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut context).is_pending());
+    }
+    let handle = thread::spawn(move || {
+        compute_side.wait();
+        compute_side.wait();
+    });
+    runtime.block_on(async_side.wait_async());
+    runtime.block_on(async_side.wait_async());
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_rendezvous_data_dyn() {
+    use rendezvous_swap::{RendezvousDataDyn, SwapPoint};
+    use std::thread;
+
+    let (mut host, mut plugin) = RendezvousDataDyn::new(0_u64, [0_u8; 4]);
+    let handle = thread::spawn(move || {
+        assert!(plugin.swap_as::<u64>().is_ok());
+        plugin.set(vec![1, 2, 3]);
+        SwapPoint::swap(&mut plugin);
+        plugin.swap_as::<u64>().map(|data| *data)
+    });
+    assert_eq!(host.swap_as::<[u8; 4]>(), Ok(&mut [0; 4]));
+    *host.current_as::<[u8; 4]>().unwrap() = [1; 4];
+    let data = host.swap();
+    assert_eq!(data.downcast_ref::<Vec<i32>>().unwrap(), &[1, 2, 3]);
+    *data = Box::new(5_u64);
+    let error = host.swap_as::<String>().unwrap_err();
+    assert_eq!(error.expected, "alloc::string::String");
+    assert_eq!(host.current_as::<[u8; 4]>(), Ok(&mut [1; 4]));
+    assert_eq!(handle.join().unwrap(), Ok(5));
+}
+
+#[test]
+fn test_try_wait_and_try_swap() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    assert!(!my_rendezvous.try_wait());
+    assert!(!my_rendezvous.try_wait());
+    let handle = thread::spawn(move || {
+        for _ in 0..100 {
+            their_rendezvous.wait();
+        }
+    });
+    // failed tries must not publish anything, or the counts would drift
+    let mut synced = 0;
+    while synced < 99 {
+        if my_rendezvous.try_wait() {
+            synced += 1;
+        }
+    }
+    my_rendezvous.wait();
+    handle.join().unwrap();
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    assert!(my_rendezvous.try_swap().is_none());
+    let handle = thread::spawn(move || {
+        for i in 1..=100 {
+            *their_rendezvous.swap() = i;
+        }
+        their_rendezvous.swap();
+    });
+    let mut received = Vec::new();
+    while received.len() < 101 {
+        if let Some(data) = my_rendezvous.try_swap() {
+            received.push(*data);
+        }
+    }
+    assert_eq!(received, (0..=100).collect::<Vec<_>>());
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_await_partner() {
+    use rendezvous_swap::{Rendezvous, RendezvousData, StartupError};
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, their_rendezvous) = Rendezvous::new();
+    assert_eq!(
+        my_rendezvous.await_partner(Duration::from_millis(5)),
+        Err(StartupError::NotArrived)
+    );
+    let handle = thread::spawn(move || {
+        let _their_rendezvous = their_rendezvous;
+        panic!("setup failed");
+    });
+    assert!(handle.join().is_err());
+    assert_eq!(
+        my_rendezvous.await_partner(Duration::from_secs(10)),
+        Err(StartupError::Panicked)
+    );
+
+    let (mut my_rendezvous, their_rendezvous) = Rendezvous::new();
+    drop(their_rendezvous);
+    assert_eq!(
+        my_rendezvous.await_partner(Duration::from_secs(10)),
+        Err(StartupError::Dropped)
+    );
+    assert_eq!(
+        my_rendezvous.await_partner(Duration::from_secs(10)),
+        Err(StartupError::Dropped)
+    );
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(1, 2);
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(5));
+        *their_rendezvous.swap()
+    });
+    assert_eq!(
+        my_rendezvous.await_partner(Duration::from_secs(10)),
+        Ok(&mut 2)
+    );
+    assert_eq!(handle.join().unwrap(), 1);
+}
+
+#[test]
+fn test_exchange() {
+    use rendezvous_swap::RendezvousExchange;
+    use std::thread;
+
+    let (mut client, mut server) = RendezvousExchange::<Vec<u32>, u64>::new();
+    let handle = thread::spawn(move || {
+        let mut response = 0;
+        loop {
+            match server.exchange_checked(response) {
+                Ok(request) => response = request.iter().map(|&n| u64::from(n)).sum(),
+                Err(_) => return response,
+            }
+        }
+    });
+    assert_eq!(client.exchange(vec![1, 2]), 0);
+    for n in 0..100 {
+        // responses answer the request of the previous exchange
+        let expected = if n == 0 { 3 } else { u64::from(n - 1) * 3 };
+        assert_eq!(client.exchange(vec![n; 3]), expected);
+    }
+    drop(client);
+    assert_eq!(handle.join().unwrap(), 99 * 3);
+}
+
+#[test]
+fn test_rendezvous_data_ref() {
+    use rendezvous_swap::{RendezvousDataRef, RendezvousDataStorage};
+    use std::thread;
+
+    let mut storage = RendezvousDataStorage::new(vec![1], vec![2]);
+    for _ in 0..3 {
+        let (mut my_rendezvous, mut their_rendezvous) = RendezvousDataRef::new_in(&mut storage);
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                for i in 0..100 {
+                    their_rendezvous.swap().push(i);
+                }
+                // dropped here, arriving at the next swap with its data
+            });
+            for _ in 0..100 {
+                my_rendezvous.swap();
+            }
+            // completes thanks to the dropped handle arriving
+            my_rendezvous.swap();
+        });
+    }
+    let (first, second) = storage.into_inner();
+    assert_eq!(first.len() + second.len(), 302);
+}
+
+// indices stay valid while other pairs come and go between splits
+#[test]
+fn test_pair_arena() {
+    use rendezvous_swap::PairArena;
+    use std::thread;
+
+    let mut arena = PairArena::new();
+    let indices = arena.insert_batch((0..8).map(|i| (i, i)));
+    assert_eq!(indices, (0..8).collect::<Vec<_>>());
+    arena.retain(|index| index % 2 == 0);
+    assert_eq!(arena.len(), 4);
+    // a removed index is reused
+    let index = arena.insert(0, 42);
+    assert!(index % 2 == 1 && arena.contains(index));
+    for round in 0..4 {
+        let (mut my_side, mut their_side) = arena.split();
+        assert!(my_side.get_mut(1).is_none() || index == 1);
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                for (index, handle) in their_side.iter_mut() {
+                    *handle.swap() = index * 10 + round;
+                }
+                for (_, handle) in their_side.iter_mut() {
+                    handle.swap();
+                }
+            });
+            for (_, handle) in my_side.iter_mut() {
+                handle.swap();
+            }
+            let mut seen = 0;
+            for (index, handle) in my_side.iter_mut() {
+                assert_eq!(*handle.swap(), index * 10 + round);
+                seen += 1;
+            }
+            assert_eq!(seen, 5);
+        });
+    }
+    assert_eq!(arena.remove(index), Some((index * 10 + 3, 42)));
+    assert_eq!(arena.remove(index), None);
+    assert_eq!(arena.len(), 4);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_watch() {
+    use rendezvous_swap::Rendezvous;
+    use std::thread;
+    use std::time::Duration;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let mut watch = my_rendezvous.watch();
+    assert_eq!(watch.poll(), 0);
+    assert!(!watch.is_closed());
+    let monitor = thread::spawn(move || {
+        let mut reports = Vec::new();
+        while let Some(generations) = watch.wait(Duration::from_micros(10)) {
+            reports.push(generations);
+        }
+        reports
+    });
+    let handle = thread::spawn(move || {
+        for _ in 0..1000 {
+            their_rendezvous.wait();
+        }
+        // dropped, arriving at generation 1001
+    });
+    for _ in 0..1001 {
+        my_rendezvous.wait();
+    }
+    handle.join().unwrap();
+    let reports = monitor.join().unwrap();
+    assert!(reports.iter().all(|&generations| generations > 0));
+    assert_eq!(reports.iter().sum::<usize>(), 1001);
+}
+
+#[test]
+fn test_static_rendezvous() {
+    use rendezvous_swap::{StaticRendezvous, StaticRendezvousData};
+    use std::thread;
+
+    static RENDEZVOUS: StaticRendezvous = StaticRendezvous::new();
+    static DATA: StaticRendezvousData<u32> = StaticRendezvousData::new(1, 2);
+
+    let (mut my_rendezvous, mut their_rendezvous) = RENDEZVOUS.split().unwrap();
+    let (mut my_data, mut their_data) = DATA.split().unwrap();
+    assert!(RENDEZVOUS.split().is_none());
+    assert!(DATA.split().is_none());
+    let handle = thread::spawn(move || {
+        for _ in 0..100 {
+            their_rendezvous.wait();
+            *their_data.swap() += 1;
+        }
+    });
+    for _ in 0..100 {
+        my_rendezvous.wait();
+        my_data.swap();
+    }
+    handle.join().unwrap();
+    // the other handle arrived at the next swap when it was dropped
+    assert_eq!(*my_data.swap(), 2 + 50);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_run_pair() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::panic;
+
+    let mut received = Vec::new();
+    let ((), sent) = RendezvousData::run_pair(
+        0,
+        0,
+        |mut consumer| {
+            while let Ok(value) = consumer.swap() {
+                received.push(*value);
+            }
+        },
+        |mut producer| {
+            for i in 1..=10 {
+                *producer.swap().unwrap() = i;
+            }
+            10
+        },
+    );
+    assert_eq!(sent, 10);
+    assert_eq!(received, (0..=10).collect::<Vec<_>>());
+
+    // the panic of either closure reaches the caller, the other one is not
+    // left waiting
+    let result = panic::catch_unwind(|| {
+        Rendezvous::run_pair(
+            |mut rendezvous| while rendezvous.wait().is_ok() {},
+            |mut rendezvous| {
+                rendezvous.wait().unwrap();
+                panic!("setup failed");
+            },
+        )
+    });
+    let payload = result.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"setup failed"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_self_test() {
+    let report = rendezvous_swap::self_test();
+    assert!(report.passed(), "{report:?}");
+    assert_eq!(report.swaps, 4096);
+}
+
+#[test]
+fn test_triple_buffer() {
+    use rendezvous_swap::TripleBuffer;
+    use std::thread;
+
+    let (mut writer, mut reader) = TripleBuffer::new([0_u64; 16], [0; 16], [0; 16]);
+    assert!(!reader.has_new());
+    // the writer never waits, even if the reader never reads
+    for i in 1..=10 {
+        *writer.current() = [i; 16];
+        writer.publish();
+    }
+    assert!(reader.has_new());
+    assert_eq!(*reader.latest(), [10; 16]);
+    assert!(!reader.has_new());
+    assert_eq!(*reader.latest(), [10; 16]);
+
+    let handle = thread::spawn(move || {
+        for i in 11..=100_000 {
+            *writer.current() = [i; 16];
+            writer.publish();
+        }
+    });
+    let mut last = 10;
+    while last < 100_000 {
+        let frame = *reader.latest();
+        // never torn, never older than a buffer read before
+        assert!(frame.iter().all(|&value| value == frame[0]));
+        assert!(frame[0] >= last);
+        last = frame[0];
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_spsc() {
+    use rendezvous_swap::spsc::{self, TrySendError};
+    use std::sync::Arc;
+    use std::thread;
+
+    // the sender runs ahead until the channel is full
+    let (mut sender, mut receiver) = spsc::channel(3);
+    for i in 0..3 {
+        sender.try_send(i).unwrap();
+    }
+    assert_eq!(sender.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(receiver.try_recv(), Some(0));
+    sender.try_send(3).unwrap();
+    assert_eq!(
+        (0..4).map(|_| receiver.try_recv()).collect::<Vec<_>>(),
+        [Some(1), Some(2), Some(3), None]
+    );
+
+    let handle = thread::spawn(move || {
+        for i in 0..1000_u64 {
+            sender.send(i).unwrap();
+        }
+    });
+    let mut expected = 0;
+    while let Ok(value) = receiver.recv() {
+        assert_eq!(value, expected);
+        expected += 1;
+    }
+    assert_eq!(expected, 1000);
+    handle.join().unwrap();
+
+    // values left in the channel are dropped with it, sending to a dropped
+    // receiver fails
+    let value = Arc::new(());
+    let (mut sender, receiver) = spsc::channel(2);
+    sender.send(Arc::clone(&value)).unwrap();
+    drop(receiver);
+    assert!(sender.send(Arc::clone(&value)).is_err());
+    drop(sender);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_wait_strategy() {
+    use rendezvous_swap::{Rendezvous, RendezvousData, SpinBackoff, WaitStrategy, YieldAfter};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct Counting(Arc<AtomicU32>);
+    impl WaitStrategy for Counting {
+        fn pause(&self, spins: u32) {
+            self.0.fetch_max(spins, Ordering::Relaxed);
+            thread::yield_now();
+        }
+    }
+
+    // the strategy is called while the other thread has not arrived
+    let pauses = Arc::new(AtomicU32::new(0));
+    let (mut my_rendezvous, mut their_rendezvous) =
+        Rendezvous::new_with_strategy(Counting(Arc::clone(&pauses)));
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        their_rendezvous.wait();
+    });
+    my_rendezvous.wait();
+    handle.join().unwrap();
+    assert!(pauses.load(Ordering::Relaxed) > 0);
+    // and by waits that give up, the dropped handle arrives once more
+    assert!(my_rendezvous.wait_checked().is_ok());
+    assert!(my_rendezvous.wait_checked().is_err());
+
+    for strategy in [0, 1] {
+        let (mut my_rendezvous, mut their_rendezvous) = if strategy == 0 {
+            RendezvousData::new_with_strategy(0, 0, SpinBackoff::new(16))
+        } else {
+            RendezvousData::new_with_strategy(0, 0, YieldAfter::new(10))
+        };
+        let handle = thread::spawn(move || {
+            for i in 1..=100 {
+                *their_rendezvous.swap() = i;
+            }
+        });
+        for i in 0..100 {
+            assert_eq!(*my_rendezvous.swap(), i);
+        }
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_builder() {
+    use core::num::NonZeroUsize;
+    use rendezvous_swap::{Rendezvous, RendezvousData, SpinBackoff};
+    use std::thread;
+
+    let (mut fast, mut slow) = Rendezvous::builder()
+        .wait_strategy(SpinBackoff::new(16))
+        .ratio(NonZeroUsize::new(3).unwrap())
+        .name("fast<->slow")
+        .build_pair();
+    assert_eq!(fast.name(), Some("fast<->slow"));
+    assert!(format!("{slow:?}").contains("fast<->slow"));
+    let handle = thread::spawn(move || {
+        for _ in 0..6 {
+            fast.wait();
+        }
+    });
+    slow.wait();
+    slow.wait();
+    handle.join().unwrap();
+    assert_eq!(Rendezvous::new().0.name(), None);
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::builder()
+        .init_with(|| vec![1], || vec![2])
+        .name("data")
+        .build_pair();
+    assert_eq!(their_rendezvous.name(), Some("data"));
+    let handle = thread::spawn(move || {
+        assert_eq!(*their_rendezvous.swap(), [1]);
+    });
+    assert_eq!(*my_rendezvous.swap(), [2]);
+    handle.join().unwrap();
+}
+
+#[test]
+#[should_panic = "initial values"]
+fn test_builder_without_init() {
+    use rendezvous_swap::RendezvousData;
+
+    let _ = RendezvousData::<i32>::builder().build_pair();
+}
+
+#[test]
+fn test_cache_line_padding() {
+    use rendezvous_swap::CACHE_LINE_PADDING;
+
+    assert!(CACHE_LINE_PADDING.is_power_of_two() && CACHE_LINE_PADDING >= 16);
+    #[cfg(target_arch = "x86_64")]
+    assert_eq!(CACHE_LINE_PADDING, 128);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_poisoning() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![0; 4], vec![0; 4]);
+    let handle = thread::spawn(move || {
+        for i in 1..=3 {
+            their_rendezvous.swap().fill(i);
+        }
+        let data = their_rendezvous.swap();
+        data[..2].fill(4);
+        panic!("panicked while writing");
+    });
+    for i in 0..=3 {
+        assert_eq!(*my_rendezvous.swap_poisoned().unwrap(), vec![i; 4]);
+    }
+    // the buffer the other thread was writing, which held the data it
+    // handed over two swaps earlier
+    let mut error = my_rendezvous.swap_poisoned().unwrap_err();
+    assert_eq!(**error.get_mut(), [4, 4, 2, 2]);
+    assert!(my_rendezvous.is_poisoned());
+    // stays poisoned, with the same buffer
+    assert_eq!(
+        *my_rendezvous.swap_poisoned().unwrap_err().into_inner(),
+        [4, 4, 2, 2]
+    );
+    handle.join().unwrap_err();
+
+    // a handle dropped without panicking does not poison the pair
+    let (mut my_rendezvous, their_rendezvous) = Rendezvous::new();
+    drop(their_rendezvous);
+    my_rendezvous.wait_poisoned().unwrap();
+    assert!(!my_rendezvous.is_poisoned());
+    assert!(my_rendezvous.wait_checked().is_err());
+}
+
+// with more than two slots, either side gets the slot the panicking side was
+// writing, not the one in flight
+#[test]
+#[cfg(feature = "std")]
+fn test_poisoning_slots() {
+    use rendezvous_swap::RendezvousDataN;
+    use std::thread;
+
+    for first_panics in [true, false] {
+        let (first, second) = RendezvousDataN::<_, 3>::new_n([1, 2, 3]);
+        let (mut panicking, mut surviving) = if first_panics {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let handle = thread::spawn(move || {
+            panicking.swap();
+            *panicking.swap() = 9;
+            panic!("panicked while writing");
+        });
+        surviving.swap_poisoned().unwrap();
+        surviving.swap_poisoned().unwrap();
+        let mut error = surviving.swap_poisoned().unwrap_err();
+        assert_eq!(**error.get_mut(), 9, "first panics: {first_panics}");
+        handle.join().unwrap_err();
+    }
+}
+
+#[test]
+fn test_non_sync_payload() {
+    use rendezvous_swap::RendezvousData;
+    use std::cell::{Cell, RefCell};
+    use std::thread;
+
+    // only one thread accesses a buffer at a time, so payloads need not be
+    // `Sync`
+    struct Frame {
+        count: Cell<u64>,
+        log: RefCell<Vec<u64>>,
+    }
+    let frame = || Frame {
+        count: Cell::new(0),
+        log: RefCell::new(Vec::new()),
+    };
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(frame(), frame());
+    let handle = thread::spawn(move || {
+        for i in 1..=10 {
+            let frame = their_rendezvous.swap();
+            frame.count.set(i);
+            frame.log.borrow_mut().push(i);
+        }
+    });
+    for i in 0..10 {
+        let frame = my_rendezvous.swap();
+        assert_eq!(frame.count.get(), i);
+    }
+    let frame = my_rendezvous.swap();
+    assert_eq!(frame.count.get(), 10);
+    assert_eq!(frame.log.borrow().last(), Some(&10));
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_rendezvous_slices() {
+    use rendezvous_swap::{RendezvousData, SwapPoint};
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new_slices(1000, |i| i);
+    assert_eq!(my_rendezvous.len(), 1000);
+    assert_eq!(my_rendezvous.current_mut()[999], 999);
+    let handle = thread::spawn(move || {
+        for round in 1..=10 {
+            let slice = SwapPoint::swap(&mut their_rendezvous);
+            slice.iter_mut().for_each(|value| *value = round);
+        }
+    });
+    assert_eq!(my_rendezvous.swap()[10], 10);
+    for round in 1..=10 {
+        assert!(my_rendezvous.swap().iter().all(|&value| value == round));
+    }
+    handle.join().unwrap();
+    assert!(my_rendezvous.swap_checked().is_err());
+}
+
+#[test]
+fn test_new_with_and_new_zeroed() {
+    use rendezvous_swap::{RendezvousData, RendezvousDataN, Zeroable};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::thread;
+
+    let mut calls = 0;
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousDataN::<_, 3>::new_with(|| {
+        calls += 1;
+        vec![calls; 4]
+    });
+    assert_eq!(calls, 3);
+    let handle = thread::spawn(move || their_rendezvous.swap().clone());
+    assert_eq!(*my_rendezvous.swap(), [2; 4]);
+    assert_eq!(handle.join().unwrap(), [3; 4]);
+
+    #[derive(Debug)]
+    struct Stats {
+        hits: [AtomicU32; 8],
+        total: u64,
+    }
+    // SAFETY: all fields are zeroable
+    unsafe impl Zeroable for Stats {}
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::<Stats>::new_zeroed();
+    let handle = thread::spawn(move || {
+        let stats = their_rendezvous.swap();
+        stats.hits[3].store(1, Ordering::Relaxed);
+        stats.total = 1;
+        their_rendezvous.swap();
+    });
+    assert_eq!(my_rendezvous.swap().total, 0);
+    let stats = my_rendezvous.swap();
+    assert_eq!((stats.hits[3].load(Ordering::Relaxed), stats.total), (1, 1));
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_introspection() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    assert_eq!(my_rendezvous.generation(), 0);
+    assert!(!my_rendezvous.is_peer_waiting());
+    let handle = thread::spawn(move || {
+        their_rendezvous.wait();
+        their_rendezvous.wait();
+        their_rendezvous.generation()
+    });
+    my_rendezvous.wait();
+    assert_eq!(my_rendezvous.generation(), 1);
+    // The other thread arrives at the second sync point and waits there
+    while !my_rendezvous.is_peer_waiting() {
+        thread::yield_now();
+    }
+    my_rendezvous.wait();
+    assert_eq!(handle.join().unwrap(), my_rendezvous.generation());
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(1_u32, 2);
+    assert!(!their_rendezvous.is_peer_waiting());
+    let handle = thread::spawn(move || {
+        their_rendezvous.swap();
+        their_rendezvous
+    });
+    while !my_rendezvous.is_peer_waiting() {
+        thread::yield_now();
+    }
+    assert_eq!(*my_rendezvous.swap(), 2);
+    let their_rendezvous = handle.join().unwrap();
+    assert_eq!(
+        (my_rendezvous.generation(), their_rendezvous.generation()),
+        (1, 1)
+    );
+}
+
+#[test]
+fn test_leader_election() {
+    use rendezvous_swap::{Rendezvous, RendezvousData};
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+    let handle = thread::spawn(move || {
+        (0..10)
+            .map(|_| their_rendezvous.wait_leader().is_leader())
+            .collect::<Vec<_>>()
+    });
+    let mine: Vec<_> = (0..10)
+        .map(|_| my_rendezvous.wait_leader().is_leader())
+        .collect();
+    let theirs = handle.join().unwrap();
+    assert!(mine.iter().zip(&theirs).all(|(me, them)| me != them));
+    assert!(mine.windows(2).all(|pair| pair[0] != pair[1]));
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+    let handle = thread::spawn(move || {
+        let (data, result) = their_rendezvous.swap_leader();
+        *data = 1;
+        (
+            result.is_leader(),
+            their_rendezvous.swap_leader().1.is_leader(),
+        )
+    });
+    let first = my_rendezvous.swap_leader().1.is_leader();
+    let (data, second) = my_rendezvous.swap_leader();
+    assert_eq!(*data, 1);
+    assert_eq!((first, second.is_leader()), (true, false));
+    assert_eq!(handle.join().unwrap(), (false, true));
+}
+
+#[test]
+fn test_into_inner() {
+    use rendezvous_swap::RendezvousData;
+    use std::thread;
+
+    let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(vec![0], vec![0]);
+    let handle = thread::spawn(move || {
+        for round in 1..=3 {
+            their_rendezvous.swap().push(round);
+        }
+        their_rendezvous
+    });
+    for _ in 1..=3 {
+        my_rendezvous.swap().push(0);
+    }
+    let their_rendezvous = handle.join().unwrap();
+    // The buffers change hands at every swap, so both threads pushed to both.
+    let (theirs, mine) = their_rendezvous.into_inner(my_rendezvous);
+    assert_eq!((theirs, mine), (vec![0, 1, 0, 3], vec![0, 0, 2, 0]));
+}
+
+#[test]
+fn test_rendezvous_counters_padded() {
+    use rendezvous_swap::{Rendezvous, CACHE_LINE_PADDING};
+
+    let (my_rendezvous, their_rendezvous) = Rendezvous::new();
+    let (mine, theirs) = my_rendezvous.counter_ptrs();
+    assert_eq!(their_rendezvous.counter_ptrs(), (theirs, mine));
+    assert!((mine as usize).abs_diff(theirs as usize) >= CACHE_LINE_PADDING);
+    assert!(Rendezvous::same_pair(&my_rendezvous, &their_rendezvous));
+    assert!(!Rendezvous::same_pair(&my_rendezvous, &my_rendezvous));
+}
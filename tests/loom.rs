@@ -0,0 +1,81 @@
+//! Model checking of the pair protocol with [loom](https://docs.rs/loom),
+//! which runs every test under every interleaving (and every ordering the
+//! memory model allows) of the two threads.
+//!
+//! Run with
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+#![cfg(loom)]
+
+use loom::cell::UnsafeCell;
+use loom::sync::Arc;
+use loom::thread;
+use rendezvous_swap::{Rendezvous, RendezvousData, RendezvousDataN};
+
+/// Data shared between the threads through plain (non-atomic) accesses,
+/// ordered only by the pair.
+struct Shared(UnsafeCell<usize>);
+// SAFETY: the tests only access the cell in turns, ordered by a pair, and
+// loom reports any access that is not.
+unsafe impl Sync for Shared {}
+
+#[test]
+fn loom_wait_orders_memory() {
+    loom::model(|| {
+        let data = Arc::new(Shared(UnsafeCell::new(0)));
+        let (mut my_rendezvous, mut their_rendezvous) = Rendezvous::new();
+        let their_data = Arc::clone(&data);
+        let handle = thread::spawn(move || {
+            // SAFETY: the other thread only accesses the data after the wait
+            their_data.0.with_mut(|value| unsafe { *value = 1 });
+            their_rendezvous.wait();
+        });
+        my_rendezvous.wait();
+        // SAFETY: the other thread only accessed the data before the wait
+        assert_eq!(data.0.with(|value| unsafe { *value }), 1);
+        handle.join().unwrap();
+    });
+}
+
+#[test]
+fn loom_swap_hands_over_data() {
+    loom::model(|| {
+        let (mut my_rendezvous, mut their_rendezvous) = RendezvousData::new(0, 0);
+        let handle = thread::spawn(move || {
+            *their_rendezvous.swap() = 1;
+            *their_rendezvous.swap()
+        });
+        let data = my_rendezvous.swap();
+        assert_eq!(*data, 0);
+        *data = 2;
+        assert_eq!(*my_rendezvous.swap(), 1);
+        assert_eq!(handle.join().unwrap(), 2);
+    });
+}
+
+#[test]
+fn loom_swap_three_slots() {
+    loom::model(|| {
+        let (mut my_rendezvous, mut their_rendezvous) = RendezvousDataN::new_n([0, 0, 0]);
+        let handle = thread::spawn(move || {
+            *their_rendezvous.swap() = 1;
+            *their_rendezvous.swap() = 2;
+        });
+        *my_rendezvous.swap() = 3;
+        *my_rendezvous.swap() = 4;
+        handle.join().unwrap();
+    });
+}
+
+#[test]
+fn loom_drop_disconnects() {
+    loom::model(|| {
+        let (mut my_rendezvous, their_rendezvous) = RendezvousData::new(0, 0);
+        let handle = thread::spawn(move || drop(their_rendezvous));
+        // The dropped handle arrives at one more swap.
+        assert!(my_rendezvous.swap_checked().is_ok());
+        assert!(my_rendezvous.swap_checked().is_err());
+        handle.join().unwrap();
+    });
+}